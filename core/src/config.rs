@@ -0,0 +1,141 @@
+use std::{env, path::PathBuf, str::FromStr, time::Duration};
+
+use crate::{
+	errors::{MediaOrderError, Result},
+	organise::{PlaceMode, RenameTemplate},
+};
+
+/// Reads and parses environment variable, `default` is used when variable is not set.
+pub(crate) fn env_or<T: FromStr>(name: &str, default: T) -> Result<T> {
+	match env::var(name) {
+		Ok(value) => value.parse().map_err(|_| MediaOrderError::ConfigError(name.to_owned(), value)),
+		Err(_) => Ok(default),
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct ScanConfig {
+	/// Max number of paths read at the same time.
+	pub workers: usize,
+	/// Max number of paths read at the same time from one device (disk).
+	pub device_workers: usize,
+	/// Max number of scanned entries waiting for the consumer.
+	pub queue_size: usize,
+	pub progress_interval: Duration,
+}
+
+impl Default for ScanConfig {
+	fn default() -> Self {
+		Self {
+			workers: std::thread::available_parallelism().map(|workers| workers.get()).unwrap_or(4),
+			device_workers: 2,
+			queue_size: 256,
+			progress_interval: Duration::from_secs(5),
+		}
+	}
+}
+
+impl ScanConfig {
+	pub fn from_env() -> Result<Self> {
+		let default = Self::default();
+		Ok(Self {
+			workers: env_or("SCAN_WORKERS", default.workers)?.max(1),
+			device_workers: env_or("SCAN_DEVICE_WORKERS", default.device_workers)?.max(1),
+			queue_size: env_or("SCAN_QUEUE_SIZE", default.queue_size)?.max(1),
+			progress_interval: Duration::from_secs(env_or(
+				"SCAN_PROGRESS_INTERVAL",
+				default.progress_interval.as_secs(),
+			)?),
+		})
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct WatchConfig {
+	/// Time without changes after which file is considered written completely.
+	pub settle: Duration,
+}
+
+impl Default for WatchConfig {
+	fn default() -> Self {
+		Self {
+			settle: Duration::from_secs(10),
+		}
+	}
+}
+
+impl WatchConfig {
+	pub fn from_env() -> Result<Self> {
+		Ok(Self {
+			settle: Duration::from_secs(env_or("WATCH_SETTLE", Self::default().settle.as_secs())?),
+		})
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct OrganiseConfig {
+	/// Root folder of the organised library.
+	pub root: PathBuf,
+	pub template: RenameTemplate,
+	pub mode: PlaceMode,
+}
+
+impl OrganiseConfig {
+	pub fn new(root: PathBuf) -> Self {
+		Self {
+			root,
+			template: RenameTemplate::default(),
+			mode: PlaceMode::default(),
+		}
+	}
+
+	pub fn from_env() -> Result<Self> {
+		let root = env::var("ORGANISE_PATH")
+			.map_err(|_| MediaOrderError::ConfigError("ORGANISE_PATH".to_owned(), String::new()))?;
+		let default = Self::new(PathBuf::from(root));
+		Ok(Self {
+			template: env_or("RENAME_TEMPLATE", default.template.clone())?,
+			mode: env_or("ORGANISE_MODE", default.mode)?,
+			..default
+		})
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HookConfig {
+	/// Torrent client categories or tags handled by the hook, all downloads are handled when empty.
+	pub categories: Vec<String>,
+}
+
+impl HookConfig {
+	pub fn from_env() -> Result<Self> {
+		Ok(Self {
+			categories: env::var("HOOK_CATEGORIES")
+				.map(|value| {
+					value
+						.split(',')
+						.map(str::trim)
+						.filter(|category| !category.is_empty())
+						.map(String::from)
+						.collect()
+				})
+				.unwrap_or_default(),
+		})
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ViewConfig {
+	/// Root folder of the organised view, the view is not made when it is not set.
+	pub root: Option<PathBuf>,
+	pub template: RenameTemplate,
+}
+
+impl ViewConfig {
+	pub fn from_env() -> Result<Self> {
+		Ok(Self {
+			root: env::var("VIEW_PATH").ok().map(PathBuf::from),
+			template: env_or("RENAME_TEMPLATE", RenameTemplate::default())?,
+		})
+	}
+}
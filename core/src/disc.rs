@@ -0,0 +1,266 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use file_format::FileFormat;
+use log::debug;
+
+use crate::{
+	errors::{MediaOrderError, Result},
+	nfo::NfoHint,
+	video::Video,
+};
+
+const DVD_SECTOR: usize = 2048;
+const BLURAY_CLOCK: f64 = 45000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiscKind {
+	Dvd,
+	BluRay,
+	Iso,
+}
+
+/// DVD/Blu-ray folder structure or ISO image recognised as a single title.
+#[derive(Debug)]
+pub struct Disc {
+	pub root: PathBuf,
+	pub kind: DiscKind,
+	/// Title set IFO (DVD) or playlist (Blu-ray) of the main feature.
+	pub main_feature: Option<PathBuf>,
+	/// Main feature duration in seconds.
+	pub duration: Option<f64>,
+	/// Stream files of the main feature in playback order.
+	pub streams: Vec<PathBuf>,
+	/// Main feature video, named after the disc folder.
+	pub video: Video,
+}
+
+impl Disc {
+	/// Recognises `VIDEO_TS` or `BDMV` layout in the folder (or the folder itself being one of them).
+	pub fn from_folder(path: &Path) -> Result<Option<Disc>> {
+		let (root, kind, content) = if let Some(content) = disc_content(path, "VIDEO_TS") {
+			(disc_root(path, &content), DiscKind::Dvd, content)
+		} else if let Some(content) = disc_content(path, "BDMV") {
+			(disc_root(path, &content), DiscKind::BluRay, content)
+		} else {
+			return Ok(None);
+		};
+
+		let (main_feature, duration, streams) = match kind {
+			DiscKind::Dvd => dvd_main_feature(&content),
+			_ => bluray_main_feature(&content),
+		};
+
+		let stream = streams.first().cloned().unwrap_or_else(|| root.clone());
+		let format = FileFormat::from_file(&stream).unwrap_or(FileFormat::ArbitraryBinaryData);
+		let video = disc_video(&root, stream, format)?;
+
+		Ok(Some(Disc {
+			root,
+			kind,
+			main_feature,
+			duration,
+			streams,
+			video,
+		}))
+	}
+
+	/// Disc folder of the stream file path, i.e. the folder containing `VIDEO_TS` or `BDMV` on the path.
+	pub fn root_of(path: &Path) -> Option<&Path> {
+		path.ancestors()
+			.find(|ancestor| is_named(ancestor, "VIDEO_TS") || is_named(ancestor, "BDMV"))
+			.and_then(Path::parent)
+	}
+
+	pub fn from_iso(path: PathBuf) -> Result<Disc> {
+		let video = disc_video(&path, path.clone(), FileFormat::Iso9660)?;
+
+		Ok(Disc {
+			root: path,
+			kind: DiscKind::Iso,
+			main_feature: None,
+			duration: None,
+			streams: vec![],
+			video,
+		})
+	}
+}
+
+fn disc_video(root: &Path, stream: PathBuf, format: FileFormat) -> Result<Video> {
+	let name = root
+		.file_name()
+		.ok_or_else(|| MediaOrderError::FilePathError(root.to_path_buf()))?
+		.to_os_string()
+		.into_string()?;
+
+	let mut video = Video::new(stream, format);
+	if format != FileFormat::Iso9660 && video.read_ffmpeg_content().is_err() {
+		debug!("Can't read main feature metadata of disc {:?}", root);
+	}
+	video.parse_name(&name)?;
+	if let Some(hint) = NfoHint::find(&video) {
+		hint.apply(&mut video);
+	}
+	Ok(video)
+}
+
+fn find_child(dir: &Path, name: &str) -> Option<PathBuf> {
+	fs::read_dir(dir)
+		.ok()?
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.find(|path| is_named(path, name))
+}
+
+fn is_named(path: &Path, name: &str) -> bool {
+	path.file_name()
+		.and_then(|file_name| file_name.to_str())
+		.is_some_and(|file_name| file_name.eq_ignore_ascii_case(name))
+}
+
+fn disc_content(path: &Path, name: &str) -> Option<PathBuf> {
+	if is_named(path, name) {
+		Some(path.to_path_buf())
+	} else {
+		find_child(path, name).filter(|content| content.is_dir())
+	}
+}
+
+fn disc_root(path: &Path, content: &Path) -> PathBuf {
+	if path == content {
+		path.parent().map(PathBuf::from).unwrap_or_else(|| path.to_path_buf())
+	} else {
+		path.to_path_buf()
+	}
+}
+
+fn be_u16(data: &[u8], offset: usize) -> Option<u16> {
+	Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+	Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn sorted_files(dir: &Path) -> Vec<PathBuf> {
+	let mut files: Vec<PathBuf> = fs::read_dir(dir)
+		.map(|entries| entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect())
+		.unwrap_or_default();
+	files.sort();
+	files
+}
+
+fn file_size(path: &Path) -> u64 {
+	fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+fn from_bcd(byte: u8) -> f64 {
+	f64::from((byte >> 4) * 10 + (byte & 0x0f))
+}
+
+/// DVD playback time: BCD coded hours, minutes, seconds and frames, frame rate in two high bits of the last
+/// byte.
+fn dvd_time(time: &[u8]) -> f64 {
+	let fps = if time[3] >> 6 == 0b11 { 30.0 } else { 25.0 };
+	from_bcd(time[0]) * 3600.0 + from_bcd(time[1]) * 60.0 + from_bcd(time[2]) + from_bcd(time[3] & 0x3f) / fps
+}
+
+/// Duration of the longest program chain of the title set.
+pub(crate) fn dvd_title_set_duration(data: &[u8]) -> Option<f64> {
+	if data.get(0..12)? != b"DVDVIDEO-VTS" {
+		return None;
+	}
+
+	let pgci = be_u32(data, 0xCC)? as usize * DVD_SECTOR;
+	let count = be_u16(data, pgci)? as usize;
+
+	(0..count)
+		.filter_map(|i| {
+			let pgc = pgci + be_u32(data, pgci + 8 + i * 8 + 4)? as usize;
+			data.get(pgc + 4..pgc + 8).map(dvd_time)
+		})
+		.max_by(f64::total_cmp)
+}
+
+/// Title set with the longest program chain (or the biggest VOBs when IFO can't be read) is the main feature.
+fn dvd_main_feature(video_ts: &Path) -> (Option<PathBuf>, Option<f64>, Vec<PathBuf>) {
+	let files = sorted_files(video_ts);
+	let file_name =
+		|path: &PathBuf| path.file_name().and_then(|name| name.to_str()).unwrap_or("").to_uppercase();
+
+	let title_sets = files.iter().filter_map(|ifo| {
+		let name = file_name(ifo);
+		let set = name.strip_prefix("VTS_")?.strip_suffix("_0.IFO")?.to_owned();
+		let vobs: Vec<PathBuf> = files
+			.iter()
+			.filter(|vob| {
+				let name = file_name(vob);
+				name.starts_with(&format!("VTS_{set}_"))
+					&& name.ends_with(".VOB")
+					&& !name.ends_with("_0.VOB")
+			})
+			.cloned()
+			.collect();
+		let duration = fs::read(ifo).ok().and_then(|data| dvd_title_set_duration(&data));
+		let size: u64 = vobs.iter().map(|vob| file_size(vob)).sum();
+		Some((ifo.clone(), duration, size, vobs))
+	});
+
+	match title_sets.max_by(|a, b| a.1.unwrap_or(0.0).total_cmp(&b.1.unwrap_or(0.0)).then(a.2.cmp(&b.2))) {
+		Some((ifo, duration, _, vobs)) => (Some(ifo), duration, vobs),
+		None => (None, None, vec![]),
+	}
+}
+
+/// Total duration and clip names of the playlist play items.
+pub(crate) fn bluray_playlist(data: &[u8]) -> Option<(f64, Vec<String>)> {
+	if data.get(0..4)? != b"MPLS" {
+		return None;
+	}
+
+	let playlist = be_u32(data, 8)? as usize;
+	let count = be_u16(data, playlist + 6)?;
+	let mut offset = playlist + 10;
+	let mut duration = 0.0;
+	let mut clips = vec![];
+
+	for _ in 0..count {
+		let length = be_u16(data, offset)? as usize;
+		let clip = std::str::from_utf8(data.get(offset + 2..offset + 7)?).ok()?;
+		let in_time = be_u32(data, offset + 14)?;
+		let out_time = be_u32(data, offset + 18)?;
+		duration += f64::from(out_time.saturating_sub(in_time)) / BLURAY_CLOCK;
+		clips.push(clip.to_owned());
+		offset += length + 2;
+	}
+
+	Some((duration, clips))
+}
+
+/// The longest playlist is the main feature, the biggest stream is used when there are no playlists.
+fn bluray_main_feature(bdmv: &Path) -> (Option<PathBuf>, Option<f64>, Vec<PathBuf>) {
+	let stream_dir = find_child(bdmv, "STREAM").unwrap_or_else(|| bdmv.join("STREAM"));
+	let playlist = find_child(bdmv, "PLAYLIST")
+		.map(|dir| sorted_files(&dir))
+		.unwrap_or_default()
+		.into_iter()
+		.filter_map(|mpls| {
+			fs::read(&mpls)
+				.ok()
+				.and_then(|data| bluray_playlist(&data))
+				.map(|list| (mpls, list))
+		})
+		.max_by(|a, b| a.1 .0.total_cmp(&b.1 .0));
+
+	match playlist {
+		Some((mpls, (duration, clips))) => {
+			let streams = clips.iter().map(|clip| stream_dir.join(format!("{clip}.m2ts"))).collect();
+			(Some(mpls), Some(duration), streams)
+		}
+		None => {
+			let biggest = sorted_files(&stream_dir).into_iter().max_by_key(|stream| file_size(stream));
+			(None, None, biggest.into_iter().collect())
+		}
+	}
+}
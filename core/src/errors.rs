@@ -4,14 +4,38 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum MediaOrderError {
-	#[error("Can't read video metadata")]
-	VideoMetadata,
+	#[error("Can't read video metadata: {0}")]
+	VideoMetadata(String),
 	#[error(transparent)]
 	JoinError(#[from] tokio::task::JoinError),
 	#[error("File path error {0}")]
 	FilePathError(PathBuf),
 	#[error("Error converting filename from OsString {:?} to String", 0)]
 	OsStringError(OsString),
+	#[error("Invalid config value {0}={1}")]
+	ConfigError(String, String),
+	#[error("Can't parse {0} from {1:?}")]
+	ParseError(String, String),
+	#[error("Target path {0:?} already exists")]
+	TargetExistsError(PathBuf),
+	#[error("Can't write metadata to {0:?}, only MKV and MP4 containers are supported")]
+	UnsupportedContainerError(PathBuf),
+	#[error("Remuxed {0:?} doesn't match the source: {1}")]
+	MetadataVerifyError(PathBuf, String),
+	#[error("ffmpeg error: {0}")]
+	FfmpegError(#[from] ffmpeg_the_third::Error),
+	#[error(transparent)]
+	DatabaseError(#[from] sea_orm::DbErr),
+	#[error(transparent)]
+	IoError(#[from] std::io::Error),
+	#[error(transparent)]
+	WatchError(#[from] notify::Error),
+	#[error(transparent)]
+	XmlError(#[from] quick_xml::Error),
+	#[error(transparent)]
+	TomlError(#[from] toml::de::Error),
+	#[error(transparent)]
+	ImdbError(#[from] media_order_imdb::errors::ImdbError),
 }
 
 impl From<OsString> for MediaOrderError {
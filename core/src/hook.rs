@@ -0,0 +1,222 @@
+use std::path::PathBuf;
+
+use log::{error, info, warn};
+use media_order_imdb::provider::MetadataProvider;
+
+use crate::{
+	config::{HookConfig, OrganiseConfig, ScanConfig},
+	disc::Disc,
+	errors::{MediaOrderError, Result},
+	identify::{identify, is_identifiable},
+	library::Library,
+	media::group_parts,
+	organise::{place, plan, Placement},
+	rules::ScanRules,
+	video::Video,
+	view::OrganisedView,
+};
+
+/// Exit status of the post-download hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookStatus {
+	/// All media files of the download are placed into the library.
+	Placed = 0,
+	/// Scan or placement failed.
+	Failed = 1,
+	/// No media files found in the download.
+	NoMedia = 2,
+	/// Some files are not placed as their targets already exist, nothing is overwritten.
+	Conflict = 3,
+	/// Download category and tags are not handled by the hook.
+	Skipped = 4,
+}
+
+impl HookStatus {
+	pub fn code(self) -> i32 {
+		self as i32
+	}
+}
+
+/// One-shot placement of the completed download into the organised library, to be run by the torrent client
+/// on download completion.
+pub struct Hook<'a> {
+	library: &'a Library,
+	config: OrganiseConfig,
+	hook_config: HookConfig,
+	scan_config: ScanConfig,
+	rules: ScanRules,
+	provider: Option<&'a dyn MetadataProvider>,
+	view: Option<OrganisedView>,
+}
+
+/// Moves the video path to the target of its placement (the disc folder or the file itself).
+fn relocate(video: &mut Video, placed: &[Placement]) -> bool {
+	let Some(placement) = placed.iter().find(|placement| video.path.starts_with(&placement.source)) else {
+		return false;
+	};
+	video.path = match video.path.strip_prefix(&placement.source) {
+		Ok(inner) if !inner.as_os_str().is_empty() => placement.target.join(inner),
+		_ => placement.target.clone(),
+	};
+	true
+}
+
+impl<'a> Hook<'a> {
+	pub fn new(library: &'a Library, config: OrganiseConfig) -> Self {
+		Self {
+			library,
+			config,
+			hook_config: HookConfig::default(),
+			scan_config: ScanConfig::default(),
+			rules: ScanRules::default(),
+			provider: None,
+			view: None,
+		}
+	}
+
+	pub fn with_hook_config(mut self, hook_config: HookConfig) -> Self {
+		self.hook_config = hook_config;
+		self
+	}
+
+	pub fn with_scan(mut self, scan_config: ScanConfig, rules: ScanRules) -> Self {
+		self.scan_config = scan_config;
+		self.rules = rules;
+		self
+	}
+
+	/// Identifies the items like the `identify` command before they are placed.
+	pub fn with_provider(mut self, provider: &'a dyn MetadataProvider) -> Self {
+		self.provider = Some(provider);
+		self
+	}
+
+	/// Links the placed items into the view.
+	pub fn with_view(mut self, view: OrganisedView) -> Self {
+		self.view = Some(view);
+		self
+	}
+
+	fn handles(&self, category: Option<&str>, tags: &[String]) -> bool {
+		self.hook_config.categories.is_empty()
+			|| category.into_iter().chain(tags.iter().map(String::as_str)).any(|category| {
+				self.hook_config
+					.categories
+					.iter()
+					.any(|handled| handled.eq_ignore_ascii_case(category))
+			})
+	}
+
+	/// Scans the download path (file or folder), groups found videos into items, identifies them and places
+	/// them into the library by the template. The records follow the placed files, none are kept for the
+	/// download itself.
+	pub async fn run(&self, path: PathBuf, category: Option<&str>, tags: &[String]) -> HookStatus {
+		match self.place_download(path, category, tags).await {
+			Ok(status) => status,
+			Err(err) => {
+				error!("{err}");
+				HookStatus::Failed
+			}
+		}
+	}
+
+	async fn place_download(
+		&self,
+		path: PathBuf,
+		category: Option<&str>,
+		tags: &[String],
+	) -> Result<HookStatus> {
+		if !self.handles(category, tags) {
+			info!("Skipping {:?}: category {:?}, tags {:?} are not handled", path, category, tags);
+			return Ok(HookStatus::Skipped);
+		}
+
+		// missing download is a failure rather than no media
+		tokio::fs::metadata(&path).await?;
+		let (progress, videos) = self
+			.library
+			.scan(path.clone(), self.scan_config.clone(), self.rules.clone())
+			.await?;
+		info!("Scanned {:?}: {}", path, progress);
+
+		let mut items = group_parts(videos);
+		if items.is_empty() {
+			warn!("No media found in {:?}", path);
+			self.library.remove(&path).await?;
+			return Ok(HookStatus::NoMedia);
+		}
+
+		if let Some(provider) = self.provider {
+			let mut overrides = self.library.override_cache().await?;
+			for item in items.iter_mut().filter(|item| !item.is_extra()) {
+				let record_path = Disc::root_of(&item.main().path).unwrap_or(&item.main().path).to_path_buf();
+				let Some(record) = self.library.get(&record_path).await?.filter(is_identifiable) else {
+					continue;
+				};
+				// the unidentified item is placed by the parsed name
+				let title = match identify(provider, &record).await {
+					Ok(Some(title)) => title,
+					Ok(None) => continue,
+					Err(err) => {
+						warn!("Can't identify {:?}: {err}", record_path);
+						continue;
+					}
+				};
+				if self.library.set_title(&record_path, &title, &mut overrides).await? {
+					for video in item.videos.iter_mut().chain(item.extras.iter_mut()) {
+						title.apply(video);
+					}
+				}
+			}
+		}
+
+		let placements: Vec<_> = items
+			.iter()
+			.flat_map(|item| plan(item, &self.config.root, &self.config.template, category))
+			.collect();
+		let mode = self.config.mode;
+		let placed = tokio::task::spawn_blocking(move || {
+			placements
+				.into_iter()
+				.map(|placement| (place(&placement, mode), placement))
+				.collect::<Vec<_>>()
+		})
+		.await?;
+
+		let mut status = HookStatus::Placed;
+		let mut done = vec![];
+		for (result, placement) in placed {
+			match result {
+				Ok(()) => {
+					self.library.rename(&placement.source, &placement.target).await?;
+					done.push(placement);
+				}
+				Err(MediaOrderError::TargetExistsError(target)) => {
+					warn!("Not placing {:?}: {:?} already exists", placement.source, target);
+					if status == HookStatus::Placed {
+						status = HookStatus::Conflict;
+					}
+				}
+				Err(err) => {
+					error!("Can't place {:?}: {err}", placement.source);
+					status = HookStatus::Failed;
+				}
+			}
+		}
+		// the files left in the download are not the library ones
+		self.library.remove(&path).await?;
+
+		if let Some(view) = &self.view {
+			// the items are linked from their placed files, the ones not placed whole are left out
+			items.retain_mut(|item| item.videos.iter_mut().all(|video| relocate(video, &done)));
+			for item in &mut items {
+				item.extras.retain_mut(|extra| relocate(extra, &done));
+			}
+			// the links to the moved download files are pruned
+			if let Err(err) = view.sync_under(&items, &path) {
+				error!("Can't link {:?} into the view: {err}", path);
+			}
+		}
+		Ok(status)
+	}
+}
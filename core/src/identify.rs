@@ -0,0 +1,64 @@
+use log::{debug, info};
+use media_order_imdb::provider::{MetadataProvider, TitleMetadata};
+
+use crate::{
+	errors::Result,
+	library::{entities::files, KIND_DISC, KIND_VIDEO},
+	media::TitleInfo,
+};
+
+/// Least similarity of the found title to take it without the IMDb id, lower ones are left unidentified.
+pub const MIN_SCORE: f64 = 0.8;
+/// Titles asked from the provider for one record.
+const CANDIDATES: usize = 5;
+const SERIES_TYPES: [&str; 2] = ["tvSeries", "tvMiniSeries"];
+
+impl From<TitleMetadata> for TitleInfo {
+	fn from(found: TitleMetadata) -> Self {
+		TitleInfo {
+			imdb_id: found.tconst,
+			title: found.title,
+			original_title: found.original_title,
+			year: found.year,
+			genres: found.genres,
+			runtime: None,
+			rating: found.rating,
+		}
+	}
+}
+
+/// Whether the record is a main feature (or an episode) worth identifying.
+pub fn is_identifiable(record: &files::Model) -> bool {
+	(record.kind == KIND_VIDEO || record.kind == KIND_DISC)
+		&& record.role.as_deref().is_none_or(|role| role == "main")
+		&& record.name.as_deref().is_some_and(|name| !name.is_empty())
+}
+
+/// Title of the library record: looked up by the IMDb id known from the NFO file or the override, searched by
+/// the parsed name and year otherwise. Episodes are identified as their series.
+pub async fn identify(provider: &dyn MetadataProvider, record: &files::Model) -> Result<Option<TitleInfo>> {
+	if let Some(imdb_id) = &record.imdb_id {
+		return Ok(provider.get(imdb_id).await?.map(TitleInfo::from));
+	}
+	let Some(name) = record.name.as_deref() else {
+		return Ok(None);
+	};
+	let is_episode = record.season.is_some() || record.episode.is_some();
+	// series are searched by the name only, the year of the file is the episode one
+	let year = record.year.and_then(|year| u16::try_from(year).ok()).filter(|_| !is_episode);
+
+	let found = provider
+		.search(name, year, CANDIDATES)
+		.await?
+		.into_iter()
+		.filter(|found| found.score >= MIN_SCORE)
+		.find(|found| {
+			let is_series = found.title_type.as_deref().is_some_and(|kind| SERIES_TYPES.contains(&kind));
+			found.title_type.is_none() || is_series == is_episode
+		});
+	match &found {
+		Some(found) => debug!("{:?} is {} ({:.2})", record.path, found.tconst, found.score),
+		None => info!("{:?} ({name}) is not identified", record.path),
+	}
+	Ok(found.map(TitleInfo::from))
+}
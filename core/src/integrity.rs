@@ -0,0 +1,275 @@
+extern crate ffmpeg_the_third as ffmpeg;
+
+use std::{collections::HashMap, fmt, fs, path::Path, str::FromStr};
+
+use ffmpeg::{codec, decoder, format, media, util::error::EAGAIN, Error, Frame, Packet};
+use log::debug;
+
+use crate::errors::{MediaOrderError, Result};
+
+/// File smaller than this part of the size expected by the bit rate and duration is truncated.
+const TRUNCATED_RATIO: f64 = 0.9;
+/// Stream may be this much shorter than the container, seconds.
+const DURATION_TOLERANCE: f64 = 5.0;
+/// Or this part of the container duration for the long videos.
+const DURATION_TOLERANCE_RATIO: f64 = 0.02;
+/// Positions decoded in the sampled mode.
+const SAMPLES: u32 = 10;
+/// Packets decoded at each sampled position.
+const SAMPLE_PACKETS: u32 = 200;
+/// Reading is given up after this many errors in a row.
+const MAX_READ_ERRORS: u32 = 100;
+
+/// How thoroughly the file is checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CheckMode {
+	/// Container and stream durations and the file size only.
+	Quick,
+	/// Quick checks and decoding of the segments spread over the file.
+	#[default]
+	Sampled,
+	/// Quick checks and decoding of the whole file.
+	Deep,
+}
+
+impl FromStr for CheckMode {
+	type Err = MediaOrderError;
+
+	fn from_str(str: &str) -> Result<Self> {
+		match str.to_lowercase().as_str() {
+			"quick" => Ok(CheckMode::Quick),
+			"sampled" | "sample" => Ok(CheckMode::Sampled),
+			"deep" | "full" => Ok(CheckMode::Deep),
+			_ => Err(MediaOrderError::ParseError("check mode".to_owned(), str.to_owned())),
+		}
+	}
+}
+
+/// Check verdict, from the best to the worst.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntegrityStatus {
+	#[default]
+	Ok,
+	/// Decoder errors or corrupt packets.
+	Corrupt,
+	/// Data is missing at the end (incomplete download or copy).
+	Truncated,
+	/// ffmpeg can't open the file.
+	Unreadable,
+}
+
+impl IntegrityStatus {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			IntegrityStatus::Ok => "ok",
+			IntegrityStatus::Corrupt => "corrupt",
+			IntegrityStatus::Truncated => "truncated",
+			IntegrityStatus::Unreadable => "unreadable",
+		}
+	}
+}
+
+impl fmt::Display for IntegrityStatus {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// Result of the integrity check of the file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Integrity {
+	pub status: IntegrityStatus,
+	pub issues: Vec<String>,
+	pub decode_errors: u64,
+	pub frames: u64,
+}
+
+impl Integrity {
+	fn issue(&mut self, status: IntegrityStatus, issue: String) {
+		debug!("{status}: {issue}");
+		self.status = self.status.max(status);
+		self.issues.push(issue);
+	}
+}
+
+/// File of the `size` is noticeably smaller than the container bit rate (bits per second) and duration promise.
+pub fn is_truncated(bit_rate: i64, duration: f64, size: u64) -> bool {
+	bit_rate > 0 && duration > 0.0 && (size as f64) < bit_rate as f64 * duration / 8.0 * TRUNCATED_RATIO
+}
+
+fn duration_tolerance(duration: f64) -> f64 {
+	DURATION_TOLERANCE.max(duration * DURATION_TOLERANCE_RATIO)
+}
+
+/// Issues of the audio and video streams (index, duration in seconds) ending before the container does.
+pub fn short_streams(duration: f64, streams: &[(usize, f64)]) -> Vec<String> {
+	streams
+		.iter()
+		.filter(|(_, stream)| *stream > 0.0 && duration - stream > duration_tolerance(duration))
+		.map(|(index, stream)| format!("stream #{index} ends at {stream:.0}s of {duration:.0}s"))
+		.collect()
+}
+
+fn seconds(timestamp: i64, time_base: ffmpeg::Rational) -> f64 {
+	timestamp as f64 * f64::from(time_base)
+}
+
+/// Decoders of the audio and video streams with their time bases.
+struct Decoders(HashMap<usize, (decoder::Opened, ffmpeg::Rational)>);
+
+impl Decoders {
+	fn new(input: &format::context::Input) -> Self {
+		let mut decoders = HashMap::new();
+		for stream in input.streams() {
+			let medium = stream.parameters().medium();
+			if medium != media::Type::Audio && medium != media::Type::Video {
+				continue;
+			}
+			match codec::context::Context::from_parameters(stream.parameters())
+				.and_then(|context| context.decoder().open())
+			{
+				Ok(decoder) => {
+					decoders.insert(stream.index(), (decoder, stream.time_base()));
+				}
+				Err(err) => debug!("No decoder for stream #{}: {err}", stream.index()),
+			}
+		}
+		Self(decoders)
+	}
+
+	fn flush(&mut self) {
+		for (decoder, _) in self.0.values_mut() {
+			decoder.flush();
+		}
+	}
+
+	/// Decodes the packet, returns its end in seconds when it belongs to the checked stream.
+	fn decode(&mut self, packet: &Packet, integrity: &mut Integrity) -> Option<f64> {
+		let (decoder, time_base) = self.0.get_mut(&packet.stream())?;
+		if packet.is_corrupt() {
+			integrity.decode_errors += 1;
+		}
+		match decoder.send_packet(packet) {
+			Ok(()) | Err(Error::Other { errno: EAGAIN }) => {}
+			Err(_) => integrity.decode_errors += 1,
+		}
+		// the frame is only filled by the decoder
+		let mut frame = unsafe { Frame::empty() };
+		loop {
+			match decoder.receive_frame(&mut frame) {
+				Ok(()) => integrity.frames += 1,
+				Err(Error::Other { errno: EAGAIN }) | Err(Error::Eof) => break,
+				Err(_) => {
+					integrity.decode_errors += 1;
+					break;
+				}
+			}
+		}
+		packet.pts().map(|pts| seconds(pts + packet.duration(), *time_base))
+	}
+}
+
+/// Reads and decodes up to `limit` packets from the current position. Returns the number of read packets and
+/// the end of the last decoded one in seconds.
+fn decode_packets(
+	input: &mut format::context::Input,
+	decoders: &mut Decoders,
+	integrity: &mut Integrity,
+	limit: Option<u32>,
+) -> (u32, Option<f64>) {
+	let mut packet = Packet::empty();
+	let mut read = 0;
+	let mut errors = 0;
+	let mut end = None;
+	while limit.is_none_or(|limit| read < limit) {
+		match packet.read(input) {
+			Ok(()) => {
+				read += 1;
+				errors = 0;
+				end = decoders.decode(&packet, integrity).or(end);
+			}
+			Err(Error::Eof) => break,
+			Err(_) => {
+				integrity.decode_errors += 1;
+				errors += 1;
+				if errors >= MAX_READ_ERRORS {
+					break;
+				}
+			}
+		}
+	}
+	(read, end)
+}
+
+/// Checks the file integrity: durations of the streams against the container, the file size against the
+/// bit rate, and in the sampled and deep modes decodes the audio and video streams counting errors.
+pub fn check(path: &Path, mode: CheckMode) -> Integrity {
+	let mut integrity = Integrity::default();
+	let mut input = match format::input(&path) {
+		Ok(input) => input,
+		Err(err) => {
+			integrity.issue(IntegrityStatus::Unreadable, err.to_string());
+			return integrity;
+		}
+	};
+
+	let duration = Some(input.duration())
+		.filter(|&duration| duration > 0)
+		.map(|duration| duration as f64 / f64::from(ffmpeg::ffi::AV_TIME_BASE));
+	if let Some(duration) = duration {
+		let streams: Vec<(usize, f64)> = input
+			.streams()
+			.filter(|stream| [media::Type::Audio, media::Type::Video].contains(&stream.parameters().medium()))
+			.map(|stream| (stream.index(), seconds(stream.duration(), stream.time_base())))
+			.collect();
+		for issue in short_streams(duration, &streams) {
+			integrity.issue(IntegrityStatus::Truncated, issue);
+		}
+		let size = fs::metadata(path).map(|metadata| metadata.len()).unwrap_or_default();
+		if is_truncated(input.bit_rate(), duration, size) {
+			integrity.issue(
+				IntegrityStatus::Truncated,
+				format!("{size} bytes for {duration:.0}s at {} bit/s", input.bit_rate()),
+			);
+		}
+	}
+
+	let mut decoders = Decoders::new(&input);
+	match (mode, duration) {
+		(CheckMode::Quick, _) => {}
+		(CheckMode::Sampled, Some(duration)) => {
+			for sample in 0..SAMPLES {
+				let position = duration * f64::from(sample) / f64::from(SAMPLES);
+				let timestamp = (position * f64::from(ffmpeg::ffi::AV_TIME_BASE)) as i64;
+				if input.seek(timestamp, ..timestamp).is_err() {
+					integrity.issue(IntegrityStatus::Corrupt, format!("can't seek to {position:.0}s"));
+					continue;
+				}
+				decoders.flush();
+				let (read, _) =
+					decode_packets(&mut input, &mut decoders, &mut integrity, Some(SAMPLE_PACKETS));
+				if read == 0 {
+					integrity.issue(IntegrityStatus::Truncated, format!("no data at {position:.0}s"));
+					break;
+				}
+			}
+		}
+		(CheckMode::Sampled, None) | (CheckMode::Deep, _) => {
+			let (_, end) = decode_packets(&mut input, &mut decoders, &mut integrity, None);
+			if let (Some(duration), Some(end)) = (duration, end) {
+				if duration - end > duration_tolerance(duration) {
+					integrity.issue(
+						IntegrityStatus::Truncated,
+						format!("data ends at {end:.0}s of {duration:.0}s"),
+					);
+				}
+			}
+		}
+	}
+
+	if integrity.decode_errors > 0 {
+		let issue = format!("{} decode errors in {} frames", integrity.decode_errors, integrity.frames);
+		integrity.issue(IntegrityStatus::Corrupt, issue);
+	}
+	integrity
+}
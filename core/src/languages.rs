@@ -0,0 +1,183 @@
+use std::fmt;
+
+use ffmpeg_the_third::media::Type as MediaType;
+
+use crate::{
+	errors::Result,
+	metadata::{write_tags, MetadataTags},
+	video::{StreamInfo, Video},
+};
+
+lazy_static! {
+	/// Words of the audio stream title pointing to the language.
+	static ref TITLE_HINTS: Vec<(&'static str, &'static str)> = vec![
+		("english", "eng"),
+		("eng", "eng"),
+		("russian", "rus"),
+		("rus", "rus"),
+		("русский", "rus"),
+		("дубляж", "rus"),
+		("дублированный", "rus"),
+		("многоголосый", "rus"),
+		("одноголосый", "rus"),
+		("двухголосый", "rus"),
+		("mvo", "rus"),
+		("dvo", "rus"),
+		("avo", "rus"),
+		("ukrainian", "ukr"),
+		("ukr", "ukr"),
+		("українська", "ukr"),
+		("украинский", "ukr"),
+		("french", "fra"),
+		("français", "fra"),
+		("fra", "fra"),
+	];
+}
+
+lazy_static! {
+	static ref COMMENTARY: Vec<&'static str> = vec!["commentary", "comment", "комментарий", "комментарии"];
+}
+
+/// Why the language is proposed for the stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LanguageReason {
+	/// Stream title names the language.
+	Title,
+	/// Stream order matches the order of the languages in the file name.
+	Order,
+	/// There are more untagged streams than languages in the name, the ones with more channels are taken.
+	Channels,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LanguageProposal {
+	pub index: usize,
+	pub current: Option<String>,
+	pub language: String,
+	pub reason: LanguageReason,
+}
+
+impl fmt::Display for LanguageProposal {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"stream #{}: {} -> {} ({:?})",
+			self.index,
+			self.current.as_deref().unwrap_or("none"),
+			self.language,
+			self.reason
+		)
+	}
+}
+
+fn is_untagged(stream: &StreamInfo) -> bool {
+	stream
+		.language
+		.as_deref()
+		.is_none_or(|language| language.is_empty() || language == "und")
+}
+
+fn title_words(stream: &StreamInfo) -> Vec<String> {
+	stream
+		.title
+		.as_deref()
+		.unwrap_or_default()
+		.to_lowercase()
+		.split(|c: char| !c.is_alphanumeric())
+		.filter(|word| !word.is_empty())
+		.map(String::from)
+		.collect()
+}
+
+fn title_language(stream: &StreamInfo) -> Option<&'static str> {
+	let words = title_words(stream);
+	TITLE_HINTS
+		.iter()
+		.find(|(hint, _)| words.iter().any(|word| word == hint))
+		.map(|(_, language)| *language)
+}
+
+fn is_commentary(stream: &StreamInfo) -> bool {
+	let words = title_words(stream);
+	COMMENTARY.iter().any(|comment| words.iter().any(|word| word == comment))
+}
+
+/// Proposes languages of the untagged (`und`) audio streams from the languages of the file name (`hints`,
+/// ISO 639-2 codes in the name order). Languages of the tagged streams and the streams with the language in the
+/// title are taken out of the hints, the rest are matched to the remaining untagged streams by order. When there
+/// are more such streams than hints, commentaries are skipped and the streams with more channels are taken.
+pub fn propose_languages(hints: &[&str], streams: &[StreamInfo]) -> Vec<LanguageProposal> {
+	let mut hints: Vec<&str> = hints.to_vec();
+	let mut proposals = vec![];
+	let audio: Vec<&StreamInfo> = streams.iter().filter(|stream| stream.medium == MediaType::Audio).collect();
+
+	let mut take_hint = |language: &str| {
+		if let Some(position) = hints.iter().position(|hint| *hint == language) {
+			hints.remove(position);
+		}
+	};
+	for stream in &audio {
+		if !is_untagged(stream) {
+			take_hint(stream.language.as_deref().unwrap_or_default());
+		}
+	}
+
+	let mut untagged = vec![];
+	for stream in audio.into_iter().filter(|stream| is_untagged(stream)) {
+		match title_language(stream) {
+			Some(language) => {
+				take_hint(language);
+				proposals.push(LanguageProposal {
+					index: stream.index,
+					current: stream.language.clone(),
+					language: language.to_owned(),
+					reason: LanguageReason::Title,
+				});
+			}
+			None => untagged.push(stream),
+		}
+	}
+
+	let mut reason = LanguageReason::Order;
+	if untagged.len() > hints.len() {
+		untagged.retain(|stream| !is_commentary(stream));
+	}
+	if untagged.len() > hints.len() {
+		reason = LanguageReason::Channels;
+		let mut by_channels = untagged.clone();
+		by_channels.sort_by_key(|stream| std::cmp::Reverse(stream.channels));
+		let min_channels = by_channels.get(hints.len().saturating_sub(1)).map(|stream| stream.channels);
+		let ambiguous = by_channels.get(hints.len()).map(|stream| stream.channels) == min_channels;
+		if hints.is_empty() || ambiguous {
+			untagged.clear();
+		} else {
+			by_channels.truncate(hints.len());
+			untagged.retain(|stream| by_channels.iter().any(|taken| taken.index == stream.index));
+		}
+	}
+
+	for (stream, language) in untagged.into_iter().zip(hints) {
+		proposals.push(LanguageProposal {
+			index: stream.index,
+			current: stream.language.clone(),
+			language: language.to_owned(),
+			reason,
+		});
+	}
+	proposals.sort_by_key(|proposal| proposal.index);
+	proposals
+}
+
+/// Proposals for the video by its name languages and probed streams.
+pub fn propose(video: &Video) -> Vec<LanguageProposal> {
+	let hints: Vec<&str> = video.lang.iter().flatten().map(|lang| lang.name()).collect();
+	propose_languages(&hints, &video.streams())
+}
+
+/// Writes the proposed languages with the lossless remux.
+pub fn apply(video: &Video, proposals: &[LanguageProposal]) -> Result<()> {
+	let tags = proposals.iter().fold(MetadataTags::default(), |tags, proposal| {
+		tags.language(proposal.index, &proposal.language)
+	});
+	write_tags(&video.path, &tags)
+}
@@ -1,7 +1,25 @@
 #[cfg(test)] mod tests;
 
+pub mod config;
+pub mod disc;
 mod errors;
+pub mod hook;
+pub mod identify;
+pub mod integrity;
+pub mod languages;
+pub mod library;
+pub mod media;
+pub mod metadata;
+pub mod nfo;
+pub mod organise;
+pub mod overrides;
+pub mod report;
+pub mod rules;
+pub mod scanner;
+pub mod series;
 mod types;
 pub mod video;
+pub mod view;
+pub mod watch;
 
 #[macro_use] extern crate lazy_static;
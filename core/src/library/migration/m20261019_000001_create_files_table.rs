@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Files::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Files::Path).string().not_null().primary_key())
+					.col(ColumnDef::new(Files::Kind).string().not_null())
+					.col(ColumnDef::new(Files::Format).string())
+					.col(ColumnDef::new(Files::Size).big_integer().not_null())
+					.col(ColumnDef::new(Files::Modified).big_integer().not_null())
+					.col(ColumnDef::new(Files::Scanned).big_integer().not_null())
+					.col(ColumnDef::new(Files::Error).string())
+					.col(ColumnDef::new(Files::Name).string())
+					.col(ColumnDef::new(Files::Year).integer())
+					.col(ColumnDef::new(Files::Part).integer())
+					.col(ColumnDef::new(Files::Role).string())
+					.col(ColumnDef::new(Files::Lang).string())
+					.col(ColumnDef::new(Files::Ext).string())
+					.col(ColumnDef::new(Files::Venc).string())
+					.col(ColumnDef::new(Files::Aenc).string())
+					.col(ColumnDef::new(Files::Vres).string())
+					.col(ColumnDef::new(Files::Vqual).string())
+					.col(ColumnDef::new(Files::Duration).double())
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("idx_files_name")
+					.table(Files::Table)
+					.col(Files::Name)
+					.to_owned(),
+			)
+			.await?;
+
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.drop_index(Index::drop().name("idx_files_name").table(Files::Table).to_owned())
+			.await?;
+
+		manager.drop_table(Table::drop().table(Files::Table).to_owned()).await?;
+
+		Ok(())
+	}
+}
+
+#[derive(DeriveIden)]
+enum Files {
+	Table,
+	Path,
+	Kind,
+	Format,
+	Size,
+	Modified,
+	Scanned,
+	Error,
+	Name,
+	Year,
+	Part,
+	Role,
+	Lang,
+	Ext,
+	Venc,
+	Aenc,
+	Vres,
+	Vqual,
+	Duration,
+}
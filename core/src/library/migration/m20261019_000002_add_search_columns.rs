@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		// SQLite alters one column at a time
+		for mut column in [
+			ColumnDef::new(Files::NameEnglish).string().to_owned(),
+			ColumnDef::new(Files::ImdbId).string().to_owned(),
+			ColumnDef::new(Files::Genres).string().to_owned(),
+			ColumnDef::new(Files::Rating).double().to_owned(),
+			ColumnDef::new(Files::AudioLangs).string().to_owned(),
+			ColumnDef::new(Files::Height).integer().to_owned(),
+		] {
+			manager
+				.alter_table(Table::alter().table(Files::Table).add_column(&mut column).to_owned())
+				.await?;
+		}
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("idx_files_year")
+					.table(Files::Table)
+					.col(Files::Year)
+					.to_owned(),
+			)
+			.await?;
+
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.drop_index(Index::drop().name("idx_files_year").table(Files::Table).to_owned())
+			.await?;
+
+		for column in [
+			Files::NameEnglish,
+			Files::ImdbId,
+			Files::Genres,
+			Files::Rating,
+			Files::AudioLangs,
+			Files::Height,
+		] {
+			manager
+				.alter_table(Table::alter().table(Files::Table).drop_column(column).to_owned())
+				.await?;
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(DeriveIden)]
+enum Files {
+	Table,
+	Year,
+	NameEnglish,
+	ImdbId,
+	Genres,
+	Rating,
+	AudioLangs,
+	Height,
+}
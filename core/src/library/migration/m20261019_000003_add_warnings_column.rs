@@ -0,0 +1,30 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter()
+					.table(Files::Table)
+					.add_column(ColumnDef::new(Files::Warnings).string())
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(Table::alter().table(Files::Table).drop_column(Files::Warnings).to_owned())
+			.await
+	}
+}
+
+#[derive(DeriveIden)]
+enum Files {
+	Table,
+	Warnings,
+}
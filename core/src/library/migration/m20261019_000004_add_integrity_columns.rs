@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		for mut column in [
+			ColumnDef::new(Files::Integrity).string().to_owned(),
+			ColumnDef::new(Files::IntegrityIssues).string().to_owned(),
+			ColumnDef::new(Files::DecodeErrors).big_integer().to_owned(),
+			ColumnDef::new(Files::Checked).big_integer().to_owned(),
+		] {
+			manager
+				.alter_table(Table::alter().table(Files::Table).add_column(&mut column).to_owned())
+				.await?;
+		}
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		for column in [
+			Files::Integrity,
+			Files::IntegrityIssues,
+			Files::DecodeErrors,
+			Files::Checked,
+		] {
+			manager
+				.alter_table(Table::alter().table(Files::Table).drop_column(column).to_owned())
+				.await?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(DeriveIden)]
+enum Files {
+	Table,
+	Integrity,
+	IntegrityIssues,
+	DecodeErrors,
+	Checked,
+}
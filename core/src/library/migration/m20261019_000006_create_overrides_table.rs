@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.alter_table(
+				Table::alter()
+					.table(Files::Table)
+					.add_column(ColumnDef::new(Files::Fingerprint).string())
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_table(
+				Table::create()
+					.table(Overrides::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Overrides::Path).string().not_null().primary_key())
+					.col(ColumnDef::new(Overrides::Folder).boolean().not_null())
+					.col(ColumnDef::new(Overrides::Fingerprint).string())
+					.col(ColumnDef::new(Overrides::ImdbId).string())
+					.col(ColumnDef::new(Overrides::Title).string())
+					.col(ColumnDef::new(Overrides::Year).integer())
+					.col(ColumnDef::new(Overrides::SeasonOffset).integer())
+					.col(ColumnDef::new(Overrides::Ignore).boolean())
+					.col(ColumnDef::new(Overrides::Updated).big_integer().not_null())
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("idx_overrides_fingerprint")
+					.table(Overrides::Table)
+					.col(Overrides::Fingerprint)
+					.to_owned(),
+			)
+			.await
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Overrides::Table).to_owned()).await?;
+		manager
+			.alter_table(Table::alter().table(Files::Table).drop_column(Files::Fingerprint).to_owned())
+			.await
+	}
+}
+
+#[derive(DeriveIden)]
+enum Files {
+	Table,
+	Fingerprint,
+}
+
+#[derive(DeriveIden)]
+enum Overrides {
+	Table,
+	Path,
+	Folder,
+	Fingerprint,
+	ImdbId,
+	Title,
+	Year,
+	SeasonOffset,
+	Ignore,
+	Updated,
+}
@@ -0,0 +1,35 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		for mut column in [
+			ColumnDef::new(Files::Title).string().to_owned(),
+			ColumnDef::new(Files::OriginalTitle).string().to_owned(),
+		] {
+			manager
+				.alter_table(Table::alter().table(Files::Table).add_column(&mut column).to_owned())
+				.await?;
+		}
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		for column in [Files::Title, Files::OriginalTitle] {
+			manager
+				.alter_table(Table::alter().table(Files::Table).drop_column(column).to_owned())
+				.await?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(DeriveIden)]
+enum Files {
+	Table,
+	Title,
+	OriginalTitle,
+}
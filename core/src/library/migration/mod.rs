@@ -0,0 +1,26 @@
+pub use sea_orm_migration::prelude::*;
+
+mod m20261019_000001_create_files_table;
+mod m20261019_000002_add_search_columns;
+mod m20261019_000003_add_warnings_column;
+mod m20261019_000004_add_integrity_columns;
+mod m20261019_000005_add_episode_columns;
+mod m20261019_000006_create_overrides_table;
+mod m20261019_000007_add_title_columns;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+	fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+		vec![
+			Box::new(m20261019_000001_create_files_table::Migration),
+			Box::new(m20261019_000002_add_search_columns::Migration),
+			Box::new(m20261019_000003_add_warnings_column::Migration),
+			Box::new(m20261019_000004_add_integrity_columns::Migration),
+			Box::new(m20261019_000005_add_episode_columns::Migration),
+			Box::new(m20261019_000006_create_overrides_table::Migration),
+			Box::new(m20261019_000007_add_title_columns::Migration),
+		]
+	}
+}
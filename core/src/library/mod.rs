@@ -0,0 +1,612 @@
+pub mod entities;
+pub mod migration;
+pub mod query;
+
+use std::{
+	collections::HashMap,
+	fs::Metadata,
+	path::{Path, PathBuf},
+	time::UNIX_EPOCH,
+};
+
+use chrono::Utc;
+use ffmpeg_the_third::media::Type as MediaType;
+use sea_orm::{
+	sea_query::{Expr, OnConflict},
+	ActiveValue,
+	ColumnTrait,
+	ConnectOptions,
+	Database,
+	DatabaseConnection,
+	EntityTrait,
+	IdenStatic,
+	Iterable,
+	QueryFilter,
+	QueryOrder,
+	QuerySelect,
+	TransactionTrait,
+};
+use sea_orm_migration::MigratorTrait;
+use tokio::sync::mpsc;
+
+use crate::{
+	config::ScanConfig,
+	errors::Result,
+	integrity::Integrity,
+	library::{
+		entities::{files, overrides},
+		query::Query,
+	},
+	media::TitleInfo,
+	overrides::{fingerprint, Override, OverrideCache},
+	rules::ScanRules,
+	scanner::{Progress, Scanner},
+	types::FSEntry,
+	video::{Video, WARNING_TRUNCATED},
+};
+
+pub const KIND_VIDEO: &str = "video";
+pub const KIND_DISC: &str = "disc";
+pub const KIND_FILE: &str = "file";
+pub const KIND_UNKNOWN: &str = "unknown";
+pub const KIND_ERROR: &str = "error";
+
+/// Store of the scanned library files.
+pub struct Library {
+	db: DatabaseConnection,
+}
+
+fn path_string(path: &Path) -> String {
+	path.to_string_lossy().into_owned()
+}
+
+/// Modification time of the file in seconds since the epoch.
+fn modified_secs(metadata: &Metadata) -> i64 {
+	metadata
+		.modified()
+		.ok()
+		.and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+		.map(|modified| modified.as_secs() as i64)
+		.unwrap_or_default()
+}
+
+/// The file has the size and the modification time of its record.
+async fn is_unchanged(file: &files::Model, path: &Path) -> bool {
+	tokio::fs::metadata(path)
+		.await
+		.is_ok_and(|metadata| metadata.len() as i64 == file.size && modified_secs(&metadata) == file.modified)
+}
+
+/// Sets the video fields, truncation is checked when the `size` is of the video file itself.
+fn set_video(model: &mut files::ActiveModel, video: &Video, size: Option<u64>) {
+	model.name = ActiveValue::Set(Some(video.name_original.clone()));
+	model.year = ActiveValue::Set(video.year.map(i32::from));
+	model.part = ActiveValue::Set(video.part.map(i32::from));
+	model.role = ActiveValue::Set(Some(video.role.as_str().to_owned()));
+	model.lang = ActiveValue::Set(
+		video
+			.lang
+			.as_ref()
+			.map(|langs| langs.iter().map(|lang| lang.name()).collect::<Vec<_>>().join(",")),
+	);
+	model.ext = ActiveValue::Set(video.ext.clone());
+	model.venc = ActiveValue::Set(video.venc.clone());
+	model.aenc = ActiveValue::Set(video.aenc.clone());
+	model.vres = ActiveValue::Set(video.vres.clone());
+	model.vqual = ActiveValue::Set(video.vqual.clone());
+	model.duration = ActiveValue::Set(video.duration());
+	model.name_english = ActiveValue::Set(video.name_english.clone());
+	model.season = ActiveValue::Set(video.season.map(i32::from));
+	model.episode = ActiveValue::Set(video.episode.map(i32::from));
+	model.last_episode = ActiveValue::Set(video.last_episode.map(i32::from));
+	model.imdb_id = ActiveValue::Set(video.imdb_id.clone());
+	model.error = ActiveValue::Set(video.probe_error.clone());
+
+	let mut warnings = video.warnings();
+	if size.is_some_and(|size| video.is_truncated(size)) {
+		warnings.push(WARNING_TRUNCATED);
+	}
+	model.warnings = ActiveValue::Set(Some(warnings.join(",")).filter(|warnings| !warnings.is_empty()));
+
+	let streams = video.streams();
+	let audio_langs: Vec<&str> = streams
+		.iter()
+		.filter(|stream| stream.medium == MediaType::Audio)
+		.filter_map(|stream| stream.language.as_deref())
+		.collect();
+	model.audio_langs = ActiveValue::Set(Some(audio_langs.join(",")).filter(|langs| !langs.is_empty()));
+	model.height = ActiveValue::Set(
+		streams
+			.iter()
+			.find(|stream| stream.medium == MediaType::Video && stream.height > 0)
+			.map(|stream| stream.height as i32),
+	);
+}
+
+fn is_integrity_column(column: &files::Column) -> bool {
+	matches!(
+		column,
+		files::Column::Integrity
+			| files::Column::IntegrityIssues
+			| files::Column::DecodeErrors
+			| files::Column::Checked
+	)
+}
+
+/// Path under `from` moved to `to`, the joined empty path would end with a separator.
+fn moved_path(path: &str, from: &Path, to: &Path) -> String {
+	match Path::new(path).strip_prefix(from) {
+		Ok(relative) if relative != Path::new("") => path_string(&to.join(relative)),
+		_ => path_string(to),
+	}
+}
+
+async fn file_size(path: &Path) -> u64 {
+	tokio::fs::metadata(path)
+		.await
+		.map(|metadata| metadata.len())
+		.unwrap_or_default()
+}
+
+impl Library {
+	/// Opens (creates if needed) sqlite library database file and migrates it to the latest version.
+	pub async fn open(path: &Path) -> Result<Self> {
+		Self::connect(&format!("sqlite://{}?mode=rwc", path.display())).await
+	}
+
+	pub async fn connect(url: &str) -> Result<Self> {
+		let db = Database::connect(ConnectOptions::new(url.to_owned()).to_owned()).await?;
+		migration::Migrator::up(&db, None).await?;
+		Ok(Self { db })
+	}
+
+	pub fn db(&self) -> &DatabaseConnection {
+		&self.db
+	}
+
+	/// Inserts or updates the record of the scanned entry, folders are not stored. Title details set by
+	/// [`Library::set_title`] are kept.
+	pub async fn store(&self, path: &Path, entry: &Result<FSEntry>) -> Result<()> {
+		let fingerprint = match entry {
+			Ok(FSEntry::Video(_)) => fingerprint(path).await,
+			_ => None,
+		};
+		self.store_entry(path, entry, fingerprint).await
+	}
+
+	async fn store_entry(
+		&self,
+		path: &Path,
+		entry: &Result<FSEntry>,
+		fingerprint: Option<String>,
+	) -> Result<()> {
+		let metadata = tokio::fs::metadata(path).await.ok();
+		let modified = metadata.as_ref().map(modified_secs).unwrap_or_default();
+
+		let size = metadata.map(|metadata| metadata.len()).unwrap_or_default();
+		let mut model = files::ActiveModel {
+			path: ActiveValue::Set(path_string(path)),
+			format: ActiveValue::Set(None),
+			size: ActiveValue::Set(size as i64),
+			modified: ActiveValue::Set(modified),
+			scanned: ActiveValue::Set(Utc::now().timestamp()),
+			error: ActiveValue::Set(None),
+			name: ActiveValue::Set(None),
+			year: ActiveValue::Set(None),
+			part: ActiveValue::Set(None),
+			role: ActiveValue::Set(None),
+			lang: ActiveValue::Set(None),
+			ext: ActiveValue::Set(None),
+			venc: ActiveValue::Set(None),
+			aenc: ActiveValue::Set(None),
+			vres: ActiveValue::Set(None),
+			vqual: ActiveValue::Set(None),
+			duration: ActiveValue::Set(None),
+			name_english: ActiveValue::Set(None),
+			imdb_id: ActiveValue::Set(None),
+			audio_langs: ActiveValue::Set(None),
+			height: ActiveValue::Set(None),
+			warnings: ActiveValue::Set(None),
+			integrity: ActiveValue::Set(None),
+			integrity_issues: ActiveValue::Set(None),
+			decode_errors: ActiveValue::Set(None),
+			checked: ActiveValue::Set(None),
+			season: ActiveValue::Set(None),
+			episode: ActiveValue::Set(None),
+			last_episode: ActiveValue::Set(None),
+			fingerprint: ActiveValue::Set(fingerprint),
+			..Default::default()
+		};
+
+		let kind = match entry {
+			Ok(FSEntry::Folder(_)) => return Ok(()),
+			Ok(FSEntry::Video(video)) => {
+				set_video(&mut model, video, Some(size));
+				model.format =
+					ActiveValue::Set(Some(video.format.short_name().unwrap_or_default().to_owned()));
+				KIND_VIDEO
+			}
+			Ok(FSEntry::DiscImage(disc)) => {
+				set_video(&mut model, &disc.video, None);
+				let mut size = 0;
+				for stream in &disc.streams {
+					size += file_size(stream).await;
+				}
+				if !disc.streams.is_empty() {
+					model.size = ActiveValue::Set(size as i64);
+				}
+				model.duration = ActiveValue::Set(disc.duration.or(disc.video.duration()));
+				KIND_DISC
+			}
+			Ok(FSEntry::File((_, format))) => {
+				model.format =
+					ActiveValue::Set(Some(format.short_name().unwrap_or(format.name()).to_owned()));
+				KIND_FILE
+			}
+			Ok(FSEntry::Unknown(_)) => KIND_UNKNOWN,
+			Err(err) => {
+				model.error = ActiveValue::Set(Some(err.to_string()));
+				KIND_ERROR
+			}
+		};
+		model.kind = ActiveValue::Set(kind.to_owned());
+
+		files::Entity::insert(model)
+			.on_conflict(
+				OnConflict::column(files::Column::Path)
+					.update_columns(files::Column::iter().filter(|column| {
+						!matches!(
+							column,
+							files::Column::Path
+								| files::Column::ImdbId | files::Column::Title
+								| files::Column::OriginalTitle
+								| files::Column::Genres | files::Column::Rating
+						) && !is_integrity_column(column)
+					}))
+					// the identified title is kept unless the scan has the id (e.g. from the nfo or override)
+					.value(files::Column::ImdbId, Expr::cust("COALESCE(excluded.imdb_id, files.imdb_id)"))
+					.values(files::Column::iter().filter(is_integrity_column).map(|column| {
+						// the check result is dropped once the file is modified
+						let expr = format!(
+							"CASE WHEN excluded.modified = files.modified THEN files.{} END",
+							column.as_str()
+						);
+						(column, Expr::cust(expr))
+					}))
+					.to_owned(),
+			)
+			.exec(&self.db)
+			.await?;
+		Ok(())
+	}
+
+	/// Stores the integrity check result of the file.
+	pub async fn set_integrity(&self, path: &Path, integrity: &Integrity) -> Result<bool> {
+		let result = files::Entity::update_many()
+			.col_expr(files::Column::Integrity, Expr::value(integrity.status.as_str()))
+			.col_expr(
+				files::Column::IntegrityIssues,
+				Expr::value(Some(integrity.issues.join("; ")).filter(|issues| !issues.is_empty())),
+			)
+			.col_expr(files::Column::DecodeErrors, Expr::value(integrity.decode_errors as i64))
+			.col_expr(files::Column::Checked, Expr::value(Utc::now().timestamp()))
+			.filter(files::Column::Path.eq(path_string(path)))
+			.exec(&self.db)
+			.await?;
+		Ok(result.rows_affected > 0)
+	}
+
+	/// Stores the IMDb title the file is identified as, unless the file is overridden to another title. The
+	/// overrides are the ones of [`Library::override_cache`], loaded once for all the identified files.
+	pub async fn set_title(
+		&self,
+		path: &Path,
+		title: &TitleInfo,
+		overrides: &mut OverrideCache,
+	) -> Result<bool> {
+		let fingerprint = self.get(path).await?.and_then(|file| file.fingerprint);
+		let forced = self.resolve_override(path, fingerprint.as_deref(), overrides).await?;
+		if forced.imdb_id.is_some_and(|imdb_id| imdb_id != title.imdb_id) {
+			return Ok(false);
+		}
+		let result = files::Entity::update_many()
+			.col_expr(files::Column::ImdbId, Expr::value(title.imdb_id.clone()))
+			.col_expr(files::Column::Title, Expr::value(title.title.clone()))
+			.col_expr(files::Column::OriginalTitle, Expr::value(title.original_title.clone()))
+			.col_expr(
+				files::Column::Genres,
+				Expr::value(Some(title.genres.join(",")).filter(|genres| !genres.is_empty())),
+			)
+			.col_expr(files::Column::Rating, Expr::value(title.rating))
+			.filter(files::Column::Path.eq(path_string(path)))
+			.exec(&self.db)
+			.await?;
+		Ok(result.rows_affected > 0)
+	}
+
+	/// Records matching the query, in the query order.
+	pub async fn search(&self, query: &Query) -> Result<Vec<files::Model>> {
+		let mut select = files::Entity::find().filter(query.condition());
+		for (column, order) in query.order() {
+			select = select.order_by(column, order);
+		}
+		Ok(select
+			.order_by_asc(files::Column::Path)
+			.limit(query.limit())
+			.all(&self.db)
+			.await?)
+	}
+
+	pub async fn get(&self, path: &Path) -> Result<Option<files::Model>> {
+		Ok(files::Entity::find_by_id(path_string(path)).one(&self.db).await?)
+	}
+
+	pub async fn files(&self) -> Result<Vec<files::Model>> {
+		Ok(files::Entity::find().all(&self.db).await?)
+	}
+
+	/// Records of the path and everything under it.
+	pub async fn files_under(&self, root: &Path) -> Result<Vec<files::Model>> {
+		let root_string = path_string(root);
+		let files = files::Entity::find()
+			.filter(
+				files::Column::Path
+					.eq(root_string.as_str())
+					.or(files::Column::Path.starts_with(format!("{root_string}/"))),
+			)
+			.all(&self.db)
+			.await?;
+
+		// LIKE treats `_` and `%` in the path as wildcards
+		Ok(files
+			.into_iter()
+			.filter(|file| Path::new(&file.path).starts_with(root))
+			.collect())
+	}
+
+	/// Removes records of the path and everything under it.
+	pub async fn remove(&self, path: &Path) -> Result<u64> {
+		let paths: Vec<String> = self.files_under(path).await?.into_iter().map(|file| file.path).collect();
+		if paths.is_empty() {
+			return Ok(0);
+		}
+		let result = files::Entity::delete_many()
+			.filter(files::Column::Path.is_in(paths))
+			.exec(&self.db)
+			.await?;
+		Ok(result.rows_affected)
+	}
+
+	/// Moves records and overrides of the path and everything under it to the new location.
+	pub async fn rename(&self, from: &Path, to: &Path) -> Result<u64> {
+		let files = self.files_under(from).await?;
+		let moved: Vec<overrides::Model> = self
+			.overrides()
+			.await?
+			.into_iter()
+			.filter(|found| Path::new(&found.path).starts_with(from))
+			.collect();
+		let txn = self.db.begin().await?;
+
+		for found in &moved {
+			let new_path = moved_path(&found.path, from, to);
+			overrides::Entity::delete_by_id(new_path.clone()).exec(&txn).await?;
+			overrides::Entity::update_many()
+				.col_expr(overrides::Column::Path, Expr::value(new_path))
+				.filter(overrides::Column::Path.eq(found.path.as_str()))
+				.exec(&txn)
+				.await?;
+		}
+
+		for file in &files {
+			let new_path = moved_path(&file.path, from, to);
+			files::Entity::delete_by_id(new_path.clone()).exec(&txn).await?;
+			files::Entity::update_many()
+				.col_expr(files::Column::Path, Expr::value(new_path))
+				.filter(files::Column::Path.eq(file.path.as_str()))
+				.exec(&txn)
+				.await?;
+		}
+
+		txn.commit().await?;
+		Ok(files.len() as u64)
+	}
+
+	/// Scans the path, stores every found entry with its overrides applied and removes records of the files
+	/// under the path which are gone or ignored. Returns scan progress and found videos.
+	pub async fn scan(
+		&self,
+		root: PathBuf,
+		config: ScanConfig,
+		rules: ScanRules,
+	) -> Result<(Progress, Vec<Video>)> {
+		let (tx, mut rx) = mpsc::channel(config.queue_size);
+		let scanner = tokio::spawn(Scanner::new(config).with_rules(rules).run(root.clone(), tx));
+		let mut known: HashMap<String, files::Model> = self
+			.files_under(&root)
+			.await?
+			.into_iter()
+			.map(|file| (file.path.clone(), file))
+			.collect();
+		let mut videos = vec![];
+		let mut overrides = self.override_cache().await?;
+
+		while let Some((path, mut entry)) = rx.recv().await {
+			let path = match &entry {
+				Ok(FSEntry::DiscImage(disc)) => disc.root.clone(),
+				_ => path,
+			};
+			let record = known.get(&path_string(&path));
+			let fingerprint = match (&entry, record) {
+				(Ok(FSEntry::Video(_)), Some(file)) if is_unchanged(file, &path).await => {
+					file.fingerprint.clone()
+				}
+				(Ok(FSEntry::Video(_)), _) if overrides.needs_fingerprints() => fingerprint(&path).await,
+				_ => None,
+			};
+			let forced = self.resolve_override(&path, fingerprint.as_deref(), &mut overrides).await?;
+			if forced.is_ignored() {
+				continue;
+			}
+			match &mut entry {
+				Ok(FSEntry::Video(video)) => forced.apply(video),
+				Ok(FSEntry::DiscImage(disc)) => forced.apply(&mut disc.video),
+				_ => {}
+			}
+			self.store_entry(&path, &entry, fingerprint).await?;
+			known.remove(&path_string(&path));
+
+			match entry {
+				Ok(FSEntry::Video(video)) => videos.push(video),
+				Ok(FSEntry::DiscImage(disc)) => videos.push(disc.video),
+				_ => {}
+			}
+		}
+		let progress = scanner.await??;
+
+		// the records left are gone or ignored
+		let gone: Vec<String> = known.into_keys().collect();
+		if !gone.is_empty() {
+			files::Entity::delete_many()
+				.filter(files::Column::Path.is_in(gone))
+				.exec(&self.db)
+				.await?;
+		}
+
+		Ok((progress, videos))
+	}
+
+	/// Own override of the path, as set by [`Library::set_override`].
+	pub async fn get_override(&self, path: &Path) -> Result<Option<Override>> {
+		let found = overrides::Entity::find_by_id(path_string(path)).one(&self.db).await?;
+		Ok(found.as_ref().map(Override::from))
+	}
+
+	pub async fn overrides(&self) -> Result<Vec<overrides::Model>> {
+		Ok(overrides::Entity::find()
+			.order_by_asc(overrides::Column::Path)
+			.all(&self.db)
+			.await?)
+	}
+
+	/// Sets override of the file or of everything in the folder, the empty one removes it. Overrides of the
+	/// files are found by the fingerprint once the file is moved, the next scan applies them.
+	pub async fn set_override(&self, path: &Path, value: &Override) -> Result<()> {
+		if value.is_empty() {
+			self.remove_override(path).await?;
+			return Ok(());
+		}
+		let folder = tokio::fs::metadata(path).await.is_ok_and(|metadata| metadata.is_dir());
+		let fingerprint = if folder { None } else { fingerprint(path).await };
+		let model = overrides::ActiveModel {
+			path: ActiveValue::Set(path_string(path)),
+			folder: ActiveValue::Set(folder),
+			fingerprint: ActiveValue::Set(fingerprint),
+			imdb_id: ActiveValue::Set(value.imdb_id.clone()),
+			title: ActiveValue::Set(value.title.clone()),
+			year: ActiveValue::Set(value.year.map(i32::from)),
+			season_offset: ActiveValue::Set(value.season_offset),
+			ignore: ActiveValue::Set(value.ignore),
+			updated: ActiveValue::Set(Utc::now().timestamp()),
+		};
+		overrides::Entity::insert(model)
+			.on_conflict(
+				OnConflict::column(overrides::Column::Path)
+					.update_columns(
+						overrides::Column::iter().filter(|column| !matches!(column, overrides::Column::Path)),
+					)
+					.to_owned(),
+			)
+			.exec(&self.db)
+			.await?;
+		Ok(())
+	}
+
+	pub async fn remove_override(&self, path: &Path) -> Result<bool> {
+		let result = overrides::Entity::delete_by_id(path_string(path)).exec(&self.db).await?;
+		Ok(result.rows_affected > 0)
+	}
+
+	/// Override of the file set by the path, or by the fingerprint when the file was moved outside of the
+	/// library (the override then follows the file).
+	async fn file_override(
+		&self,
+		path: &Path,
+		fingerprint: Option<&str>,
+		overrides: &mut OverrideCache,
+	) -> Result<Option<Override>> {
+		let path = path_string(path);
+		if let Some(own) = overrides.file(&path).cloned() {
+			if fingerprint.is_some() && own.fingerprint.as_deref() != fingerprint {
+				// the replaced file keeps the override of its path
+				overrides::Entity::update_many()
+					.col_expr(overrides::Column::Fingerprint, Expr::value(fingerprint))
+					.filter(overrides::Column::Path.eq(path.as_str()))
+					.exec(&self.db)
+					.await?;
+				let updated = overrides::Model {
+					fingerprint: fingerprint.map(str::to_owned),
+					..own.clone()
+				};
+				overrides.update_file(&path, updated);
+			}
+			return Ok(Some((&own).into()));
+		}
+
+		let Some(fingerprint) = fingerprint else {
+			return Ok(None);
+		};
+		let found: Vec<overrides::Model> =
+			overrides.fingerprinted(fingerprint).into_iter().cloned().collect();
+		for moved in found {
+			// copies of the file are left alone
+			if tokio::fs::try_exists(&moved.path).await.unwrap_or(true) {
+				continue;
+			}
+			overrides::Entity::update_many()
+				.col_expr(overrides::Column::Path, Expr::value(path.as_str()))
+				.filter(overrides::Column::Path.eq(moved.path.as_str()))
+				.exec(&self.db)
+				.await?;
+			let updated = overrides::Model {
+				path: path.clone(),
+				..moved.clone()
+			};
+			overrides.update_file(&moved.path, updated);
+			return Ok(Some((&moved).into()));
+		}
+		Ok(None)
+	}
+
+	/// Overrides of the database and the override files to resolve the overrides of a scan.
+	pub async fn override_cache(&self) -> Result<OverrideCache> {
+		Ok(OverrideCache::new(self.overrides().await?))
+	}
+
+	/// Override of the path merged field by field: the own one, then the ones of the path and the folders
+	/// above it from the nearest up. Each of them is taken from the database, then the own override file of
+	/// the folder, then the `[files]` entry of the override file of its parent.
+	pub async fn resolve_override(
+		&self,
+		path: &Path,
+		fingerprint: Option<&str>,
+		overrides: &mut OverrideCache,
+	) -> Result<Override> {
+		let mut resolved = self.file_override(path, fingerprint, overrides).await?.unwrap_or_default();
+		for folder in path.ancestors() {
+			if let Some(found) = overrides.folder(folder) {
+				resolved = resolved.or(found.clone());
+			}
+			if let Some(file) = overrides.override_file(folder).await {
+				resolved = resolved.or(file.folder.clone());
+			}
+			if let (Some(parent), Some(name)) = (folder.parent(), folder.file_name()) {
+				let name = name.to_string_lossy();
+				if let Some(found) = overrides.override_file(parent).await.and_then(|file| file.file(&name)) {
+					resolved = resolved.or(found.clone());
+				}
+			}
+		}
+		Ok(resolved)
+	}
+}
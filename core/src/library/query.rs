@@ -0,0 +1,412 @@
+use std::str::FromStr;
+
+use regex::Regex;
+use sea_orm::{
+	sea_query::{Condition, Expr, Func, SimpleExpr},
+	Order,
+};
+
+use crate::{
+	errors::{MediaOrderError, Result},
+	library::entities::files,
+};
+
+/// How the value of the query field is matched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FieldKind {
+	/// Original or English name contains the value.
+	Name,
+	/// Column contains the value.
+	Contains,
+	/// Case insensitive equality.
+	Text,
+	/// Comma separated list (languages, genres) has the value.
+	List,
+	Number,
+	/// Bytes, value with `K`, `M`, `G` or `T` unit.
+	Size,
+	/// Seconds, value in minutes or with `h`, `m`, `s` units (`1h30m`).
+	Duration,
+	/// `vres` name (`720p`) or frame height, compared by the height.
+	Resolution,
+}
+
+lazy_static! {
+	/// Query fields with their columns and the way the value is matched.
+	static ref FIELDS: Vec<(&'static str, files::Column, FieldKind)> = vec![
+		("name", files::Column::Name, FieldKind::Name),
+		("title", files::Column::Name, FieldKind::Name),
+		("path", files::Column::Path, FieldKind::Contains),
+		("error", files::Column::Error, FieldKind::Contains),
+		("kind", files::Column::Kind, FieldKind::Text),
+		("format", files::Column::Format, FieldKind::Text),
+		("role", files::Column::Role, FieldKind::Text),
+		("ext", files::Column::Ext, FieldKind::Text),
+		("venc", files::Column::Venc, FieldKind::Text),
+		("aenc", files::Column::Aenc, FieldKind::Text),
+		("vqual", files::Column::Vqual, FieldKind::Text),
+		("imdb", files::Column::ImdbId, FieldKind::Text),
+		("lang", files::Column::Lang, FieldKind::List),
+		("audio", files::Column::AudioLangs, FieldKind::List),
+		("genre", files::Column::Genres, FieldKind::List),
+		("warning", files::Column::Warnings, FieldKind::List),
+		("integrity", files::Column::Integrity, FieldKind::Text),
+		("year", files::Column::Year, FieldKind::Number),
+		("part", files::Column::Part, FieldKind::Number),
+		("season", files::Column::Season, FieldKind::Number),
+		("episode", files::Column::Episode, FieldKind::Number),
+		("height", files::Column::Height, FieldKind::Number),
+		("rating", files::Column::Rating, FieldKind::Number),
+		("size", files::Column::Size, FieldKind::Size),
+		("duration", files::Column::Duration, FieldKind::Duration),
+		("modified", files::Column::Modified, FieldKind::Number),
+		("checked", files::Column::Checked, FieldKind::Number),
+		("decode_errors", files::Column::DecodeErrors, FieldKind::Number),
+		("vres", files::Column::Vres, FieldKind::Resolution),
+		("res", files::Column::Vres, FieldKind::Resolution),
+	];
+	static ref DURATION: Regex =
+		Regex::new(r"^(?:(\d+(?:\.\d+)?)h)?(?:(\d+(?:\.\d+)?)m(?:in)?)?(?:(\d+(?:\.\d+)?)s)?$").unwrap();
+}
+
+/// Operators in the order they are looked for.
+const OPERATORS: [(&str, Op); 7] = [
+	(">=", Op::Ge),
+	("<=", Op::Le),
+	("!=", Op::Ne),
+	(":", Op::Eq),
+	("=", Op::Eq),
+	(">", Op::Gt),
+	("<", Op::Lt),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+	Eq,
+	Ne,
+	Gt,
+	Ge,
+	Lt,
+	Le,
+}
+
+fn parse_error(what: &str, value: &str) -> MediaOrderError {
+	MediaOrderError::ParseError(what.to_owned(), value.to_owned())
+}
+
+fn field(name: &str) -> Option<(files::Column, FieldKind)> {
+	FIELDS
+		.iter()
+		.find(|(field, _, _)| field.eq_ignore_ascii_case(name))
+		.map(|(_, column, kind)| (*column, *kind))
+}
+
+fn parse_size(value: &str) -> Option<f64> {
+	let value = value.to_uppercase();
+	let value = value.trim_end_matches("IB").trim_end_matches('B');
+	let (number, multiplier) = match value.chars().last()? {
+		'K' => (&value[..value.len() - 1], 1u64 << 10),
+		'M' => (&value[..value.len() - 1], 1 << 20),
+		'G' => (&value[..value.len() - 1], 1 << 30),
+		'T' => (&value[..value.len() - 1], 1 << 40),
+		_ => (value, 1),
+	};
+	number.parse::<f64>().ok().map(|number| number * multiplier as f64)
+}
+
+fn parse_duration(value: &str) -> Option<f64> {
+	if let Ok(minutes) = value.parse::<f64>() {
+		return Some(minutes * 60.0);
+	}
+	let captures = DURATION.captures(value).filter(|_| !value.is_empty())?;
+	let part = |index: usize| {
+		captures
+			.get(index)
+			.and_then(|part| part.as_str().parse::<f64>().ok())
+			.unwrap_or_default()
+	};
+	Some(part(1) * 3600.0 + part(2) * 60.0 + part(3))
+}
+
+/// Frame height of the resolution name: `720p`, `1080i`, `4k`.
+fn parse_height(value: &str) -> Option<f64> {
+	match value {
+		"4k" | "uhd" => Some(2160.0),
+		"sd" => Some(480.0),
+		_ => value.trim_end_matches(['p', 'i']).parse().ok(),
+	}
+}
+
+fn parse_number(kind: FieldKind, value: &str) -> Result<f64> {
+	match kind {
+		FieldKind::Size => parse_size(value),
+		FieldKind::Duration => parse_duration(value),
+		FieldKind::Resolution => parse_height(value),
+		_ => value.parse().ok(),
+	}
+	.ok_or_else(|| parse_error("query number", value))
+}
+
+/// Lowercased column value, empty string for `NULL`.
+fn lower(column: files::Column) -> SimpleExpr {
+	Func::lower(Expr::col(column).if_null("")).into()
+}
+
+fn compare(expr: Expr, op: Op, value: f64) -> SimpleExpr {
+	match op {
+		Op::Eq => expr.eq(value),
+		Op::Ne => expr.ne(value),
+		Op::Gt => expr.gt(value),
+		Op::Ge => expr.gte(value),
+		Op::Lt => expr.lt(value),
+		Op::Le => expr.lte(value),
+	}
+}
+
+/// Numeric equality with the value or the inclusive range `a..b`, `a..`, `..b`.
+fn number_eq(column: files::Column, kind: FieldKind, value: &str) -> Result<SimpleExpr> {
+	let Some((from, to)) = value.split_once("..") else {
+		return Ok(Expr::col(column).eq(parse_number(kind, value)?));
+	};
+	match (from.is_empty(), to.is_empty()) {
+		(false, false) => Ok(Expr::col(column).between(parse_number(kind, from)?, parse_number(kind, to)?)),
+		(false, true) => Ok(Expr::col(column).gte(parse_number(kind, from)?)),
+		(true, false) => Ok(Expr::col(column).lte(parse_number(kind, to)?)),
+		(true, true) => Err(parse_error("query range", value)),
+	}
+}
+
+/// Positive match of the single value.
+fn value_eq(column: files::Column, kind: FieldKind, value: &str) -> Result<SimpleExpr> {
+	let lowered = value.to_lowercase();
+	if lowered == "none" {
+		return Ok(match kind {
+			FieldKind::Name => Expr::col(files::Column::Name).is_null(),
+			_ => Expr::expr(lower(column)).eq(""),
+		});
+	}
+	Ok(match kind {
+		FieldKind::Name => Expr::col(files::Column::Name)
+			.like(format!("%{value}%"))
+			.or(Expr::col(files::Column::NameEnglish).like(format!("%{value}%"))),
+		FieldKind::Contains => Expr::col(column).like(format!("%{value}%")),
+		FieldKind::Text => Expr::expr(lower(column)).eq(lowered),
+		FieldKind::List => Expr::cust_with_exprs("(',' || ? || ',') LIKE ?", [
+			lower(column),
+			Expr::val(format!("%,{lowered},%")).into(),
+		]),
+		FieldKind::Resolution => match number_eq(files::Column::Height, kind, &lowered) {
+			Ok(height) => Expr::expr(lower(column)).eq(lowered).or(height),
+			Err(_) => Expr::expr(lower(column)).eq(lowered),
+		},
+		FieldKind::Number | FieldKind::Size | FieldKind::Duration => number_eq(column, kind, value)?,
+	})
+}
+
+/// Condition of the `field<op>value` term. Comma separated values of `:` match any of them, negation matches
+/// the records without the field value too.
+fn field_condition(column: files::Column, kind: FieldKind, op: Op, value: &str) -> Result<SimpleExpr> {
+	if matches!(op, Op::Eq | Op::Ne) {
+		let values: Vec<&str> = match kind {
+			FieldKind::Name | FieldKind::Contains => vec![value],
+			_ => value.split(',').collect(),
+		};
+		let mut any = value_eq(column, kind, values[0])?;
+		for value in &values[1..] {
+			any = any.or(value_eq(column, kind, value)?);
+		}
+		return Ok(match op {
+			Op::Ne => negate(any),
+			_ => any,
+		});
+	}
+
+	match kind {
+		FieldKind::Number | FieldKind::Size | FieldKind::Duration => {
+			Ok(compare(Expr::col(column), op, parse_number(kind, value)?))
+		}
+		FieldKind::Resolution => Ok(compare(
+			Expr::col(files::Column::Height),
+			op,
+			parse_number(kind, &value.to_lowercase())?,
+		)),
+		_ => Err(parse_error("query comparison", value)),
+	}
+}
+
+/// `NULL` (unknown value) is not a match, so it is negated as false.
+fn negate(expr: SimpleExpr) -> SimpleExpr {
+	SimpleExpr::from(Func::coalesce([expr, Expr::val(false).into()])).not()
+}
+
+fn parse_term(term: &str) -> Result<SimpleExpr> {
+	let (negated, term) = match term.strip_prefix('-') {
+		Some(rest) if !rest.is_empty() => (true, rest),
+		_ => (false, term),
+	};
+	let field_end = term.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(term.len());
+	let (name, rest) = term.split_at(field_end);
+	let operator = OPERATORS.iter().find(|(operator, _)| rest.starts_with(operator));
+
+	let expr = match (operator, field(name)) {
+		(Some((operator, op)), Some((column, kind))) => {
+			field_condition(column, kind, *op, &rest[operator.len()..])?
+		}
+		(Some(_), None) if !name.is_empty() => return Err(parse_error("query field", name)),
+		_ => value_eq(files::Column::Name, FieldKind::Name, term)?,
+	};
+	Ok(if negated { negate(expr) } else { expr })
+}
+
+/// Splits the query by whitespace, double quoted parts are kept together (`name:"the matrix"`).
+fn terms(query: &str) -> Vec<String> {
+	let mut terms = vec![];
+	let mut term = String::new();
+	let mut quoted = false;
+	for c in query.chars() {
+		match c {
+			'"' => quoted = !quoted,
+			c if c.is_whitespace() && !quoted => {
+				if !term.is_empty() {
+					terms.push(std::mem::take(&mut term));
+				}
+			}
+			c => term.push(c),
+		}
+	}
+	if !term.is_empty() {
+		terms.push(term);
+	}
+	terms
+}
+
+/// Library search query: whitespace separated terms, all of them have to match.
+///
+/// - `field:value` (or `field=value`) equality, `field:a,b` any of the values, `field:none` the field is empty;
+/// - `field>value`, `field>=value`, `field<value`, `field<=value` comparison of the numeric fields;
+/// - `field:a..b`, `field:a..`, `field:..b` inclusive range of the numeric fields;
+/// - `-field:value` or `field!=value` negation;
+/// - bare words match the name.
+///
+/// Fields are listed in `FIELDS`, e.g. `vres:720p venc:xvid year<2005` or `kind:video -audio:eng`.
+#[derive(Clone, Debug)]
+pub struct Query {
+	condition: Condition,
+	order: Vec<(files::Column, Order)>,
+	limit: Option<u64>,
+}
+
+impl Default for Query {
+	fn default() -> Self {
+		Self {
+			condition: Condition::all(),
+			order: vec![],
+			limit: None,
+		}
+	}
+}
+
+impl FromStr for Query {
+	type Err = MediaOrderError;
+
+	fn from_str(query: &str) -> Result<Self> {
+		let mut condition = Condition::all();
+		for term in terms(query) {
+			condition = condition.add(parse_term(&term)?);
+		}
+		Ok(Self {
+			condition,
+			..Default::default()
+		})
+	}
+}
+
+impl Query {
+	/// Sorts by the comma separated fields, `-` prefix for descending order (`-rating,year`).
+	pub fn with_sort(mut self, sort: &str) -> Result<Self> {
+		for key in sort.split(',').filter(|key| !key.is_empty()) {
+			let (name, order) = match key.strip_prefix('-') {
+				Some(name) => (name, Order::Desc),
+				None => (key, Order::Asc),
+			};
+			let (column, kind) = field(name).ok_or_else(|| parse_error("sort field", name))?;
+			let column = match kind {
+				FieldKind::Resolution => files::Column::Height,
+				_ => column,
+			};
+			self.order.push((column, order));
+		}
+		Ok(self)
+	}
+
+	pub fn with_limit(mut self, limit: Option<u64>) -> Self {
+		self.limit = limit;
+		self
+	}
+
+	pub(crate) fn condition(&self) -> Condition {
+		self.condition.clone()
+	}
+
+	pub(crate) fn order(&self) -> Vec<(files::Column, Order)> {
+		self.order.clone()
+	}
+
+	pub(crate) fn limit(&self) -> Option<u64> {
+		self.limit
+	}
+}
+
+pub(crate) fn human_size(size: i64) -> String {
+	let mut size = size as f64;
+	for unit in ["B", "K", "M", "G"] {
+		if size < 1024.0 {
+			return format!("{size:.1}{unit}");
+		}
+		size /= 1024.0;
+	}
+	format!("{size:.1}T")
+}
+
+/// Plain text table of the search results, one record per line.
+pub fn table(records: &[files::Model]) -> String {
+	let rows: Vec<[String; 8]> = records
+		.iter()
+		.map(|record| {
+			[
+				record.name.clone().unwrap_or_default(),
+				record.year.map(|year| year.to_string()).unwrap_or_default(),
+				record
+					.vres
+					.clone()
+					.or(record.height.map(|height| format!("{height}p")))
+					.unwrap_or_default(),
+				record.venc.clone().unwrap_or_default(),
+				record.audio_langs.clone().or(record.lang.clone()).unwrap_or_default(),
+				record
+					.duration
+					.map(|duration| format!("{:.0}m", duration / 60.0))
+					.unwrap_or_default(),
+				human_size(record.size),
+				record.path.clone(),
+			]
+		})
+		.collect();
+	let header = ["NAME", "YEAR", "RES", "VENC", "AUDIO", "TIME", "SIZE", "PATH"].map(String::from);
+
+	let mut widths = header.clone().map(|title| title.chars().count());
+	for row in &rows {
+		for (width, cell) in widths.iter_mut().zip(row) {
+			*width = (*width).max(cell.chars().count());
+		}
+	}
+	std::iter::once(&header)
+		.chain(&rows)
+		.map(|row| {
+			let cells: Vec<String> =
+				row.iter().zip(widths).map(|(cell, width)| format!("{cell:width$}")).collect();
+			cells.join("  ").trim_end().to_owned()
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
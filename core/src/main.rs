@@ -1,18 +1,192 @@
+mod config;
+mod disc;
 mod errors;
+mod hook;
+mod identify;
+mod integrity;
+mod languages;
+mod library;
+mod media;
+mod metadata;
+mod nfo;
+mod organise;
+mod overrides;
+mod report;
+mod rules;
+mod scanner;
+mod series;
 mod types;
 pub mod video;
+mod view;
+mod watch;
 
 #[macro_use] extern crate lazy_static;
 
-use std::{env, path::PathBuf};
+use std::{
+	collections::HashSet,
+	env,
+	path::{Path, PathBuf},
+};
 
-use log::debug;
+use clap::{Parser, Subcommand, ValueEnum};
+use config::{env_or, HookConfig, OrganiseConfig, ScanConfig, ViewConfig, WatchConfig};
+use disc::Disc;
+use hook::Hook;
+use integrity::{CheckMode, IntegrityStatus};
+use library::{query::Query, Library};
+use log::{debug, error, info, warn};
+use media::{group_parts, TitleInfo};
+use media_order_imdb::{config::DatabaseConfig, local::open_database, provider::local::LocalProvider};
+use nfo::NfoKind;
+use overrides::Override;
+use report::Report;
+use rules::ScanRules;
+use scanner::Scanner;
 use simple_logger::SimpleLogger;
-use tokio::task::JoinSet;
-use types::{FSEntry, FromPath};
 use video::Video;
+use view::OrganisedView;
+use watch::LibraryWatcher;
 
-use crate::errors::{MediaOrderError, Result};
+use crate::errors::Result;
+
+#[derive(Parser)]
+#[command(version, about = "Ordering media files in local storage")]
+struct Cli {
+	#[command(subcommand)]
+	command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Scan the library folder (VIDEO_LIBRARY_PATH by default), update the library database and the organised
+	/// view (VIEW_PATH) when it is set
+	Scan { path: Option<PathBuf> },
+	/// Watch the library folder and update the library database on changes
+	Watch { path: Option<PathBuf> },
+	/// Search the library database, e.g. `vres:720p venc:xvid year<2005` or `kind:video -audio:eng`
+	Search {
+		query: Vec<String>,
+		/// Comma separated fields to sort by, `-` prefix for descending order
+		#[arg(long, allow_hyphen_values = true)]
+		sort: Option<String>,
+		#[arg(long)]
+		limit: Option<u64>,
+		#[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+		format: OutputFormat,
+	},
+	/// Print library statistics and health report (probe failures, empty and truncated files, duplicates)
+	Report {
+		#[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+		format: ReportFormat,
+	},
+	/// List the series episodes by season with the missing and duplicate ones
+	Series {
+		/// Only the shows with missing or duplicate episodes
+		#[arg(long)]
+		gaps: bool,
+		/// Compare the identified shows with the episode lists of the local IMDb database (IMDB_DATABASE)
+		#[arg(long)]
+		official: bool,
+		#[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+		format: OutputFormat,
+	},
+	/// Check integrity of the scanned videos (truncated and corrupted files) and store the results, searchable
+	/// as `integrity:truncated,corrupt,unreadable`
+	Check {
+		path: Option<PathBuf>,
+		/// quick (durations and size), sampled (decode segments) or deep (decode everything)
+		#[arg(long, default_value = "sampled")]
+		mode: CheckMode,
+		/// Check the files already checked since their last modification too
+		#[arg(long)]
+		all: bool,
+	},
+	/// Propose languages of the untagged audio streams from the languages in the file names (dry run by
+	/// default)
+	Languages {
+		path: Option<PathBuf>,
+		/// Write proposed languages into the files (lossless remux)
+		#[arg(long)]
+		apply: bool,
+	},
+	/// Identify the scanned videos by the local IMDb database (IMDB_DATABASE) and store their IMDb ids, genres
+	/// and ratings, searchable as `imdb:`, `genre:` and `rating:`
+	Identify {
+		path: Option<PathBuf>,
+		/// Identify the already identified videos again
+		#[arg(long)]
+		all: bool,
+	},
+	/// Write Kodi NFO files of the identified videos, the existing NFO files of other titles are kept
+	Nfo { path: Option<PathBuf> },
+	/// Place completed download into the organised library (ORGANISE_PATH), to be run by the torrent client.
+	/// Exit codes: 0 placed, 1 failed, 2 no media found, 3 target exists, 4 category is not handled
+	Hook {
+		path: PathBuf,
+		/// Torrent client category, available as `{category}` in the rename template
+		#[arg(long)]
+		category: Option<String>,
+		/// Comma separated torrent client tags
+		#[arg(long, value_delimiter = ',')]
+		tags: Vec<String>,
+		/// hardlink, symlink, copy or move (ORGANISE_MODE by default)
+		#[arg(long)]
+		mode: Option<String>,
+	},
+	/// Pin identification of the file, or of everything in the folder, kept across rescans and renames. Prints
+	/// the override of the path without options, and all the overrides without the path
+	Override {
+		path: Option<PathBuf>,
+		#[arg(long)]
+		imdb: Option<String>,
+		#[arg(long)]
+		title: Option<String>,
+		#[arg(long)]
+		year: Option<u16>,
+		/// Added to the parsed season numbers
+		#[arg(long, allow_hyphen_values = true)]
+		season_offset: Option<i32>,
+		/// Keep the path out of the library, `--ignore false` takes it back from the ignored folder
+		#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+		ignore: Option<bool>,
+		/// Remove the override of the path
+		#[arg(long)]
+		clear: bool,
+	},
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+	Table,
+	Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+	Text,
+	Json,
+	Html,
+}
+
+fn library_path(path: Option<PathBuf>) -> Result<PathBuf> {
+	absolute_path(&path.unwrap_or_else(|| {
+		PathBuf::from(env::var("VIDEO_LIBRARY_PATH").expect("VIDEO_LIBRARY_PATH is not set"))
+	}))
+}
+
+/// View of the library when its root is configured.
+fn organised_view() -> Result<Option<OrganisedView>> {
+	let view = ViewConfig::from_env()?;
+	Ok(view.root.map(|root| OrganisedView::new(root).with_template(view.template)))
+}
+
+/// Path as the library stores it, the gone file keeps its absolute path.
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+	match path.canonicalize() {
+		Ok(path) => Ok(path),
+		Err(_) => Ok(std::path::absolute(path)?),
+	}
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -20,17 +194,259 @@ async fn main() -> Result<()> {
 	Video::init();
 
 	dotenvy::dotenv().ok();
-	let video_library_path = env::var("VIDEO_LIBRARY_PATH").expect("VIDEO_LIBRARY_PATH is not set");
+	let cli = Cli::parse();
+	let library =
+		Library::open(&PathBuf::from(env_or("LIBRARY_DATABASE", "media_order.sqlite".to_owned())?)).await?;
+
+	match cli.command.unwrap_or(Command::Scan { path: None }) {
+		Command::Scan { path: scanned } => {
+			let path = library_path(scanned.clone())?;
+			let (progress, videos) = library
+				.scan(path.clone(), ScanConfig::from_env()?, ScanRules::from_env()?)
+				.await?;
+			info!("{}", progress);
+
+			let items = group_parts(videos);
+			if let Some(view) = organised_view()? {
+				// the scan of a subfolder keeps the rest of the view
+				match scanned {
+					Some(_) => view.sync_under(&items, &path)?,
+					None => view.sync(&items)?,
+				};
+			}
 
-	let path = PathBuf::from(video_library_path);
-	let mut set = JoinSet::new();
-	set.spawn(FSEntry::from_path(path));
+			for item in items {
+				debug!("{:#?}", item);
+			}
+		}
+		Command::Watch { path } => {
+			let path = library_path(path)?;
+			let (progress, _) = library
+				.scan(path.clone(), ScanConfig::from_env()?, ScanRules::from_env()?)
+				.await?;
+			info!("{}", progress);
 
-	while let Some(entry) = set.join_next().await {
-		debug!("{:#?}", entry);
-		if let Ok(FSEntry::Folder(entries)) = entry.map_err(MediaOrderError::JoinError)? {
-			for entry in entries {
-				set.spawn(FSEntry::from_path(entry));
+			let mut watcher =
+				LibraryWatcher::new(&library, path, ScanConfig::from_env()?, ScanRules::from_env()?)
+					.with_config(WatchConfig::from_env()?);
+			if let Some(view) = organised_view()? {
+				watcher = watcher.with_view(view);
+			}
+			watcher
+				.run(async {
+					let _ = tokio::signal::ctrl_c().await;
+				})
+				.await?;
+		}
+		Command::Search {
+			query,
+			sort,
+			limit,
+			format,
+		} => {
+			let query = query
+				.join(" ")
+				.parse::<Query>()?
+				.with_sort(sort.as_deref().unwrap_or_default())?
+				.with_limit(limit);
+			let records = library.search(&query).await?;
+			match format {
+				OutputFormat::Table => println!("{}", library::query::table(&records)),
+				OutputFormat::Json => {
+					println!("{}", serde_json::to_string_pretty(&records).expect("records are serializable"))
+				}
+			}
+		}
+		Command::Report { format } => {
+			let report = Report::new(&library.files().await?);
+			match format {
+				ReportFormat::Text => print!("{}", report.text()),
+				ReportFormat::Json => {
+					println!("{}", serde_json::to_string_pretty(&report).expect("report is serializable"))
+				}
+				ReportFormat::Html => print!("{}", report.html()),
+			}
+		}
+		Command::Series { gaps, official, format } => {
+			let mut shows = series::shows(&library.files().await?);
+			if official {
+				let provider =
+					LocalProvider::new(open_database(&DatabaseConfig::from_env()?.read_only()).await?);
+				series::compare_official(&mut shows, &provider).await?;
+			}
+			if gaps {
+				shows.retain(|show| show.has_gaps());
+			}
+			match format {
+				OutputFormat::Table => print!("{}", series::text(&shows)),
+				OutputFormat::Json => {
+					println!("{}", serde_json::to_string_pretty(&shows).expect("shows are serializable"))
+				}
+			}
+		}
+		Command::Check { path, mode, all } => {
+			let records = library.files_under(&library_path(path)?).await?;
+			for record in records {
+				if record.kind != library::KIND_VIDEO || (record.integrity.is_some() && !all) {
+					continue;
+				}
+				let path = PathBuf::from(record.path);
+				let checked = path.clone();
+				let integrity = tokio::task::spawn_blocking(move || integrity::check(&checked, mode)).await?;
+				library.set_integrity(&path, &integrity).await?;
+				if integrity.status != IntegrityStatus::Ok {
+					println!("{}\t{}: {}", integrity.status, path.display(), integrity.issues.join("; "));
+				}
+			}
+		}
+		Command::Languages { path, apply } => {
+			let (config, rules) = (ScanConfig::from_env()?, ScanRules::from_env()?);
+			// the dry run doesn't touch the library
+			let (_, videos) = match apply {
+				true => library.scan(library_path(path)?, config, rules).await?,
+				false => Scanner::new(config).with_rules(rules).videos(library_path(path)?).await?,
+			};
+
+			for video in videos {
+				let proposals = languages::propose(&video);
+				if proposals.is_empty() {
+					continue;
+				}
+				println!("{}", video.path.display());
+				for proposal in &proposals {
+					println!("\t{proposal}");
+				}
+				if apply {
+					if let Err(err) = languages::apply(&video, &proposals) {
+						error!("{err}");
+					}
+				}
+			}
+		}
+		Command::Identify { path, all } => {
+			let provider = LocalProvider::new(open_database(&DatabaseConfig::from_env()?.read_only()).await?);
+			let mut overrides = library.override_cache().await?;
+			for record in library.files_under(&library_path(path)?).await? {
+				let identified = record.genres.is_some() || record.rating.is_some();
+				if !identify::is_identifiable(&record) || (identified && !all) {
+					continue;
+				}
+				let path = PathBuf::from(&record.path);
+				match identify::identify(&provider, &record).await {
+					Ok(Some(title)) => {
+						if library.set_title(&path, &title, &mut overrides).await? {
+							println!("{}\t{}\t{}", title.imdb_id, title.title, path.display());
+						}
+					}
+					Ok(None) => {}
+					Err(err) => error!("{:?}: {err}", path),
+				}
+			}
+		}
+		Command::Nfo { path } => {
+			let (_, videos) = library
+				.scan(library_path(path)?, ScanConfig::from_env()?, ScanRules::from_env()?)
+				.await?;
+
+			// episodes are identified as their show, which gets one tvshow.nfo
+			let mut shows = HashSet::new();
+			for item in group_parts(videos) {
+				if item.is_extra() {
+					continue;
+				}
+				let video = item.main();
+				let record_path = Disc::root_of(&video.path).unwrap_or(&video.path);
+				let Some(title) = library.get(record_path).await?.as_ref().and_then(TitleInfo::from_record)
+				else {
+					continue;
+				};
+				let mut written = vec![nfo::write_item(&item, &title)];
+				let is_episode = video.season.is_some() && video.episode.is_some();
+				if is_episode && nfo::nfo_path(&item, NfoKind::TvShow).is_some_and(|show| shows.insert(show))
+				{
+					written.push(nfo::write_show(&item, &title));
+				}
+				for written in written {
+					match written {
+						Ok(Some(path)) => println!("{}", path.display()),
+						Ok(None) => {}
+						Err(err) => error!("{:?}: {err}", video.path),
+					}
+				}
+			}
+		}
+		Command::Hook {
+			path,
+			category,
+			tags,
+			mode,
+		} => {
+			let mut config = OrganiseConfig::from_env()?;
+			if let Some(mode) = mode {
+				config.mode = mode.parse()?;
+			}
+			let mut hook = Hook::new(&library, config)
+				.with_hook_config(HookConfig::from_env()?)
+				.with_scan(ScanConfig::from_env()?, ScanRules::from_env()?);
+			if let Some(view) = organised_view()? {
+				hook = hook.with_view(view);
+			}
+			// the download is placed by the parsed names without the IMDb database
+			let provider = match open_database(&DatabaseConfig::from_env()?.read_only()).await {
+				Ok(db) => Some(LocalProvider::new(db)),
+				Err(err) => {
+					warn!("Not identifying {:?}: {err}", path);
+					None
+				}
+			};
+			if let Some(provider) = &provider {
+				hook = hook.with_provider(provider);
+			}
+			let status = hook.run(path, category.as_deref(), &tags).await;
+			std::process::exit(status.code());
+		}
+		Command::Override {
+			path,
+			imdb,
+			title,
+			year,
+			season_offset,
+			ignore,
+			clear,
+		} => {
+			let Some(path) = path else {
+				for found in library.overrides().await? {
+					println!("{}\t{}", found.path, Override::from(&found));
+				}
+				return Ok(());
+			};
+			// stored like the scanned paths
+			let path = absolute_path(&path)?;
+			let changes = Override {
+				imdb_id: imdb,
+				title,
+				year,
+				season_offset,
+				ignore,
+			};
+			if !clear && changes.is_empty() {
+				if let Some(found) = library.get_override(&path).await? {
+					println!("{}\t{found}", path.display());
+				}
+				return Ok(());
+			}
+
+			if clear {
+				library.remove_override(&path).await?;
+			} else {
+				let current = library.get_override(&path).await?.unwrap_or_default();
+				library.set_override(&path, &changes.or(current)).await?;
+			}
+			// records of the path are updated right away
+			if path.exists() {
+				let (progress, _) =
+					library.scan(path, ScanConfig::from_env()?, ScanRules::from_env()?).await?;
+				info!("{}", progress);
 			}
 		}
 	}
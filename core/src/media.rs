@@ -0,0 +1,189 @@
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	library::entities::files,
+	video::{Video, VideoRole},
+};
+
+/// Logical media item: a single video file or all parts (`CD1`, `CD2`, `.001`, ...) of one release, with
+/// samples, trailers and other extras of the release.
+#[derive(Debug)]
+pub struct MediaItem {
+	pub videos: Vec<Video>,
+	pub extras: Vec<Video>,
+}
+
+impl MediaItem {
+	pub fn new(video: Video) -> Self {
+		Self {
+			videos: vec![video],
+			extras: vec![],
+		}
+	}
+
+	pub fn main(&self) -> &Video {
+		&self.videos[0]
+	}
+
+	pub fn name(&self) -> &str {
+		&self.main().name_original
+	}
+
+	pub fn year(&self) -> Option<u16> {
+		self.main().year
+	}
+
+	pub fn is_multipart(&self) -> bool {
+		self.main().part.is_some()
+	}
+
+	/// Item made of extras only (trailer or sample without the main feature).
+	pub fn is_extra(&self) -> bool {
+		self.main().role != VideoRole::Main
+	}
+
+	pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+		self.videos.iter().map(|video| &video.path)
+	}
+
+	/// Part numbers absent between the first and the last found part.
+	pub fn missing_parts(&self) -> Vec<u8> {
+		let parts: Vec<u8> = self.videos.iter().filter_map(|video| video.part).collect();
+		match parts.iter().max() {
+			Some(&max) => (1..=max).filter(|part| !parts.contains(part)).collect(),
+			None => vec![],
+		}
+	}
+}
+
+/// Title the media item is identified as (by IMDb).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TitleInfo {
+	pub imdb_id: String,
+	pub title: String,
+	pub original_title: Option<String>,
+	pub year: Option<u16>,
+	pub genres: Vec<String>,
+	/// Runtime in minutes.
+	pub runtime: Option<u32>,
+	/// Average IMDb rating.
+	pub rating: Option<f64>,
+}
+
+impl TitleInfo {
+	/// Title stored for the library record by [`crate::library::Library::set_title`], none for the
+	/// unidentified one. The records identified by the IMDb id alone (e.g. from the NFO) are titled by the
+	/// parsed name.
+	pub fn from_record(record: &files::Model) -> Option<TitleInfo> {
+		let name = record.name.clone()?;
+		let (title, original_title) = match &record.title {
+			Some(title) => (title.clone(), record.original_title.clone()),
+			None => (
+				record.name_english.clone().unwrap_or_else(|| name.clone()),
+				record.name_english.is_some().then_some(name),
+			),
+		};
+		Some(TitleInfo {
+			imdb_id: record.imdb_id.clone()?,
+			title,
+			original_title,
+			year: record.year.and_then(|year| u16::try_from(year).ok()),
+			genres: record
+				.genres
+				.iter()
+				.flat_map(|genres| genres.split(','))
+				.map(str::to_owned)
+				.collect(),
+			runtime: record.duration.map(|duration| (duration / 60.0).round() as u32),
+			rating: record.rating,
+		})
+	}
+
+	/// Names the video by the title, the episodes keep their own year.
+	pub fn apply(&self, video: &mut Video) {
+		video.imdb_id = Some(self.imdb_id.clone());
+		video.name_original = self.title.clone();
+		video.name_english = None;
+		if let (Some(year), false) = (self.year, video.is_episode()) {
+			video.year = Some(year);
+		}
+	}
+}
+
+type GroupKey = (Option<PathBuf>, String, Option<u16>);
+
+fn group_key(video: &Video) -> GroupKey {
+	(
+		video.path.parent().map(PathBuf::from),
+		video.name_original.to_lowercase(),
+		video.year,
+	)
+}
+
+/// Release folder of the video: the parent folder, or the one above for `Sample`, `Extras`, ... folders.
+pub fn release_folder(video: &Video) -> Option<&Path> {
+	let parent = video.path.parent()?;
+	let is_role_folder = parent
+		.file_name()
+		.and_then(|name| name.to_str())
+		.and_then(VideoRole::from_folder);
+	match is_role_folder {
+		Some(_) => parent.parent(),
+		None => Some(parent),
+	}
+}
+
+/// Groups sibling parts of the same release (same folder, name and year) into one [`MediaItem`].
+/// Videos without part marker always make their own item. Items keep the order of the first met part.
+/// Extras are attached to the main item of the release folder with the same name, or to the only main item
+/// of the folder, otherwise they make their own item.
+pub fn group_parts(videos: impl IntoIterator<Item = Video>) -> Vec<MediaItem> {
+	let mut items: Vec<MediaItem> = vec![];
+	let mut groups: HashMap<GroupKey, usize> = HashMap::new();
+	let (videos, extras): (Vec<Video>, Vec<Video>) =
+		videos.into_iter().partition(|video| video.role == VideoRole::Main);
+
+	for video in videos {
+		if video.part.is_none() {
+			items.push(MediaItem::new(video));
+			continue;
+		}
+
+		match groups.get(&group_key(&video)) {
+			Some(&index) => items[index].videos.push(video),
+			None => {
+				groups.insert(group_key(&video), items.len());
+				items.push(MediaItem::new(video));
+			}
+		}
+	}
+
+	for item in items.iter_mut() {
+		item.videos.sort_by_key(|video| video.part);
+	}
+
+	for extra in extras {
+		let folder = release_folder(&extra);
+		let candidates: Vec<usize> = (0..items.len())
+			.filter(|&i| release_folder(items[i].main()) == folder)
+			.collect();
+		let index = candidates
+			.iter()
+			.find(|&&i| items[i].name().eq_ignore_ascii_case(&extra.name_original))
+			.or(if candidates.len() == 1 {
+				candidates.first()
+			} else {
+				None
+			});
+
+		match index {
+			Some(&i) => items[i].extras.push(extra),
+			None => items.push(MediaItem::new(extra)),
+		}
+	}
+
+	items
+}
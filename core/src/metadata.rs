@@ -0,0 +1,242 @@
+extern crate ffmpeg_the_third as ffmpeg;
+
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use ffmpeg::{codec, encoder, format, format::stream::Disposition, media, Dictionary, Rational};
+use log::{debug, info};
+
+use crate::{
+	errors::{MediaOrderError, Result},
+	media::TitleInfo,
+};
+
+/// Max difference of the source and the remuxed durations, seconds.
+const DURATION_TOLERANCE: f64 = 1.0;
+
+/// Tags of the stream, `None` keeps the source value.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamTags {
+	/// ISO 639-2 language code.
+	pub language: Option<String>,
+	pub default: Option<bool>,
+	pub forced: Option<bool>,
+}
+
+/// Tags written into the container, `None` keeps the source value.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetadataTags {
+	pub title: Option<String>,
+	pub year: Option<u16>,
+	pub imdb_id: Option<String>,
+	/// Tags by the source stream index.
+	pub streams: BTreeMap<usize, StreamTags>,
+}
+
+impl From<&TitleInfo> for MetadataTags {
+	fn from(title: &TitleInfo) -> Self {
+		Self {
+			title: Some(title.title.clone()),
+			year: title.year,
+			imdb_id: Some(title.imdb_id.clone()),
+			streams: BTreeMap::new(),
+		}
+	}
+}
+
+impl MetadataTags {
+	pub fn language(mut self, index: usize, language: &str) -> Self {
+		self.streams.entry(index).or_default().language = Some(language.to_owned());
+		self
+	}
+
+	pub fn default_flag(mut self, index: usize, default: bool) -> Self {
+		self.streams.entry(index).or_default().default = Some(default);
+		self
+	}
+
+	pub fn forced_flag(mut self, index: usize, forced: bool) -> Self {
+		self.streams.entry(index).or_default().forced = Some(forced);
+		self
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.title.is_none() && self.year.is_none() && self.imdb_id.is_none() && self.streams.is_empty()
+	}
+
+	fn set_container(&self, metadata: &mut Dictionary, format: &str) {
+		if let Some(title) = &self.title {
+			metadata.set("title", title);
+		}
+		if let Some(year) = self.year {
+			metadata.set("date", &year.to_string());
+		}
+		if let Some(imdb_id) = &self.imdb_id {
+			metadata.set(if format == "matroska" { "IMDB" } else { "imdb" }, imdb_id);
+		}
+	}
+}
+
+/// Muxer name by the file extension, only Matroska and MP4 are written.
+fn muxer(path: &Path) -> Result<&'static str> {
+	let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+	match ext.as_str() {
+		"mkv" | "mka" => Ok("matroska"),
+		"mp4" | "m4v" => Ok("mp4"),
+		_ => Err(MediaOrderError::UnsupportedContainerError(path.to_path_buf())),
+	}
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+	let file_name = path
+		.file_name()
+		.map(|name| name.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	path.with_file_name(format!(".{file_name}.mediaorder.tmp"))
+}
+
+fn disposition(source: Disposition, tags: &StreamTags) -> Disposition {
+	let mut disposition = source;
+	if let Some(default) = tags.default {
+		disposition.set(Disposition::DEFAULT, default);
+	}
+	if let Some(forced) = tags.forced {
+		disposition.set(Disposition::FORCED, forced);
+	}
+	disposition
+}
+
+/// Copies audio, video and subtitle streams without re-encoding, with the tags applied. Returns mapping of the
+/// source stream indexes to the output ones.
+fn remux(source: &Path, target: &Path, format: &str, tags: &MetadataTags) -> Result<Vec<Option<usize>>> {
+	let mut input = format::input(&source)?;
+	let mut output = format::output_as(&target, format)?;
+	let mut mapping = vec![None; input.nb_streams() as usize];
+	let mut time_bases = vec![Rational(0, 1); input.nb_streams() as usize];
+
+	for (index, stream) in input.streams().enumerate() {
+		let medium = stream.parameters().medium();
+		if ![media::Type::Audio, media::Type::Video, media::Type::Subtitle].contains(&medium) {
+			continue;
+		}
+		let stream_tags = tags.streams.get(&index).cloned().unwrap_or_default();
+		let mut metadata = stream.metadata().to_owned();
+		if let Some(language) = &stream_tags.language {
+			metadata.set("language", language);
+		}
+
+		mapping[index] = Some(output.nb_streams() as usize);
+		time_bases[index] = stream.time_base();
+		let mut output_stream = output.add_stream(encoder::find(codec::Id::None))?;
+		output_stream.set_parameters(stream.parameters());
+		output_stream.set_metadata(metadata);
+		// there is no high level API for the codec tag (reset to avoid muxer incompatibility) and disposition
+		unsafe {
+			(*output_stream.parameters().as_mut_ptr()).codec_tag = 0;
+			(*output_stream.as_mut_ptr()).disposition =
+				disposition(stream.disposition(), &stream_tags).bits();
+		}
+	}
+
+	let mut metadata = input.metadata().to_owned();
+	tags.set_container(&mut metadata, format);
+	output.set_metadata(metadata);
+	let mut options = Dictionary::new();
+	if format == "mp4" {
+		// keeps custom tags like `imdb` in MP4
+		options.set("movflags", "use_metadata_tags");
+	}
+	output.write_header_with(options)?;
+
+	for (stream, mut packet) in input.packets() {
+		let index = stream.index();
+		let Some(output_index) = mapping[index] else {
+			continue;
+		};
+		let output_time_base = output
+			.stream(output_index)
+			.map(|stream| stream.time_base())
+			.unwrap_or(time_bases[index]);
+		packet.rescale_ts(time_bases[index], output_time_base);
+		packet.set_position(-1);
+		packet.set_stream(output_index);
+		packet.write_interleaved(&mut output)?;
+	}
+	output.write_trailer()?;
+
+	Ok(mapping)
+}
+
+/// Checks that the remuxed file has all the copied streams with the same codecs and the tags, and its duration
+/// matches the source one.
+fn verify(source: &Path, target: &Path, mapping: &[Option<usize>], tags: &MetadataTags) -> Result<()> {
+	let source_context = format::input(&source)?;
+	let target_context = format::input(&target)?;
+	let fail = |reason: String| Err(MediaOrderError::MetadataVerifyError(source.to_path_buf(), reason));
+
+	let copied = mapping.iter().flatten().count();
+	if target_context.nb_streams() as usize != copied {
+		return fail(format!("{} streams instead of {copied}", target_context.nb_streams()));
+	}
+	for (index, stream) in source_context.streams().enumerate() {
+		let Some(target_stream) = mapping[index].and_then(|index| target_context.stream(index)) else {
+			continue;
+		};
+		if stream.parameters().id() != target_stream.parameters().id() {
+			return fail(format!("stream {index} codec changed"));
+		}
+		let language = tags.streams.get(&index).and_then(|tags| tags.language.as_deref());
+		if language.is_some() && target_stream.metadata().get("language") != language {
+			return fail(format!("stream {index} language is not written"));
+		}
+	}
+
+	let duration =
+		|context: &format::context::Input| context.duration() as f64 / f64::from(ffmpeg::ffi::AV_TIME_BASE);
+	if (duration(&source_context) - duration(&target_context)).abs() > DURATION_TOLERANCE {
+		return fail(format!(
+			"duration {:.1}s instead of {:.1}s",
+			duration(&target_context),
+			duration(&source_context)
+		));
+	}
+	if tags.title.is_some() && target_context.metadata().get("title") != tags.title.as_deref() {
+		return fail("title is not written".to_owned());
+	}
+	Ok(())
+}
+
+/// Writes the tags into the MKV or MP4 file: remuxes it without re-encoding next to the source, verifies the
+/// result and atomically replaces the source with it. The source is left untouched on any error. Note the
+/// replaced file is a new inode, so hardlinks of the source (e.g. seeded by the torrent client) keep the old
+/// content.
+pub fn write_tags(path: &Path, tags: &MetadataTags) -> Result<()> {
+	let format = muxer(path)?;
+	if tags.is_empty() {
+		return Ok(());
+	}
+	let temp = temp_path(path);
+
+	let result = remux(path, &temp, format, tags)
+		.and_then(|mapping| verify(path, &temp, &mapping, tags))
+		.and_then(|_| {
+			fs::set_permissions(&temp, fs::metadata(path)?.permissions())?;
+			fs::rename(&temp, path)?;
+			Ok(())
+		});
+
+	match result {
+		Ok(()) => {
+			info!("Tags written to {:?}", path);
+			Ok(())
+		}
+		Err(err) => {
+			debug!("Can't write tags to {:?}: {err}", path);
+			let _ = fs::remove_file(&temp);
+			Err(err)
+		}
+	}
+}
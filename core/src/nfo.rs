@@ -0,0 +1,333 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use ffmpeg_the_third::media::Type as MediaType;
+use file_format::FileFormat;
+use log::{debug, info};
+use quick_xml::{
+	events::{BytesDecl, BytesText, Event},
+	Reader,
+	Writer,
+};
+use regex::Regex;
+
+use crate::{
+	disc::Disc,
+	errors::{MediaOrderError, Result},
+	media::{release_folder, MediaItem, TitleInfo},
+	video::{StreamInfo, Video},
+};
+
+lazy_static! {
+	static ref IMDB_ID: Regex = Regex::new(r"\btt\d{7,8}\b").unwrap();
+	static ref SEASON_FOLDER: Regex = Regex::new(r"(?i)^(season|сезон)?\s*s?\d{1,2}$").unwrap();
+}
+
+/// Kind of the Kodi NFO file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NfoKind {
+	Movie,
+	TvShow,
+	Episode { season: u16, episode: u16 },
+}
+
+impl NfoKind {
+	fn root(&self) -> &'static str {
+		match self {
+			NfoKind::Movie => "movie",
+			NfoKind::TvShow => "tvshow",
+			NfoKind::Episode { .. } => "episodedetails",
+		}
+	}
+}
+
+/// Identification hint from the existing NFO file: Kodi XML or any text (scene NFO) with the IMDb link.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NfoHint {
+	pub imdb_id: Option<String>,
+	pub title: Option<String>,
+	pub original_title: Option<String>,
+	pub year: Option<u16>,
+	/// Show of the episode NFO.
+	pub show_title: Option<String>,
+}
+
+impl NfoHint {
+	pub fn parse(content: &str) -> NfoHint {
+		let mut hint = NfoHint::default();
+		let mut reader = Reader::from_str(content);
+		reader.trim_text(true);
+		let mut path: Vec<String> = vec![];
+		let mut imdb_uniqueid = false;
+
+		loop {
+			match reader.read_event() {
+				Ok(Event::Start(start)) => {
+					imdb_uniqueid = start.name().as_ref() == b"uniqueid"
+						&& start
+							.try_get_attribute("type")
+							.ok()
+							.flatten()
+							.and_then(|kind| kind.unescape_value().ok())
+							.is_some_and(|kind| kind == "imdb");
+					path.push(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+				}
+				Ok(Event::End(_)) => {
+					path.pop();
+				}
+				Ok(Event::Text(text)) if path.len() == 2 => {
+					let Ok(text) = text.unescape() else {
+						continue;
+					};
+					let text = text.trim().to_owned();
+					match path[1].as_str() {
+						"title" => hint.title = Some(text),
+						"originaltitle" => hint.original_title = Some(text),
+						"showtitle" => hint.show_title = Some(text),
+						"year" | "premiered" => {
+							hint.year = hint.year.or(text.get(..4).and_then(|year| year.parse().ok()))
+						}
+						"uniqueid" if imdb_uniqueid => hint.imdb_id = Some(text),
+						"imdbid" | "id" if IMDB_ID.is_match(&text) && hint.imdb_id.is_none() => {
+							hint.imdb_id = Some(text)
+						}
+						_ => {}
+					}
+				}
+				Ok(Event::Eof) | Err(_) => break,
+				_ => {}
+			}
+		}
+
+		if hint.imdb_id.is_none() {
+			hint.imdb_id = IMDB_ID.find(content).map(|id| id.as_str().to_owned());
+		}
+		hint
+	}
+
+	pub fn read(path: &Path) -> Option<NfoHint> {
+		let content = fs::read(path).ok()?;
+		Some(NfoHint::parse(&String::from_utf8_lossy(&content)))
+	}
+
+	/// Looks for `<video file name>.nfo`, then `movie.nfo` in the disc folder or in the own folder of the video
+	/// (named after it), as the shared folders have one `movie.nfo` for many videos.
+	pub fn find(video: &Video) -> Option<NfoHint> {
+		let folder = match Disc::root_of(&video.path) {
+			Some(disc) => Some(disc),
+			None => video
+				.path
+				.parent()
+				.filter(|folder| is_named_after(&video.name_original, folder)),
+		};
+		[
+			Some(video.path.with_extension("nfo")),
+			folder.map(|folder| folder.join("movie.nfo")),
+		]
+		.iter()
+		.flatten()
+		.filter(|path| path.is_file())
+		.find_map(|path| NfoHint::read(path))
+	}
+
+	/// Fills the video identification from the hint, the year is only taken when it is not in the name.
+	pub fn apply(self, video: &mut Video) {
+		video.imdb_id = self.imdb_id.or(video.imdb_id.take());
+		video.year = video.year.or(self.year);
+		if video.name_english.is_none() && self.title.as_deref() != Some(video.name_original.as_str()) {
+			video.name_english = self.title;
+		}
+	}
+}
+
+/// Folder holds this title only, when the folder is named after it, like `Brat (1997)` or
+/// `Brat.1997.1080p.BluRay`.
+fn is_named_after(title: &str, folder: &Path) -> bool {
+	let Some(name) = folder.file_name().and_then(|name| name.to_str()) else {
+		return false;
+	};
+	let mut parsed = Video::new(folder.to_path_buf(), FileFormat::ArbitraryBinaryData);
+	parsed.parse_name(name).is_ok() && parsed.name_original.eq_ignore_ascii_case(title)
+}
+
+fn is_item_folder(item: &MediaItem, folder: &Path) -> bool {
+	is_named_after(item.name(), folder)
+}
+
+/// Path of the NFO file of the item: `movie.nfo` in the own folder of the movie (or disc), `<file name>.nfo`
+/// for the episodes and the movies sharing the folder, `tvshow.nfo` in the show folder (above the season one).
+pub fn nfo_path(item: &MediaItem, kind: NfoKind) -> Option<PathBuf> {
+	let video = item.main();
+	if let Some(disc) = Disc::root_of(&video.path) {
+		return Some(disc.join("movie.nfo"));
+	}
+	let folder = release_folder(video)?;
+
+	match kind {
+		NfoKind::Movie if is_item_folder(item, folder) => Some(folder.join("movie.nfo")),
+		NfoKind::Movie | NfoKind::Episode { .. } => Some(video.path.with_extension("nfo")),
+		NfoKind::TvShow => {
+			let is_season = folder
+				.file_name()
+				.and_then(|name| name.to_str())
+				.is_some_and(|name| SEASON_FOLDER.is_match(name));
+			match is_season {
+				true => folder.parent().map(|show| show.join("tvshow.nfo")),
+				false => Some(folder.join("tvshow.nfo")),
+			}
+		}
+	}
+}
+
+fn write_element<W: std::io::Write>(writer: &mut Writer<W>, name: &str, value: &str) -> Result<()> {
+	writer.create_element(name).write_text_content(BytesText::new(value))?;
+	Ok(())
+}
+
+fn write_stream_details<W: std::io::Write>(writer: &mut Writer<W>, streams: &[StreamInfo]) -> Result<()> {
+	writer.create_element("fileinfo").write_inner_content(|writer| {
+		writer.create_element("streamdetails").write_inner_content(|writer| {
+			for stream in streams {
+				match stream.medium {
+					MediaType::Video => {
+						writer.create_element("video").write_inner_content(|writer| {
+							write_element(writer, "codec", &stream.codec)?;
+							if stream.height > 0 {
+								let aspect = f64::from(stream.width) / f64::from(stream.height);
+								write_element(writer, "aspect", &format!("{aspect:.2}"))?;
+							}
+							write_element(writer, "width", &stream.width.to_string())?;
+							write_element(writer, "height", &stream.height.to_string())?;
+							if let Some(duration) = stream.duration {
+								write_element(writer, "durationinseconds", &format!("{duration:.0}"))?;
+							}
+							Ok::<_, MediaOrderError>(())
+						})?;
+					}
+					MediaType::Audio => {
+						writer.create_element("audio").write_inner_content(|writer| {
+							write_element(writer, "codec", &stream.codec)?;
+							if let Some(language) = &stream.language {
+								write_element(writer, "language", language)?;
+							}
+							write_element(writer, "channels", &stream.channels.to_string())?;
+							Ok::<_, MediaOrderError>(())
+						})?;
+					}
+					MediaType::Subtitle => {
+						writer.create_element("subtitle").write_inner_content(|writer| {
+							if let Some(language) = &stream.language {
+								write_element(writer, "language", language)?;
+							}
+							Ok::<_, MediaOrderError>(())
+						})?;
+					}
+					_ => {}
+				}
+			}
+			Ok::<_, MediaOrderError>(())
+		})?;
+		Ok::<_, MediaOrderError>(())
+	})?;
+	Ok(())
+}
+
+/// Kodi NFO XML of the title, stream details are taken from the video probe (not written for the tv show).
+/// The episode is written with the title of its show and its numbers only, as the title is the show one.
+pub fn render(kind: NfoKind, title: &TitleInfo, video: Option<&Video>) -> Result<String> {
+	let mut writer = Writer::new_with_indent(Vec::new(), b'\t', 1);
+	writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), Some("yes"))))?;
+
+	if let NfoKind::Episode { season, episode } = kind {
+		writer.create_element(kind.root()).write_inner_content(|writer| {
+			write_element(writer, "showtitle", &title.title)?;
+			write_element(writer, "season", &season.to_string())?;
+			write_element(writer, "episode", &episode.to_string())?;
+			if let Some(video) = video {
+				if let Some(duration) = video.duration() {
+					write_element(writer, "runtime", &((duration / 60.0).round() as u32).to_string())?;
+				}
+				write_stream_details(writer, &video.streams())?;
+			}
+			Ok::<_, MediaOrderError>(())
+		})?;
+		return Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned());
+	}
+
+	writer.create_element(kind.root()).write_inner_content(|writer| {
+		write_element(writer, "title", &title.title)?;
+		if let Some(original_title) = &title.original_title {
+			write_element(writer, "originaltitle", original_title)?;
+		}
+		if let Some(year) = title.year {
+			write_element(writer, "year", &year.to_string())?;
+		}
+		let runtime = title
+			.runtime
+			.or(video.and_then(Video::duration).map(|duration| (duration / 60.0).round() as u32));
+		if let (Some(runtime), false) = (runtime, kind == NfoKind::TvShow) {
+			write_element(writer, "runtime", &runtime.to_string())?;
+		}
+		for genre in &title.genres {
+			write_element(writer, "genre", genre)?;
+		}
+		writer
+			.create_element("uniqueid")
+			.with_attributes([("type", "imdb"), ("default", "true")])
+			.write_text_content(BytesText::new(&title.imdb_id))?;
+		if let (Some(video), false) = (video, kind == NfoKind::TvShow) {
+			write_stream_details(writer, &video.streams())?;
+		}
+		Ok::<_, MediaOrderError>(())
+	})?;
+
+	Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+}
+
+/// Writes the NFO file. Existing file is only replaced when it is of the same IMDb title, or of the same
+/// show for the episodes (written by us before), otherwise it is kept as it is and `false` is returned.
+pub fn write(path: &Path, kind: NfoKind, title: &TitleInfo, video: Option<&Video>) -> Result<bool> {
+	if let Some(existing) = NfoHint::read(path) {
+		let same = match kind {
+			NfoKind::Episode { .. } => {
+				existing.imdb_id.is_none() && existing.show_title.as_deref() == Some(title.title.as_str())
+			}
+			_ => existing.imdb_id.as_deref() == Some(title.imdb_id.as_str()),
+		};
+		if !same {
+			info!("Keeping {:?} of {:?} instead of {}", path, existing.imdb_id, title.imdb_id);
+			return Ok(false);
+		}
+	}
+
+	let content = render(kind, title, video)?;
+	let temp = path.with_extension("nfo.tmp");
+	fs::write(&temp, content)?;
+	fs::rename(&temp, path)?;
+	debug!("{:?} written", path);
+	Ok(true)
+}
+
+/// Writes the NFO file of the identified item (an episode, with the title of its show, or a movie), returns
+/// the path when it is written.
+pub fn write_item(item: &MediaItem, title: &TitleInfo) -> Result<Option<PathBuf>> {
+	let video = item.main();
+	let kind = match (video.season, video.episode) {
+		(Some(season), Some(episode)) => NfoKind::Episode { season, episode },
+		_ => NfoKind::Movie,
+	};
+	let Some(path) = nfo_path(item, kind) else {
+		return Ok(None);
+	};
+	Ok(write(&path, kind, title, Some(video))?.then_some(path))
+}
+
+/// Writes `tvshow.nfo` of the show of the episode item, returns the path when it is written.
+pub fn write_show(item: &MediaItem, title: &TitleInfo) -> Result<Option<PathBuf>> {
+	let Some(path) = nfo_path(item, NfoKind::TvShow) else {
+		return Ok(None);
+	};
+	Ok(write(&path, NfoKind::TvShow, title, None)?.then_some(path))
+}
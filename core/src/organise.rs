@@ -0,0 +1,253 @@
+use std::{
+	fs,
+	io,
+	os::unix::fs::MetadataExt,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+
+use log::{debug, info};
+use regex::{Captures, Regex};
+
+use crate::{
+	disc::Disc,
+	errors::{MediaOrderError, Result},
+	media::MediaItem,
+	video::Video,
+};
+
+pub const DEFAULT_TEMPLATE: &str = "{name} ({year})/{name} ({year})";
+
+lazy_static! {
+	static ref PLACEHOLDER: Regex = Regex::new(r"\{(\w+)\}").unwrap();
+	static ref EMPTY_BRACKETS: Regex = Regex::new(r"\(\s*\)|\[\s*\]").unwrap();
+	static ref SPACES: Regex = Regex::new(r"\s{2,}").unwrap();
+}
+
+lazy_static! {
+	static ref UNSAFE_CHARS: Vec<char> = vec!['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+}
+
+/// Path template of the organised library item relative to the library root, without the extension.
+/// Placeholders: `{name}`, `{year}`, `{part}` (`cd1`), `{vres}`, `{vqual}` and `{category}`. Empty brackets
+/// and separators left by the missing values are dropped. Parts get ` - cdN` suffix when the template has no
+/// `{part}`.
+#[derive(Clone, Debug)]
+pub struct RenameTemplate(String);
+
+impl Default for RenameTemplate {
+	fn default() -> Self {
+		Self(DEFAULT_TEMPLATE.to_owned())
+	}
+}
+
+impl FromStr for RenameTemplate {
+	type Err = MediaOrderError;
+
+	fn from_str(template: &str) -> Result<Self> {
+		if let Some(unknown) = PLACEHOLDER
+			.captures_iter(template)
+			.find(|captures| !["name", "year", "part", "vres", "vqual", "category"].contains(&&captures[1]))
+		{
+			return Err(MediaOrderError::ParseError(
+				"template placeholder".to_owned(),
+				unknown[0].to_owned(),
+			));
+		}
+		if template.trim_matches('/').is_empty() || template.starts_with('/') {
+			return Err(MediaOrderError::ParseError("template".to_owned(), template.to_owned()));
+		}
+		Ok(Self(template.to_owned()))
+	}
+}
+
+fn sanitize(value: &str) -> String {
+	value.chars().map(|c| if UNSAFE_CHARS.contains(&c) { ' ' } else { c }).collect()
+}
+
+/// Drops empty brackets and dangling separators, the component made of them only is dropped at all.
+fn clean_component(component: &str) -> String {
+	let component = EMPTY_BRACKETS.replace_all(component, "");
+	let component = SPACES.replace_all(&component, " ");
+	component
+		.trim_matches(|c: char| c.is_whitespace() || c == '-' || c == '.' || c == '_')
+		.to_owned()
+}
+
+impl RenameTemplate {
+	pub fn render(&self, video: &Video, category: Option<&str>) -> PathBuf {
+		let rendered = PLACEHOLDER.replace_all(&self.0, |captures: &Captures| {
+			let value = match &captures[1] {
+				"name" => Some(video.name_original.clone()),
+				"year" => video.year.map(|year| year.to_string()),
+				"part" => video.part.map(|part| format!("cd{part}")),
+				"vres" => video.vres.clone(),
+				"vqual" => video.vqual.clone(),
+				"category" => category.map(String::from),
+				_ => None,
+			};
+			sanitize(&value.unwrap_or_default())
+		});
+
+		let mut path: PathBuf = rendered.split('/').map(clean_component).filter(|c| !c.is_empty()).collect();
+		if let (Some(part), false) = (video.part, self.0.contains("{part}")) {
+			let name = path
+				.file_name()
+				.map(|name| name.to_string_lossy().into_owned())
+				.unwrap_or_default();
+			path.set_file_name(format!("{name} - cd{part}"));
+		}
+		path
+	}
+}
+
+/// How files are placed into the organised library.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlaceMode {
+	/// Hardlink (or copy, when the library is on another file system), originals stay for seeding.
+	#[default]
+	Hardlink,
+	/// Symbolic link to the original, used for the views on another file system.
+	Symlink,
+	Copy,
+	Move,
+}
+
+impl FromStr for PlaceMode {
+	type Err = MediaOrderError;
+
+	fn from_str(str: &str) -> Result<Self> {
+		match str.to_lowercase().as_str() {
+			"hardlink" | "link" => Ok(PlaceMode::Hardlink),
+			"symlink" => Ok(PlaceMode::Symlink),
+			"copy" => Ok(PlaceMode::Copy),
+			"move" => Ok(PlaceMode::Move),
+			_ => Err(MediaOrderError::ParseError("place mode".to_owned(), str.to_owned())),
+		}
+	}
+}
+
+/// Source file (or disc folder) and its path in the organised library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+	pub source: PathBuf,
+	pub target: PathBuf,
+}
+
+fn with_extension(path: PathBuf, source: &Path) -> PathBuf {
+	match source.extension() {
+		Some(ext) => {
+			let mut path = path.into_os_string();
+			path.push(".");
+			path.push(ext.to_ascii_lowercase());
+			PathBuf::from(path)
+		}
+		None => path,
+	}
+}
+
+/// Places of the item files in the library under `root`: main videos (or parts) by the template, extras to
+/// the role folders (`Trailers`, `Samples`, ...) next to the main video keeping their file names. Disc
+/// folders are placed as a whole.
+pub fn plan(
+	item: &MediaItem,
+	root: &Path,
+	template: &RenameTemplate,
+	category: Option<&str>,
+) -> Vec<Placement> {
+	let mut placements = vec![];
+
+	for video in &item.videos {
+		let target = root.join(template.render(video, category));
+		let placement = match Disc::root_of(&video.path) {
+			Some(disc) => Placement {
+				source: disc.to_path_buf(),
+				target,
+			},
+			None => Placement {
+				source: video.path.clone(),
+				target: with_extension(target, &video.path),
+			},
+		};
+		placements.push(placement);
+	}
+
+	let folder = placements
+		.first()
+		.and_then(|placement| placement.target.parent())
+		.map(Path::to_path_buf)
+		.unwrap_or_else(|| root.to_path_buf());
+	for extra in &item.extras {
+		if let Some(file_name) = extra.path.file_name() {
+			placements.push(Placement {
+				source: extra.path.clone(),
+				target: folder.join(extra.role.folder_name()).join(file_name),
+			});
+		}
+	}
+
+	placements
+}
+
+pub(crate) fn is_same_file(a: &Path, b: &Path) -> bool {
+	match (fs::metadata(a), fs::metadata(b)) {
+		(Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+		_ => false,
+	}
+}
+
+fn copy_tree(source: &Path, target: &Path, link: bool) -> io::Result<()> {
+	if source.is_dir() {
+		fs::create_dir_all(target)?;
+		for entry in fs::read_dir(source)? {
+			let entry = entry?;
+			copy_tree(&entry.path(), &target.join(entry.file_name()), link)?;
+		}
+		return Ok(());
+	}
+
+	if link {
+		match fs::hard_link(source, target) {
+			Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+				debug!("Can't hardlink {:?} to another file system, copying", source);
+			}
+			result => return result,
+		}
+	}
+	fs::copy(source, target).map(|_| ())
+}
+
+/// Places the source into the library. Existing target is an error unless it is the source itself (already
+/// hardlinked).
+pub fn place(placement: &Placement, mode: PlaceMode) -> Result<()> {
+	let Placement { source, target } = placement;
+	if target.exists() {
+		if !source.is_dir() && is_same_file(source, target) {
+			debug!("{:?} is already placed", target);
+			return Ok(());
+		}
+		return Err(MediaOrderError::TargetExistsError(target.clone()));
+	}
+	if let Some(parent) = target.parent() {
+		fs::create_dir_all(parent)?;
+	}
+
+	match mode {
+		PlaceMode::Hardlink => copy_tree(source, target, true)?,
+		PlaceMode::Symlink => std::os::unix::fs::symlink(source, target)?,
+		PlaceMode::Copy => copy_tree(source, target, false)?,
+		PlaceMode::Move => match fs::rename(source, target) {
+			Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+				copy_tree(source, target, false)?;
+				if source.is_dir() {
+					fs::remove_dir_all(source)?;
+				} else {
+					fs::remove_file(source)?;
+				}
+			}
+			result => result?,
+		},
+	}
+	info!("{:?} {:?} -> {:?}", mode, source, target);
+	Ok(())
+}
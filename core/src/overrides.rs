@@ -0,0 +1,208 @@
+use std::{
+	collections::HashMap,
+	fmt::{self, Display, Formatter},
+	io::SeekFrom,
+	path::{Path, PathBuf},
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::{errors::Result, library::entities::overrides, video::Video};
+
+pub const OVERRIDE_FILE: &str = ".mediaorder.toml";
+/// Bytes hashed from the head and the tail of the file, enough to tell apart the releases of the same size.
+const FINGERPRINT_CHUNK: u64 = 64 << 10;
+
+/// Manual identification of the file or of everything in the folder, the unset fields are left to the
+/// automatic matching.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Override {
+	pub imdb_id: Option<String>,
+	/// Title used instead of the one parsed from the name.
+	pub title: Option<String>,
+	pub year: Option<u16>,
+	/// Added to the parsed season, for the shows numbered differently from IMDb.
+	pub season_offset: Option<i32>,
+	/// Keeps the file out of the library, `false` takes the file back from the ignored folder.
+	pub ignore: Option<bool>,
+}
+
+impl Override {
+	pub fn is_empty(&self) -> bool {
+		*self == Override::default()
+	}
+
+	pub fn is_ignored(&self) -> bool {
+		self.ignore.unwrap_or_default()
+	}
+
+	/// Fields of the override, the unset ones taken from the `fallback`.
+	pub fn or(self, fallback: Override) -> Override {
+		Override {
+			imdb_id: self.imdb_id.or(fallback.imdb_id),
+			title: self.title.or(fallback.title),
+			year: self.year.or(fallback.year),
+			season_offset: self.season_offset.or(fallback.season_offset),
+			ignore: self.ignore.or(fallback.ignore),
+		}
+	}
+
+	/// Replaces the parsed details of the video with the forced ones.
+	pub fn apply(&self, video: &mut Video) {
+		if let Some(imdb_id) = &self.imdb_id {
+			video.imdb_id = Some(imdb_id.clone());
+		}
+		if let Some(title) = &self.title {
+			video.name_original = title.clone();
+			video.name_english = None;
+		}
+		if let Some(year) = self.year {
+			video.year = Some(year);
+		}
+		if let (Some(offset), Some(season)) = (self.season_offset, video.season) {
+			if let Ok(season) = u16::try_from(i32::from(season) + offset) {
+				video.season = Some(season);
+			}
+		}
+	}
+}
+
+impl Display for Override {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		let fields = [
+			self.imdb_id.as_ref().map(|imdb_id| format!("imdb {imdb_id}")),
+			self.title.as_ref().map(|title| format!("title {title:?}")),
+			self.year.map(|year| format!("year {year}")),
+			self.season_offset.map(|offset| format!("season offset {offset:+}")),
+			self.ignore
+				.map(|ignore| if ignore { "ignored" } else { "not ignored" }.to_owned()),
+		];
+		let fields: Vec<String> = fields.into_iter().flatten().collect();
+		write!(f, "{}", fields.join(", "))
+	}
+}
+
+impl From<&overrides::Model> for Override {
+	fn from(model: &overrides::Model) -> Self {
+		Override {
+			imdb_id: model.imdb_id.clone(),
+			title: model.title.clone(),
+			year: model.year.and_then(|year| u16::try_from(year).ok()),
+			season_offset: model.season_offset,
+			ignore: model.ignore,
+		}
+	}
+}
+
+/// Overrides of the `.mediaorder.toml` file: the top level keys are applied to the folder and everything under
+/// it, the `[files."name"]` tables to the files (or the subfolders) of the folder.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct OverrideFile {
+	#[serde(flatten)]
+	pub folder: Override,
+	#[serde(default)]
+	pub files: HashMap<String, Override>,
+}
+
+impl OverrideFile {
+	pub fn parse(content: &str) -> Result<Self> {
+		Ok(toml::from_str(content)?)
+	}
+
+	/// Override file of the folder, the malformed one is logged and skipped.
+	pub async fn load(folder: &Path) -> Option<Self> {
+		let file = folder.join(OVERRIDE_FILE);
+		let content = tokio::fs::read_to_string(&file).await.ok()?;
+		Self::parse(&content).map_err(|err| warn!("Skipping {:?}: {err}", file)).ok()
+	}
+
+	pub fn file(&self, name: &str) -> Option<&Override> {
+		self.files.get(name)
+	}
+}
+
+/// Overrides loaded once per scan: the overrides of the database and the override files by folder.
+#[derive(Debug, Default)]
+pub struct OverrideCache {
+	folders: HashMap<PathBuf, Override>,
+	/// Own overrides of the files by the path, which follow the files by the fingerprint.
+	files: HashMap<String, overrides::Model>,
+	override_files: HashMap<PathBuf, Option<OverrideFile>>,
+}
+
+impl OverrideCache {
+	pub fn new(found: Vec<overrides::Model>) -> Self {
+		let (folders, files): (Vec<_>, Vec<_>) = found.into_iter().partition(|found| found.folder);
+		Self {
+			folders: folders.iter().map(|found| (PathBuf::from(&found.path), found.into())).collect(),
+			files: files.into_iter().map(|found| (found.path.clone(), found)).collect(),
+			override_files: HashMap::new(),
+		}
+	}
+
+	/// Database override of the folder.
+	pub fn folder(&self, folder: &Path) -> Option<&Override> {
+		self.folders.get(folder)
+	}
+
+	/// Database override of the file.
+	pub fn file(&self, path: &str) -> Option<&overrides::Model> {
+		self.files.get(path)
+	}
+
+	/// Database overrides of the files with the fingerprint, ordered by the path.
+	pub fn fingerprinted(&self, fingerprint: &str) -> Vec<&overrides::Model> {
+		let mut found: Vec<_> = self
+			.files
+			.values()
+			.filter(|found| found.fingerprint.as_deref() == Some(fingerprint))
+			.collect();
+		found.sort_by(|a, b| a.path.cmp(&b.path));
+		found
+	}
+
+	/// Replaces the file override updated in the database, `path` is its previous path.
+	pub fn update_file(&mut self, path: &str, updated: overrides::Model) {
+		self.files.remove(path);
+		self.files.insert(updated.path.clone(), updated);
+	}
+
+	/// Whether the new and modified videos need the fingerprints to find their overrides.
+	pub fn needs_fingerprints(&self) -> bool {
+		!self.files.is_empty()
+	}
+
+	/// Override file of the folder.
+	pub async fn override_file(&mut self, folder: &Path) -> Option<&OverrideFile> {
+		if !self.override_files.contains_key(folder) {
+			let file = OverrideFile::load(folder).await;
+			self.override_files.insert(folder.to_path_buf(), file);
+		}
+		self.override_files.get(folder)?.as_ref()
+	}
+}
+
+/// Hash of the size, the head and the tail of the file, which follows the file across renames without reading
+/// it whole.
+pub async fn fingerprint(path: &Path) -> Option<String> {
+	let mut file = tokio::fs::File::open(path).await.ok()?;
+	let size = file.metadata().await.ok()?.len();
+	let mut hasher = Sha256::new();
+	hasher.update(size.to_le_bytes());
+
+	let mut chunk = vec![0; FINGERPRINT_CHUNK.min(size) as usize];
+	file.read_exact(&mut chunk).await.ok()?;
+	hasher.update(&chunk);
+	if size > FINGERPRINT_CHUNK {
+		let tail = FINGERPRINT_CHUNK.min(size - FINGERPRINT_CHUNK);
+		file.seek(SeekFrom::End(-(tail as i64))).await.ok()?;
+		chunk.resize(tail as usize, 0);
+		file.read_exact(&mut chunk).await.ok()?;
+		hasher.update(&chunk);
+	}
+	Some(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,403 @@
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt::Write,
+	path::PathBuf,
+};
+
+use quick_xml::escape::escape;
+use serde::Serialize;
+
+use crate::{
+	integrity::IntegrityStatus,
+	library::{entities::files, query::human_size, KIND_DISC, KIND_ERROR, KIND_VIDEO},
+	media::group_parts,
+	video::{Video, VideoRole, WARNING_TRUNCATED},
+};
+
+const UNKNOWN: &str = "unknown";
+
+/// Files and their total size sharing the key (resolution, codec, year, ...).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Bucket {
+	pub key: String,
+	pub files: u64,
+	pub size: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Issue {
+	pub path: String,
+	pub detail: String,
+}
+
+/// Copies of the same title, all but the largest one are wasted space. Parts of the release make one copy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Duplicate {
+	pub title: String,
+	pub paths: Vec<String>,
+	pub wasted: u64,
+}
+
+/// Statistics and health of the library store.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+	pub files: u64,
+	pub size: u64,
+	pub videos: u64,
+	pub by_resolution: Vec<Bucket>,
+	pub by_codec: Vec<Bucket>,
+	pub by_quality: Vec<Bucket>,
+	pub by_year: Vec<Bucket>,
+	pub by_decade: Vec<Bucket>,
+	/// Main features without the IMDb id.
+	pub unidentified: Vec<String>,
+	/// Name parse warnings.
+	pub warnings: Vec<Issue>,
+	/// Videos ffmpeg failed to probe.
+	pub probe_failures: Vec<Issue>,
+	/// Entries failed to be scanned at all.
+	pub scan_errors: Vec<Issue>,
+	/// Videos failed the integrity check.
+	pub broken: Vec<Issue>,
+	pub empty: Vec<String>,
+	pub truncated: Vec<String>,
+	pub duplicates: Vec<Duplicate>,
+	pub wasted: u64,
+}
+
+fn add(buckets: &mut BTreeMap<String, Bucket>, key: String, size: u64) {
+	let bucket = buckets.entry(key.clone()).or_insert_with(|| Bucket {
+		key,
+		..Default::default()
+	});
+	bucket.files += 1;
+	bucket.size += size;
+}
+
+fn resolution(record: &files::Model) -> String {
+	record
+		.vres
+		.clone()
+		.or(record.height.map(|height| format!("{height}p")))
+		.unwrap_or_else(|| UNKNOWN.to_owned())
+}
+
+fn name_key(record: &files::Model) -> Option<String> {
+	let name = record.name.as_deref().filter(|name| !name.is_empty())?.to_lowercase();
+	Some(match record.year {
+		Some(year) => format!("{name} ({year})"),
+		None => name,
+	})
+}
+
+/// Total size and paths of the copy.
+type ItemCopy = (u64, Vec<String>);
+
+/// Copies are the items (all parts of the release) grouped by the IMDb id and the year, unidentified ones join
+/// the identified copy of the same name and year.
+fn duplicates(mains: &[&files::Model]) -> Vec<Duplicate> {
+	let imdb_ids: HashMap<String, &str> = mains
+		.iter()
+		.filter_map(|record| Some((name_key(record)?, record.imdb_id.as_deref()?)))
+		.collect();
+	let records: HashMap<PathBuf, &files::Model> =
+		mains.iter().map(|record| (PathBuf::from(&record.path), *record)).collect();
+	let mut copies: HashMap<(String, Option<i32>), Vec<ItemCopy>> = HashMap::new();
+	for item in group_parts(mains.iter().map(|record| Video::from_record(record))) {
+		let parts: Vec<&files::Model> = item.paths().filter_map(|path| records.get(path).copied()).collect();
+		let main = parts[0];
+		let imdb_id = parts.iter().find_map(|record| record.imdb_id.clone());
+		let title = match (imdb_id, name_key(main)) {
+			(Some(imdb_id), _) => imdb_id,
+			(None, Some(name)) => imdb_ids.get(&name).map(|imdb_id| imdb_id.to_string()).unwrap_or(name),
+			(None, None) => continue,
+		};
+		let size = parts.iter().map(|record| record.size.max(0) as u64).sum();
+		let paths = parts.iter().map(|record| record.path.clone()).collect();
+		copies.entry((title, main.year)).or_default().push((size, paths));
+	}
+
+	let mut duplicates: Vec<Duplicate> = copies
+		.into_iter()
+		.filter(|(_, items)| items.len() > 1)
+		.map(|((title, _), mut items)| {
+			items.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+			Duplicate {
+				title,
+				paths: items.iter().flat_map(|(_, paths)| paths.iter().cloned()).collect(),
+				wasted: items[1..].iter().map(|(size, _)| size).sum(),
+			}
+		})
+		.collect();
+	duplicates.sort_by(|a, b| b.wasted.cmp(&a.wasted).then_with(|| a.title.cmp(&b.title)));
+	duplicates
+}
+
+impl Report {
+	pub fn new(records: &[files::Model]) -> Self {
+		let mut report = Report::default();
+		let mut by_resolution = BTreeMap::new();
+		let mut by_codec = BTreeMap::new();
+		let mut by_quality = BTreeMap::new();
+		let mut by_year = BTreeMap::new();
+		let mut by_decade = BTreeMap::new();
+		let mut mains = vec![];
+
+		for record in records {
+			let size = record.size.max(0) as u64;
+			report.files += 1;
+			report.size += size;
+			if record.kind == KIND_ERROR {
+				report.scan_errors.push(Issue {
+					path: record.path.clone(),
+					detail: record.error.clone().unwrap_or_default(),
+				});
+				continue;
+			}
+			if size == 0 {
+				report.empty.push(record.path.clone());
+			}
+			if record.kind != KIND_VIDEO && record.kind != KIND_DISC {
+				continue;
+			}
+
+			report.videos += 1;
+			add(&mut by_resolution, resolution(record), size);
+			add(&mut by_codec, record.venc.clone().unwrap_or_else(|| UNKNOWN.to_owned()), size);
+			add(
+				&mut by_quality,
+				record.vqual.clone().unwrap_or_else(|| UNKNOWN.to_owned()),
+				size,
+			);
+			match record.year {
+				Some(year) => {
+					add(&mut by_year, year.to_string(), size);
+					add(&mut by_decade, format!("{}s", year / 10 * 10), size);
+				}
+				None => {
+					add(&mut by_year, UNKNOWN.to_owned(), size);
+					add(&mut by_decade, UNKNOWN.to_owned(), size);
+				}
+			}
+
+			if let Some(error) = &record.error {
+				report.probe_failures.push(Issue {
+					path: record.path.clone(),
+					detail: error.clone(),
+				});
+			}
+			if let Some(status) = record
+				.integrity
+				.as_deref()
+				.filter(|status| *status != IntegrityStatus::Ok.as_str())
+			{
+				report.broken.push(Issue {
+					path: record.path.clone(),
+					detail: match &record.integrity_issues {
+						Some(issues) => format!("{status}: {issues}"),
+						None => status.to_owned(),
+					},
+				});
+			}
+			let warnings: Vec<&str> = record.warnings.as_deref().unwrap_or_default().split(',').collect();
+			if warnings.contains(&WARNING_TRUNCATED) {
+				report.truncated.push(record.path.clone());
+			}
+			let parse_warnings: Vec<&str> = warnings
+				.into_iter()
+				.filter(|warning| !warning.is_empty() && *warning != WARNING_TRUNCATED)
+				.collect();
+			if !parse_warnings.is_empty() {
+				report.warnings.push(Issue {
+					path: record.path.clone(),
+					detail: parse_warnings.join(", "),
+				});
+			}
+
+			if record.role.as_deref().unwrap_or(VideoRole::Main.as_str()) != VideoRole::Main.as_str() {
+				continue;
+			}
+			if record.imdb_id.is_none() {
+				report.unidentified.push(record.path.clone());
+			}
+			// duplicate episodes are listed by the series view
+			if record.episode.is_none() {
+				mains.push(record);
+			}
+		}
+
+		report.duplicates = duplicates(&mains);
+		report.wasted = report.duplicates.iter().map(|duplicate| duplicate.wasted).sum();
+
+		report.by_resolution = by_resolution.into_values().collect();
+		report.by_codec = by_codec.into_values().collect();
+		report.by_quality = by_quality.into_values().collect();
+		report.by_year = by_year.into_values().collect();
+		report.by_decade = by_decade.into_values().collect();
+		for list in [&mut report.unidentified, &mut report.empty, &mut report.truncated] {
+			list.sort();
+		}
+		for list in [
+			&mut report.warnings,
+			&mut report.probe_failures,
+			&mut report.scan_errors,
+			&mut report.broken,
+		] {
+			list.sort_by(|a, b| a.path.cmp(&b.path));
+		}
+		report
+	}
+
+	fn buckets(&self) -> [(&'static str, &[Bucket]); 5] {
+		[
+			("Resolution", &self.by_resolution),
+			("Codec", &self.by_codec),
+			("Quality", &self.by_quality),
+			("Decade", &self.by_decade),
+			("Year", &self.by_year),
+		]
+	}
+
+	fn issues(&self) -> [(&'static str, Vec<Issue>); 7] {
+		let paths = |paths: &[String]| {
+			paths
+				.iter()
+				.map(|path| Issue {
+					path: path.clone(),
+					detail: String::new(),
+				})
+				.collect()
+		};
+		[
+			("Probe failures", self.probe_failures.clone()),
+			("Scan errors", self.scan_errors.clone()),
+			("Broken files", self.broken.clone()),
+			("Empty files", paths(&self.empty)),
+			("Truncated files", paths(&self.truncated)),
+			("Parse warnings", self.warnings.clone()),
+			("Unidentified", paths(&self.unidentified)),
+		]
+	}
+
+	pub fn text(&self) -> String {
+		let mut text = format!(
+			"{} files, {}, {} videos\n",
+			self.files,
+			human_size(self.size as i64),
+			self.videos
+		);
+		for (title, buckets) in self.buckets() {
+			let _ = writeln!(text, "\n{title}:");
+			for bucket in buckets {
+				let _ = writeln!(
+					text,
+					"\t{}\t{} files\t{}",
+					bucket.key,
+					bucket.files,
+					human_size(bucket.size as i64)
+				);
+			}
+		}
+		for (title, issues) in self.issues() {
+			if issues.is_empty() {
+				continue;
+			}
+			let _ = writeln!(text, "\n{title} ({}):", issues.len());
+			for issue in issues {
+				let _ = match issue.detail.is_empty() {
+					true => writeln!(text, "\t{}", issue.path),
+					false => writeln!(text, "\t{}: {}", issue.path, issue.detail),
+				};
+			}
+		}
+		if !self.duplicates.is_empty() {
+			let _ = writeln!(
+				text,
+				"\nDuplicates ({}, {} wasted):",
+				self.duplicates.len(),
+				human_size(self.wasted as i64)
+			);
+			for duplicate in &self.duplicates {
+				let _ =
+					writeln!(text, "\t{}: {} wasted", duplicate.title, human_size(duplicate.wasted as i64));
+				for path in &duplicate.paths {
+					let _ = writeln!(text, "\t\t{path}");
+				}
+			}
+		}
+		text
+	}
+
+	/// Self-contained HTML page (no external styles or scripts).
+	pub fn html(&self) -> String {
+		let mut html = String::from(concat!(
+			"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Library report</title>\n",
+			"<style>\nbody { font-family: sans-serif; margin: 2em; }\n",
+			"table { border-collapse: collapse; margin-bottom: 1.5em; }\n",
+			"th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }\n",
+			"td.number { text-align: right; }\n</style>\n</head>\n<body>\n<h1>Library report</h1>\n",
+		));
+		let _ = writeln!(
+			html,
+			"<p>{} files, {}, {} videos</p>",
+			self.files,
+			human_size(self.size as i64),
+			self.videos
+		);
+		for (title, buckets) in self.buckets() {
+			let _ = writeln!(
+				html,
+				"<h2>{title}</h2>\n<table>\n<tr><th>{title}</th><th>Files</th><th>Size</th></tr>"
+			);
+			for bucket in buckets {
+				let _ = writeln!(
+					html,
+					"<tr><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>",
+					escape(&bucket.key),
+					bucket.files,
+					human_size(bucket.size as i64)
+				);
+			}
+			html.push_str("</table>\n");
+		}
+		for (title, issues) in self.issues() {
+			let _ = writeln!(html, "<h2>{title} ({})</h2>", issues.len());
+			if issues.is_empty() {
+				continue;
+			}
+			html.push_str("<table>\n");
+			for issue in issues {
+				let _ = writeln!(
+					html,
+					"<tr><td>{}</td><td>{}</td></tr>",
+					escape(&issue.path),
+					escape(&issue.detail)
+				);
+			}
+			html.push_str("</table>\n");
+		}
+		let _ = writeln!(
+			html,
+			"<h2>Duplicates ({}, {} wasted)</h2>",
+			self.duplicates.len(),
+			human_size(self.wasted as i64)
+		);
+		if !self.duplicates.is_empty() {
+			html.push_str("<table>\n<tr><th>Title</th><th>Wasted</th><th>Files</th></tr>\n");
+			for duplicate in &self.duplicates {
+				let paths: Vec<String> =
+					duplicate.paths.iter().map(|path| escape(path).into_owned()).collect();
+				let _ = writeln!(
+					html,
+					"<tr><td>{}</td><td class=\"number\">{}</td><td>{}</td></tr>",
+					escape(&duplicate.title),
+					human_size(duplicate.wasted as i64),
+					paths.join("<br>")
+				);
+			}
+			html.push_str("</table>\n");
+		}
+		html.push_str("</body>\n</html>\n");
+		html
+	}
+}
@@ -0,0 +1,211 @@
+use std::{
+	fs::Metadata,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{
+	gitignore::{Gitignore, GitignoreBuilder},
+	Match,
+};
+use regex::RegexSet;
+
+use crate::{
+	config::env_or,
+	errors::{MediaOrderError, Result},
+};
+
+pub const IGNORE_FILE: &str = ".mediaorderignore";
+
+lazy_static! {
+	static ref DEFAULT_EXCLUDE: Vec<&'static str> = vec![
+		".Trash*",
+		"$RECYCLE.BIN",
+		"@eaDir",
+		"lost+found",
+		"*.part",
+		"*.!qB",
+		"*.!ut",
+		"*.crdownload",
+	];
+}
+
+/// Include/exclude rules of the scan. Globs are matched case insensitive against the file name and the full
+/// path, regexes against the full path. Include globs are only applied to files, so folders are still walked.
+#[derive(Clone, Debug)]
+pub struct ScanRules {
+	include: Option<GlobSet>,
+	exclude: GlobSet,
+	exclude_regex: RegexSet,
+	pub min_size: u64,
+	pub hidden: bool,
+	pub follow_symlinks: bool,
+}
+
+impl Default for ScanRules {
+	fn default() -> Self {
+		Self {
+			include: None,
+			exclude: glob_set("SCAN_EXCLUDE", &DEFAULT_EXCLUDE).unwrap(),
+			exclude_regex: RegexSet::empty(),
+			min_size: 0,
+			hidden: false,
+			follow_symlinks: false,
+		}
+	}
+}
+
+fn env_list(name: &str) -> Option<Vec<String>> {
+	std::env::var(name).ok().map(|value| {
+		value
+			.split(',')
+			.map(str::trim)
+			.filter(|item| !item.is_empty())
+			.map(String::from)
+			.collect()
+	})
+}
+
+fn glob_set<S: AsRef<str>>(name: &str, globs: &[S]) -> Result<GlobSet> {
+	let mut builder = GlobSetBuilder::new();
+	for glob in globs {
+		let glob = glob.as_ref();
+		builder.add(
+			GlobBuilder::new(glob)
+				.case_insensitive(true)
+				.build()
+				.map_err(|_| MediaOrderError::ConfigError(name.to_owned(), glob.to_owned()))?,
+		);
+	}
+	builder
+		.build()
+		.map_err(|err| MediaOrderError::ConfigError(name.to_owned(), err.to_string()))
+}
+
+impl ScanRules {
+	pub fn from_env() -> Result<Self> {
+		let default = Self::default();
+		let regexes = env_list("SCAN_EXCLUDE_REGEX").unwrap_or_default();
+
+		Ok(Self {
+			include: env_list("SCAN_INCLUDE")
+				.map(|globs| glob_set("SCAN_INCLUDE", &globs))
+				.transpose()?,
+			exclude: match env_list("SCAN_EXCLUDE") {
+				Some(globs) => glob_set("SCAN_EXCLUDE", &globs)?,
+				None => default.exclude,
+			},
+			exclude_regex: RegexSet::new(&regexes).map_err(|err| {
+				MediaOrderError::ConfigError("SCAN_EXCLUDE_REGEX".to_owned(), err.to_string())
+			})?,
+			min_size: env_or("SCAN_MIN_SIZE", default.min_size)?,
+			hidden: env_or("SCAN_HIDDEN", default.hidden)?,
+			follow_symlinks: env_or("SCAN_FOLLOW_SYMLINKS", default.follow_symlinks)?,
+		})
+	}
+
+	pub fn include(mut self, globs: &[&str]) -> Result<Self> {
+		self.include = Some(glob_set("include", globs)?);
+		Ok(self)
+	}
+
+	pub fn exclude(mut self, globs: &[&str]) -> Result<Self> {
+		self.exclude = glob_set("exclude", globs)?;
+		Ok(self)
+	}
+
+	pub fn exclude_regex(mut self, regexes: &[&str]) -> Result<Self> {
+		self.exclude_regex = RegexSet::new(regexes)
+			.map_err(|err| MediaOrderError::ConfigError("exclude_regex".to_owned(), err.to_string()))?;
+		Ok(self)
+	}
+
+	/// Checks the path by the rules, `link_metadata` is the metadata of the path itself (not following
+	/// symlink), `metadata` is the metadata of the symlink target.
+	pub fn accepts(
+		&self,
+		path: &Path,
+		link_metadata: &Metadata,
+		metadata: &Metadata,
+		ignores: &Ignores,
+	) -> bool {
+		let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+
+		if (!self.hidden && name.starts_with('.'))
+			|| (!self.follow_symlinks && link_metadata.is_symlink())
+			|| self.exclude.is_match(name)
+			|| self.exclude.is_match(path)
+			|| self.exclude_regex.is_match(&path.to_string_lossy())
+			|| ignores.ignored(path, metadata.is_dir())
+		{
+			return false;
+		}
+
+		metadata.is_dir()
+			|| (metadata.len() >= self.min_size
+				&& self
+					.include
+					.as_ref()
+					.is_none_or(|include| include.is_match(name) || include.is_match(path)))
+	}
+
+	/// Checks the path and all its parents up to the `root`, as the scanner walking from the root would do.
+	pub async fn accepts_path(&self, root: &Path, path: &Path) -> bool {
+		let Ok(relative) = path.strip_prefix(root) else {
+			return false;
+		};
+		let mut ignores = Arc::new(Ignores::default());
+		let mut current = root.to_path_buf();
+
+		for component in relative.components() {
+			ignores = Ignores::load(&current, ignores).await;
+			current.push(component);
+			let (Ok(link_metadata), Ok(metadata)) =
+				(tokio::fs::symlink_metadata(&current).await, tokio::fs::metadata(&current).await)
+			else {
+				return false;
+			};
+			if !self.accepts(&current, &link_metadata, &metadata, &ignores) {
+				return false;
+			}
+		}
+		true
+	}
+}
+
+/// Chain of `.mediaorderignore` files from the scanned folder up to the scan root, the deepest file wins as
+/// in gitignore.
+#[derive(Debug, Default)]
+pub struct Ignores {
+	parent: Option<Arc<Ignores>>,
+	gitignore: Option<Gitignore>,
+}
+
+impl Ignores {
+	/// Adds ignore file of the folder (if any) to the parent chain.
+	pub async fn load(folder: &Path, parent: Arc<Ignores>) -> Arc<Ignores> {
+		let file: PathBuf = folder.join(IGNORE_FILE);
+		match tokio::fs::read_to_string(&file).await {
+			Ok(content) => {
+				let mut builder = GitignoreBuilder::new(folder);
+				for line in content.lines() {
+					let _ = builder.add_line(Some(file.clone()), line);
+				}
+				Arc::new(Ignores {
+					parent: Some(parent),
+					gitignore: builder.build().ok(),
+				})
+			}
+			Err(_) => parent,
+		}
+	}
+
+	pub fn ignored(&self, path: &Path, is_dir: bool) -> bool {
+		match self.gitignore.as_ref().map(|gitignore| gitignore.matched(path, is_dir)) {
+			Some(Match::Ignore(_)) => true,
+			Some(Match::Whitelist(_)) => false,
+			_ => self.parent.as_ref().is_some_and(|parent| parent.ignored(path, is_dir)),
+		}
+	}
+}
@@ -0,0 +1,219 @@
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	fmt,
+	os::unix::fs::MetadataExt,
+	path::PathBuf,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+use log::{debug, info};
+use tokio::{
+	sync::{mpsc, Semaphore},
+	task::JoinSet,
+};
+
+use crate::{
+	config::ScanConfig,
+	errors::Result,
+	rules::{Ignores, ScanRules},
+	types::{FSEntry, FromPath},
+	video::Video,
+};
+
+pub type ScanResult = (PathBuf, Result<FSEntry>);
+
+type DeviceLimits = Arc<Mutex<HashMap<u64, Arc<Semaphore>>>>;
+
+enum Scanned {
+	Skipped,
+	/// Folder entries with folder device and inode numbers, used to detect symlink loops.
+	Folder(Vec<PathBuf>, Option<(u64, u64)>, Arc<Ignores>),
+	Entry(PathBuf, u64, Box<Result<FSEntry>>),
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Progress {
+	pub folders: u64,
+	pub files: u64,
+	pub skipped: u64,
+	pub errors: u64,
+	pub bytes: u64,
+	pub elapsed: Duration,
+}
+
+impl Progress {
+	pub fn files_per_second(&self) -> f64 {
+		self.files as f64 / self.elapsed.as_secs_f64().max(0.001)
+	}
+
+	pub fn bytes_per_second(&self) -> f64 {
+		self.bytes as f64 / self.elapsed.as_secs_f64().max(0.001)
+	}
+}
+
+impl fmt::Display for Progress {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{} files, {} folders, {} skipped, {} errors, {:.1} MB in {:.1}s ({:.1} files/s, {:.1} MB/s)",
+			self.files,
+			self.folders,
+			self.skipped,
+			self.errors,
+			self.bytes as f64 / 1e6,
+			self.elapsed.as_secs_f64(),
+			self.files_per_second(),
+			self.bytes_per_second() / 1e6
+		)
+	}
+}
+
+pub struct Scanner {
+	config: ScanConfig,
+	rules: Arc<ScanRules>,
+	devices: DeviceLimits,
+	progress: Progress,
+}
+
+impl Scanner {
+	pub fn new(config: ScanConfig) -> Self {
+		Self {
+			config,
+			rules: Arc::new(ScanRules::default()),
+			devices: Arc::new(Mutex::new(HashMap::new())),
+			progress: Progress::default(),
+		}
+	}
+
+	pub fn with_rules(mut self, rules: ScanRules) -> Self {
+		self.rules = Arc::new(rules);
+		self
+	}
+
+	/// Walks the tree from `root` and collects the found videos (main features of the discs too) without storing
+	/// anything, for the dry runs.
+	pub async fn videos(self, root: PathBuf) -> Result<(Progress, Vec<Video>)> {
+		let (tx, mut rx) = mpsc::channel(self.config.queue_size);
+		let scanner = tokio::spawn(self.run(root, tx));
+		let mut videos = vec![];
+		while let Some((_, entry)) = rx.recv().await {
+			match entry {
+				Ok(FSEntry::Video(video)) => videos.push(video),
+				Ok(FSEntry::DiscImage(disc)) => videos.push(disc.video),
+				_ => {}
+			}
+		}
+		Ok((scanner.await??, videos))
+	}
+
+	/// Walks the tree from `root` and sends every found entry except folders to `tx`. At most
+	/// [`ScanConfig::workers`] paths are read at once (and [`ScanConfig::device_workers`] from one device), the
+	/// walk waits while the channel is full and stops when the receiver is dropped. Paths rejected by the
+	/// [`ScanRules`] are skipped, the root itself is always scanned.
+	pub async fn run(mut self, root: PathBuf, tx: mpsc::Sender<ScanResult>) -> Result<Progress> {
+		let started = Instant::now();
+		let mut reported = started;
+		let mut pending = VecDeque::from([(root, Arc::new(Ignores::default()), false)]);
+		let mut visited = HashSet::new();
+		let mut set = JoinSet::new();
+
+		loop {
+			while set.len() < self.config.workers {
+				let Some((path, ignores, check)) = pending.pop_front() else {
+					break;
+				};
+				let rules = if check { Some(self.rules.clone()) } else { None };
+				set.spawn(read_entry(
+					path,
+					ignores,
+					rules,
+					self.devices.clone(),
+					self.config.device_workers,
+				));
+			}
+
+			let Some(joined) = set.join_next().await else {
+				break;
+			};
+
+			match joined? {
+				Scanned::Skipped => self.progress.skipped += 1,
+				Scanned::Folder(entries, id, ignores) => {
+					if id.is_some_and(|id| !visited.insert(id)) {
+						debug!("Folder {:?} is already scanned (symlink loop?)", id);
+						self.progress.skipped += 1;
+						continue;
+					}
+					self.progress.folders += 1;
+					pending.extend(entries.into_iter().map(|entry| (entry, ignores.clone(), true)));
+				}
+				Scanned::Entry(path, size, entry) if entry.is_ok() => {
+					self.progress.files += 1;
+					self.progress.bytes += size;
+					if tx.send((path, *entry)).await.is_err() {
+						break;
+					}
+				}
+				Scanned::Entry(path, _, entry) => {
+					self.progress.errors += 1;
+					if tx.send((path, *entry)).await.is_err() {
+						break;
+					}
+				}
+			}
+
+			if reported.elapsed() >= self.config.progress_interval {
+				self.progress.elapsed = started.elapsed();
+				info!("Scanning: {}, {} paths pending", self.progress, pending.len() + set.len());
+				reported = Instant::now();
+			}
+		}
+
+		self.progress.elapsed = started.elapsed();
+		info!("Scan finished: {}", self.progress);
+		Ok(self.progress)
+	}
+}
+
+async fn read_entry(
+	path: PathBuf,
+	ignores: Arc<Ignores>,
+	rules: Option<Arc<ScanRules>>,
+	devices: DeviceLimits,
+	device_workers: usize,
+) -> Scanned {
+	let metadata = tokio::fs::metadata(&path).await.ok();
+
+	if let Some(rules) = rules {
+		let accepted = match (tokio::fs::symlink_metadata(&path).await, &metadata) {
+			(Ok(link_metadata), Some(metadata)) => rules.accepts(&path, &link_metadata, metadata, &ignores),
+			// broken symlink
+			(Ok(link_metadata), None) => rules.follow_symlinks || !link_metadata.is_symlink(),
+			_ => true,
+		};
+		if !accepted {
+			return Scanned::Skipped;
+		}
+	}
+
+	let device = metadata.as_ref().map(|metadata| metadata.dev()).unwrap_or_default();
+	let limit = devices
+		.lock()
+		.unwrap()
+		.entry(device)
+		.or_insert_with(|| Arc::new(Semaphore::new(device_workers)))
+		.clone();
+	let _permit = limit.acquire_owned().await;
+
+	match FSEntry::from_path(path.clone()).await {
+		Ok(FSEntry::Folder(entries)) => {
+			let id = metadata.as_ref().map(|metadata| (metadata.dev(), metadata.ino()));
+			Scanned::Folder(entries, id, Ignores::load(&path, ignores).await)
+		}
+		entry => {
+			let size = metadata.filter(|metadata| metadata.is_file()).map(|metadata| metadata.len());
+			Scanned::Entry(path, size.unwrap_or_default(), Box::new(entry))
+		}
+	}
+}
@@ -0,0 +1,191 @@
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt::Write,
+};
+
+use media_order_imdb::provider::MetadataProvider;
+use serde::Serialize;
+
+use crate::{errors::Result, library::entities::files};
+
+/// Season number of the specials.
+pub const SPECIALS: u16 = 0;
+
+/// Official episode numbers by the season number.
+pub type EpisodeMap = BTreeMap<u16, BTreeSet<u16>>;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Season {
+	pub number: u16,
+	/// Files by the episode number, multi-episode files are listed under each of their episodes.
+	pub episodes: BTreeMap<u16, Vec<String>>,
+	pub missing: Vec<u16>,
+	/// Episodes found in more than one file.
+	pub duplicates: Vec<u16>,
+	/// Episodes absent from the official list, or all the specials when there is no list.
+	pub extra: Vec<u16>,
+}
+
+impl Season {
+	fn new(number: u16) -> Self {
+		Self {
+			number,
+			..Default::default()
+		}
+	}
+
+	/// Gaps are found between the first and the last episode, unless the official list is known.
+	fn update(&mut self, official: Option<&BTreeSet<u16>>) {
+		let present: BTreeSet<u16> = self.episodes.keys().copied().collect();
+		self.duplicates = self
+			.episodes
+			.iter()
+			.filter(|(_, paths)| paths.len() > 1)
+			.map(|(episode, _)| *episode)
+			.collect();
+		(self.missing, self.extra) = match official {
+			Some(official) => (
+				official.difference(&present).copied().collect(),
+				present.difference(official).copied().collect(),
+			),
+			None if self.number == SPECIALS => (vec![], present.into_iter().collect()),
+			None => {
+				let last = present.last().copied().unwrap_or_default();
+				((1..=last).filter(|episode| !present.contains(episode)).collect(), vec![])
+			}
+		};
+	}
+
+	fn label(&self, episode: u16) -> String {
+		format!("S{:02}E{episode:02}", self.number)
+	}
+}
+
+/// Episode files of the show grouped by season.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Show {
+	pub name: String,
+	pub imdb_id: Option<String>,
+	pub seasons: Vec<Season>,
+	/// Seasons are compared with the official episode list.
+	pub official: bool,
+}
+
+impl Show {
+	/// Compares the seasons with the official episode list: all its seasons and episodes are expected, the
+	/// rest are extras.
+	pub fn compare(&mut self, official: &EpisodeMap) {
+		for number in official.keys() {
+			if !self.seasons.iter().any(|season| season.number == *number) {
+				self.seasons.push(Season::new(*number));
+			}
+		}
+		self.seasons.sort_by_key(|season| season.number);
+		let empty = BTreeSet::new();
+		for season in &mut self.seasons {
+			season.update(Some(official.get(&season.number).unwrap_or(&empty)));
+		}
+		self.official = true;
+	}
+
+	/// Show has missing or duplicate episodes.
+	pub fn has_gaps(&self) -> bool {
+		self.seasons
+			.iter()
+			.any(|season| !season.missing.is_empty() || !season.duplicates.is_empty())
+	}
+}
+
+/// Groups the episode records (with the season and episode numbers) by show name and season.
+pub fn shows(records: &[files::Model]) -> Vec<Show> {
+	let mut shows: BTreeMap<String, Show> = BTreeMap::new();
+	for record in records {
+		let (Some(season), Some(episode)) = (record.season, record.episode) else {
+			continue;
+		};
+		let name = record.name.clone().unwrap_or_default();
+		let show = shows.entry(name.to_lowercase()).or_insert_with(|| Show {
+			name,
+			..Default::default()
+		});
+		if show.imdb_id.is_none() {
+			show.imdb_id = record.imdb_id.clone();
+		}
+		let season = season as u16;
+		if !show.seasons.iter().any(|known| known.number == season) {
+			show.seasons.push(Season::new(season));
+		}
+		let season = show
+			.seasons
+			.iter_mut()
+			.find(|known| known.number == season)
+			.expect("season is added above");
+		let last_episode = record.last_episode.unwrap_or(episode).max(episode);
+		for episode in episode..=last_episode {
+			season.episodes.entry(episode as u16).or_default().push(record.path.clone());
+		}
+	}
+
+	let mut shows: Vec<Show> = shows.into_values().collect();
+	for show in &mut shows {
+		show.seasons.sort_by_key(|season| season.number);
+		for season in &mut show.seasons {
+			season.update(None);
+		}
+	}
+	shows
+}
+
+/// Compares the shows with a known IMDb id with their official episode lists, the shows unknown to the
+/// provider are left numbered by the files.
+pub async fn compare_official(shows: &mut [Show], provider: &dyn MetadataProvider) -> Result<()> {
+	for show in shows {
+		let Some(imdb_id) = &show.imdb_id else {
+			continue;
+		};
+		let mut official = EpisodeMap::new();
+		for episode in provider.episodes(imdb_id).await? {
+			official.entry(episode.season).or_default().insert(episode.episode);
+		}
+		if !official.is_empty() {
+			show.compare(&official);
+		}
+	}
+	Ok(())
+}
+
+fn labels(season: &Season, episodes: &[u16]) -> String {
+	episodes
+		.iter()
+		.map(|episode| season.label(*episode))
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+/// Plain text summary of the shows, one line per season.
+pub fn text(shows: &[Show]) -> String {
+	let mut text = String::new();
+	for show in shows {
+		let _ = match &show.imdb_id {
+			Some(imdb_id) => writeln!(text, "{} ({imdb_id})", show.name),
+			None => writeln!(text, "{}", show.name),
+		};
+		for season in &show.seasons {
+			let mut line = match season.number {
+				SPECIALS => format!("\tSpecials: {} episodes", season.episodes.len()),
+				number => format!("\tSeason {number}: {} episodes", season.episodes.len()),
+			};
+			for (title, episodes) in [
+				("missing", &season.missing),
+				("duplicates", &season.duplicates),
+				("extra", &season.extra),
+			] {
+				if !episodes.is_empty() {
+					let _ = write!(line, "; {title} {}", labels(season, episodes));
+				}
+			}
+			let _ = writeln!(text, "{line}");
+		}
+	}
+	text
+}
@@ -0,0 +1,111 @@
+use std::fs;
+
+use crate::{
+	disc::{bluray_playlist, dvd_title_set_duration, DiscKind},
+	tests::temp_dir,
+	types::{FSEntry, FromPath},
+};
+
+fn mpls(clips: &[(&str, u32, u32)]) -> Vec<u8> {
+	let mut data = b"MPLS0200".to_vec();
+	data.extend(20u32.to_be_bytes());
+	data.resize(20, 0);
+	data.extend([0; 6]);
+	data.extend((clips.len() as u16).to_be_bytes());
+	data.extend([0; 2]);
+	for (clip, in_time, out_time) in clips {
+		data.extend(20u16.to_be_bytes());
+		data.extend(clip.as_bytes());
+		data.extend(b"M2TS");
+		data.extend([0; 3]);
+		data.extend(in_time.to_be_bytes());
+		data.extend(out_time.to_be_bytes());
+	}
+	data
+}
+
+fn vts_ifo(times: &[[u8; 4]]) -> Vec<u8> {
+	let mut data = b"DVDVIDEO-VTS".to_vec();
+	data.resize(0xCC, 0);
+	data.extend(1u32.to_be_bytes());
+	data.resize(2048, 0);
+	data.extend((times.len() as u16).to_be_bytes());
+	data.resize(2048 + 8, 0);
+	let table_end = 8 + times.len() * 8;
+	for i in 0..times.len() {
+		data.extend([0x81, 0, 0, 0]);
+		data.extend(((table_end + i * 8) as u32).to_be_bytes());
+	}
+	for time in times {
+		data.extend([0; 4]);
+		data.extend(time);
+	}
+	data
+}
+
+#[test]
+fn parse_disc_structures() {
+	let (duration, clips) =
+		bluray_playlist(&mpls(&[("00001", 0, 45000 * 60), ("00002", 0, 45000 * 30)])).unwrap();
+	assert_eq!(duration, 90.0);
+	assert_eq!(clips, vec!["00001", "00002"]);
+
+	// 1:32:10 and 25 frames at 25 fps
+	assert_eq!(
+		dvd_title_set_duration(&vts_ifo(&[[0x00, 0x05, 0x00, 0x40], [0x01, 0x32, 0x10, 0x65]])),
+		Some(5531.0)
+	);
+	assert_eq!(dvd_title_set_duration(b"not an ifo"), None);
+}
+
+#[tokio::test]
+async fn recognise_bluray_folder() {
+	let root = temp_dir("bluray").join("Brat.1997.BDRip");
+	fs::create_dir_all(root.join("BDMV/PLAYLIST")).unwrap();
+	fs::create_dir_all(root.join("BDMV/STREAM")).unwrap();
+	fs::write(root.join("BDMV/PLAYLIST/00000.mpls"), mpls(&[("00009", 0, 45000 * 60)])).unwrap();
+	fs::write(root.join("BDMV/PLAYLIST/00001.mpls"), mpls(&[("00001", 0, 45000 * 5000)])).unwrap();
+	fs::write(root.join("BDMV/STREAM/00001.m2ts"), [0; 16]).unwrap();
+	fs::write(root.join("BDMV/STREAM/00009.m2ts"), [0; 16]).unwrap();
+
+	match FSEntry::from_path(root.clone()).await.unwrap() {
+		FSEntry::DiscImage(disc) => {
+			assert_eq!(disc.kind, DiscKind::BluRay);
+			assert_eq!(disc.root, root);
+			assert_eq!(disc.main_feature, Some(root.join("BDMV/PLAYLIST/00001.mpls")));
+			assert_eq!(disc.streams, vec![root.join("BDMV/STREAM/00001.m2ts")]);
+			assert_eq!(disc.video.name_original, "Brat");
+			assert_eq!(disc.video.year, Some(1997));
+		}
+		entry => panic!("Not a disc: {entry:?}"),
+	}
+
+	fs::remove_dir_all(root.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn recognise_dvd_folder() {
+	let root = temp_dir("dvd").join("Ironiya sudby (1975)");
+	let video_ts = root.join("VIDEO_TS");
+	fs::create_dir_all(&video_ts).unwrap();
+	fs::write(video_ts.join("VTS_01_0.IFO"), vts_ifo(&[[0x00, 0x03, 0x00, 0x40]])).unwrap();
+	fs::write(video_ts.join("VTS_01_1.VOB"), [0; 16]).unwrap();
+	fs::write(video_ts.join("VTS_02_0.IFO"), vts_ifo(&[[0x03, 0x04, 0x00, 0x40]])).unwrap();
+	fs::write(video_ts.join("VTS_02_0.VOB"), [0; 16]).unwrap();
+	fs::write(video_ts.join("VTS_02_1.VOB"), [0; 16]).unwrap();
+	fs::write(video_ts.join("VTS_02_2.VOB"), [0; 16]).unwrap();
+
+	match FSEntry::from_path(video_ts.clone()).await.unwrap() {
+		FSEntry::DiscImage(disc) => {
+			assert_eq!(disc.kind, DiscKind::Dvd);
+			assert_eq!(disc.root, root);
+			assert_eq!(disc.duration, Some(11040.0));
+			assert_eq!(disc.streams, vec![video_ts.join("VTS_02_1.VOB"), video_ts.join("VTS_02_2.VOB")]);
+			assert_eq!(disc.video.name_original, "Ironiya sudby");
+			assert_eq!(disc.video.year, Some(1975));
+		}
+		entry => panic!("Not a disc: {entry:?}"),
+	}
+
+	fs::remove_dir_all(root.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,90 @@
+use std::fs;
+
+use media_order_imdb::provider::{fixture::FixtureProvider, TitleMetadata};
+
+use crate::{
+	config::{HookConfig, OrganiseConfig},
+	hook::{Hook, HookStatus},
+	library::Library,
+	organise::{is_same_file, PlaceMode},
+	tests::{mkv, temp_dir},
+	view::OrganisedView,
+};
+
+#[tokio::test]
+async fn hook_status() {
+	let dir = temp_dir("hook");
+	let download = dir.join("download");
+	fs::create_dir_all(&download).unwrap();
+	fs::write(download.join("readme.txt"), "no media here").unwrap();
+
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let hook = Hook::new(&library, OrganiseConfig::new(dir.join("library"))).with_hook_config(HookConfig {
+		categories: vec!["movies".to_owned()],
+	});
+
+	assert_eq!(hook.run(download.clone(), Some("music"), &[]).await, HookStatus::Skipped);
+	assert_eq!(
+		hook.run(download.clone(), None, &["Movies".to_owned()]).await,
+		HookStatus::NoMedia
+	);
+	assert_eq!(hook.run(dir.join("missing"), Some("movies"), &[]).await.code(), 1);
+	assert!(!dir.join("library").exists());
+
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn placed_download_is_linked_into_view() {
+	let dir = temp_dir("hook-view");
+	let download = dir.join("download");
+	fs::create_dir_all(&download).unwrap();
+	fs::write(download.join("Brat.1997.mkv"), mkv()).unwrap();
+
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let mut config = OrganiseConfig::new(dir.join("library"));
+	config.mode = PlaceMode::Move;
+	let hook = Hook::new(&library, config).with_view(OrganisedView::new(dir.join("view")));
+	assert_eq!(hook.run(download.clone(), None, &[]).await, HookStatus::Placed);
+
+	let placed = dir.join("library/Brat (1997)/Brat (1997).mkv");
+	let link = dir.join("view/Brat (1997)/Brat (1997).mkv");
+	assert!(placed.exists());
+	assert!(is_same_file(&placed, &link));
+
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn placed_download_is_identified() {
+	let dir = temp_dir("hook-identify");
+	let download = dir.join("download");
+	fs::create_dir_all(&download).unwrap();
+	fs::write(download.join("brat.1997.mkv"), mkv()).unwrap();
+	fs::write(download.join("readme.txt"), "release notes").unwrap();
+
+	let provider = FixtureProvider::new("fixture").with_title(TitleMetadata {
+		tconst: "tt0124315".to_owned(),
+		title: "Brat".to_owned(),
+		original_title: None,
+		title_type: Some("movie".to_owned()),
+		year: Some(1997),
+		genres: vec![],
+		rating: None,
+		votes: None,
+		source: String::new(),
+		score: 1.0,
+	});
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let hook = Hook::new(&library, OrganiseConfig::new(dir.join("library"))).with_provider(&provider);
+	assert_eq!(hook.run(download.clone(), None, &[]).await, HookStatus::Placed);
+
+	// placed by the identified title, the record follows the hardlink
+	let placed = dir.join("library/Brat (1997)/Brat (1997).mkv");
+	assert!(placed.exists() && download.join("brat.1997.mkv").exists());
+	let record = library.get(&placed).await.unwrap().unwrap();
+	assert_eq!(record.imdb_id.as_deref(), Some("tt0124315"));
+	assert!(library.files_under(&download).await.unwrap().is_empty());
+
+	fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use file_format::FileFormat;
+use media_order_imdb::provider::{fixture::FixtureProvider, TitleMetadata};
+
+use crate::{
+	identify::{identify, is_identifiable},
+	library::{query::Query, Library},
+	types::FSEntry,
+	video::Video,
+};
+
+fn title(tconst: &str, title: &str, title_type: &str, year: u16) -> TitleMetadata {
+	TitleMetadata {
+		tconst: tconst.to_owned(),
+		title: title.to_owned(),
+		original_title: None,
+		title_type: Some(title_type.to_owned()),
+		year: Some(year),
+		genres: vec!["Crime".to_owned(), "Drama".to_owned()],
+		rating: Some(8.3),
+		votes: Some(50000),
+		source: String::new(),
+		score: 1.0,
+	}
+}
+
+async fn store(library: &Library, path: &str, name: &str, year: Option<u16>, season: Option<u16>) {
+	let mut video = Video::new(PathBuf::from(path), FileFormat::MatroskaVideo);
+	video.name_original = name.to_owned();
+	video.year = year;
+	video.season = season;
+	video.episode = season.map(|_| 1);
+	library.store(&video.path.clone(), &Ok(FSEntry::Video(video))).await.unwrap();
+}
+
+#[tokio::test]
+async fn identify_and_search_by_genre() {
+	let provider = FixtureProvider::new("fixture")
+		.with_title(title("tt0124315", "Brat", "movie", 1997))
+		.with_title(title("tt0306566", "Brigada", "movie", 1990))
+		.with_title(title("tt0349125", "Brigada", "tvMiniSeries", 2002));
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	store(&library, "/films/Brat.1997.mkv", "Brat", Some(1997), None).await;
+	store(&library, "/series/Brigada.S01E01.2002.mkv", "Brigada", Some(2002), Some(1)).await;
+	store(&library, "/films/Unknown.mkv", "Neizvestnyy", None, None).await;
+
+	let mut overrides = library.override_cache().await.unwrap();
+	for record in library.files().await.unwrap() {
+		assert!(is_identifiable(&record));
+		if let Some(title) = identify(&provider, &record).await.unwrap() {
+			let path = PathBuf::from(&record.path);
+			assert!(library.set_title(&path, &title, &mut overrides).await.unwrap());
+		}
+	}
+
+	let brat = library.get(&PathBuf::from("/films/Brat.1997.mkv")).await.unwrap().unwrap();
+	assert_eq!(brat.imdb_id.as_deref(), Some("tt0124315"));
+	// episodes are identified as the series
+	let episode = library
+		.get(&PathBuf::from("/series/Brigada.S01E01.2002.mkv"))
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(episode.imdb_id.as_deref(), Some("tt0349125"));
+	let unknown = library.get(&PathBuf::from("/films/Unknown.mkv")).await.unwrap().unwrap();
+	assert_eq!(unknown.imdb_id, None);
+
+	let found = library.search(&"genre:Crime rating>8".parse::<Query>().unwrap()).await.unwrap();
+	assert_eq!(found.len(), 2);
+
+	// known IMDb id is looked up
+	let title = identify(&provider, &brat).await.unwrap().unwrap();
+	assert_eq!((title.imdb_id.as_str(), title.year), ("tt0124315", Some(1997)));
+}
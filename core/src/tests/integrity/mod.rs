@@ -0,0 +1,66 @@
+use std::{fs, path::Path};
+
+use crate::{
+	config::ScanConfig,
+	integrity::{check, is_truncated, short_streams, CheckMode, Integrity, IntegrityStatus},
+	library::Library,
+	rules::ScanRules,
+	tests::temp_dir,
+};
+
+#[test]
+fn truncation_checks() {
+	// 8 Mbit/s for 100 seconds is 100 MB
+	assert!(is_truncated(8_000_000, 100.0, 50_000_000));
+	assert!(!is_truncated(8_000_000, 100.0, 95_000_000));
+	assert!(!is_truncated(0, 100.0, 10));
+
+	let issues = short_streams(6000.0, &[(0, 5999.0), (1, 5000.0), (2, -1.0)]);
+	assert_eq!(issues, ["stream #1 ends at 5000s of 6000s"]);
+	assert!(short_streams(60.0, &[(0, 56.0)]).is_empty());
+
+	assert_eq!("deep".parse::<CheckMode>().unwrap(), CheckMode::Deep);
+	assert!("thorough".parse::<CheckMode>().is_err());
+	assert!(IntegrityStatus::Unreadable > IntegrityStatus::Truncated);
+}
+
+#[tokio::test]
+async fn integrity_is_stored_until_modified() {
+	let root = temp_dir("integrity");
+	let path = root.join("Brat (1997).avi");
+	fs::write(&path, b"RIFF\x10\0\0\0AVI LIST\x04\0\0\0hdrl").unwrap();
+
+	let integrity = check(&path, CheckMode::Quick);
+	assert_eq!(integrity.status, IntegrityStatus::Unreadable);
+	assert_eq!(
+		check(Path::new("/nonexistent.avi"), CheckMode::Deep).status,
+		IntegrityStatus::Unreadable
+	);
+
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let scan = || library.scan(root.clone(), ScanConfig::default(), ScanRules::default());
+	scan().await.unwrap();
+	assert!(library.set_integrity(&path, &integrity).await.unwrap());
+	let broken = "integrity:truncated,corrupt,unreadable".parse().unwrap();
+	assert_eq!(library.search(&broken).await.unwrap().len(), 1);
+
+	// rescan keeps the result of the unchanged file
+	scan().await.unwrap();
+	let record = library.get(&path).await.unwrap().unwrap();
+	assert_eq!(record.integrity.as_deref(), Some("unreadable"));
+	assert!(record.checked.is_some());
+
+	let ok = Integrity::default();
+	library.set_integrity(&path, &ok).await.unwrap();
+	assert!(library.search(&broken).await.unwrap().is_empty());
+
+	// modified file has to be checked again
+	let file = fs::File::options().write(true).open(&path).unwrap();
+	file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60))
+		.unwrap();
+	scan().await.unwrap();
+	let record = library.get(&path).await.unwrap().unwrap();
+	assert_eq!((record.integrity, record.checked), (None, None));
+
+	fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,68 @@
+use ffmpeg_the_third::media::Type as MediaType;
+
+use crate::{
+	languages::{propose_languages, LanguageReason},
+	video::StreamInfo,
+};
+
+fn audio(index: usize, language: Option<&str>, title: Option<&str>, channels: u16) -> StreamInfo {
+	let mut stream = StreamInfo::new(index, MediaType::Audio);
+	stream.language = language.map(String::from);
+	stream.title = title.map(String::from);
+	stream.channels = channels;
+	stream
+}
+
+fn proposed(hints: &[&str], streams: &[StreamInfo]) -> Vec<(usize, String, LanguageReason)> {
+	propose_languages(hints, streams)
+		.into_iter()
+		.map(|proposal| (proposal.index, proposal.language, proposal.reason))
+		.collect()
+}
+
+#[test]
+fn propose_by_order_and_title() {
+	let video = StreamInfo::new(0, MediaType::Video);
+	let streams = [
+		video.clone(),
+		audio(1, Some("und"), None, 6),
+		audio(2, None, None, 2),
+	];
+	assert_eq!(proposed(&["rus", "eng"], &streams), vec![
+		(1, "rus".to_owned(), LanguageReason::Order),
+		(2, "eng".to_owned(), LanguageReason::Order),
+	]);
+
+	// tagged stream and the title take their languages out of the name ones
+	let streams = [
+		video.clone(),
+		audio(1, Some("und"), Some("Original"), 6),
+		audio(2, Some("und"), Some("Дубляж"), 6),
+		audio(3, Some("ukr"), None, 2),
+	];
+	assert_eq!(proposed(&["eng", "rus", "ukr"], &streams), vec![
+		(1, "eng".to_owned(), LanguageReason::Order),
+		(2, "rus".to_owned(), LanguageReason::Title),
+	]);
+
+	// title is enough without the name languages
+	assert_eq!(proposed(&[], &streams), vec![(2, "rus".to_owned(), LanguageReason::Title)]);
+}
+
+#[test]
+fn propose_by_channels() {
+	let streams = [
+		audio(0, Some("und"), None, 2),
+		audio(1, Some("und"), None, 6),
+		audio(2, Some("und"), Some("Commentary"), 2),
+	];
+	assert_eq!(proposed(&["rus"], &streams), vec![(
+		1,
+		"rus".to_owned(),
+		LanguageReason::Channels
+	)]);
+
+	// can't choose between the streams with the same channels
+	let streams = [audio(0, Some("und"), None, 6), audio(1, Some("und"), None, 6)];
+	assert!(proposed(&["rus"], &streams).is_empty());
+}
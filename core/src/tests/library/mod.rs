@@ -0,0 +1,118 @@
+use std::fs;
+
+use file_format::FileFormat;
+
+use crate::{
+	config::ScanConfig,
+	library::{Library, KIND_FILE},
+	media::TitleInfo,
+	rules::ScanRules,
+	tests::{mkv, temp_dir},
+	types::FSEntry,
+	video::Video,
+};
+
+#[tokio::test]
+async fn store_rename_and_remove_records() {
+	let root = temp_dir("library");
+	for file in ["a/1.txt", "a/2.txt", "b/3.txt"] {
+		let path = root.join(file);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, "text file").unwrap();
+	}
+
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let (progress, videos) = library
+		.scan(root.clone(), ScanConfig::default(), ScanRules::default())
+		.await
+		.unwrap();
+	assert_eq!(progress.files, 3);
+	assert!(videos.is_empty());
+
+	let file = library.get(&root.join("a/1.txt")).await.unwrap().unwrap();
+	assert_eq!(file.kind, KIND_FILE);
+	assert_eq!(file.size, 9);
+	assert_eq!(library.files_under(&root.join("a")).await.unwrap().len(), 2);
+
+	fs::rename(root.join("a"), root.join("c")).unwrap();
+	assert_eq!(library.rename(&root.join("a"), &root.join("c")).await.unwrap(), 2);
+	assert!(library.get(&root.join("a/2.txt")).await.unwrap().is_none());
+	assert!(library.get(&root.join("c/2.txt")).await.unwrap().is_some());
+
+	fs::remove_dir_all(root.join("c")).unwrap();
+	assert_eq!(library.remove(&root.join("c")).await.unwrap(), 2);
+	assert_eq!(library.files().await.unwrap().len(), 1);
+
+	// records of the deleted files are dropped by the rescan
+	fs::remove_file(root.join("b/3.txt")).unwrap();
+	library
+		.scan(root.clone(), ScanConfig::default(), ScanRules::default())
+		.await
+		.unwrap();
+	assert!(library.files().await.unwrap().is_empty());
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn rescan_keeps_identified_title() {
+	let root = temp_dir("library-title");
+	let path = root.join("Brat.1997.mkv");
+	fs::write(&path, mkv()).unwrap();
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let entry = || {
+		let mut video = Video::new(path.clone(), FileFormat::MatroskaVideo);
+		video.name_original = "Brat".to_owned();
+		Ok(FSEntry::Video(video))
+	};
+
+	library.store(&path, &entry()).await.unwrap();
+	let title = TitleInfo {
+		imdb_id: "tt0124315".to_owned(),
+		title: "Brother".to_owned(),
+		genres: vec!["Crime".to_owned(), "Drama".to_owned()],
+		rating: Some(7.8),
+		..Default::default()
+	};
+	assert!(library
+		.set_title(&path, &title, &mut library.override_cache().await.unwrap())
+		.await
+		.unwrap());
+	library.store(&path, &entry()).await.unwrap();
+
+	let file = library.get(&path).await.unwrap().unwrap();
+	assert_eq!(file.imdb_id.as_deref(), Some("tt0124315"));
+	assert_eq!(file.genres.as_deref(), Some("Crime,Drama"));
+	assert_eq!(file.rating, Some(7.8));
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn identified_title_is_read_back() {
+	let root = temp_dir("library-title-info");
+	let path = root.join("Brat.1997.mkv");
+	fs::write(&path, mkv()).unwrap();
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let mut video = Video::new(path.clone(), FileFormat::MatroskaVideo);
+	video.name_original = "Brat".to_owned();
+	library.store(&path, &Ok(FSEntry::Video(video))).await.unwrap();
+
+	let title = TitleInfo {
+		imdb_id: "tt0124315".to_owned(),
+		title: "Brother".to_owned(),
+		original_title: Some("Брат".to_owned()),
+		year: Some(1997),
+		..Default::default()
+	};
+	assert!(library
+		.set_title(&path, &title, &mut library.override_cache().await.unwrap())
+		.await
+		.unwrap());
+	let file = library.get(&path).await.unwrap().unwrap();
+	let info = TitleInfo::from_record(&file).unwrap();
+	assert_eq!(info.title, "Brother");
+	assert_eq!(info.original_title.as_deref(), Some("Брат"));
+
+	fs::remove_dir_all(root).unwrap();
+}
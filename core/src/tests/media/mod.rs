@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use file_format::FileFormat;
+
+use crate::{
+	media::group_parts,
+	video::{Video, VideoRole},
+};
+
+fn discovered(path: &str) -> Video {
+	let mut video = Video::new(PathBuf::from(path), FileFormat::MatroskaVideo);
+	video.discover().unwrap();
+	video
+}
+
+#[test]
+fn group_sibling_parts() {
+	let items = group_parts(vec![
+		discovered("/films/Terminator.1984.CD2.avi"),
+		discovered("/films/Brat.1997.avi"),
+		discovered("/films/Terminator.1984.CD1.avi"),
+		discovered("/other/Terminator.1984.CD1.avi"),
+	]);
+
+	assert_eq!(items.len(), 3);
+	assert!(items[0].is_multipart());
+	assert_eq!(items[0].name(), "Terminator");
+	assert_eq!(items[0].videos.iter().map(|video| video.part).collect::<Vec<_>>(), vec![
+		Some(1),
+		Some(2)
+	]);
+	assert!(!items[1].is_multipart());
+	assert_eq!(items[2].missing_parts(), Vec::<u8>::new());
+}
+
+#[test]
+fn missing_parts() {
+	let items = group_parts(vec![
+		discovered("/films/Brat.1997.cd3.avi"),
+		discovered("/films/Brat.1997.cd1.avi"),
+	]);
+
+	assert_eq!(items.len(), 1);
+	assert_eq!(items[0].missing_parts(), vec![2]);
+}
+
+#[test]
+fn attach_extras_to_release() {
+	let items = group_parts(vec![
+		discovered("/films/Brat.1997/Brat.1997.cd1.avi"),
+		discovered("/films/Brat.1997/Sample/sample.avi"),
+		discovered("/films/Brat.1997/Brat.1997.cd2.avi"),
+		discovered("/films/Brat.1997/Brat-trailer.mp4"),
+		discovered("/films/Collection/Brat.1997.avi"),
+		discovered("/films/Collection/Brat 2.2000.avi"),
+		discovered("/films/Collection/Brat 2-trailer.avi"),
+		discovered("/films/Collection/Other-trailer.avi"),
+	]);
+
+	assert_eq!(items.len(), 4);
+	assert_eq!(items[0].videos.len(), 2);
+	assert_eq!(items[0].extras.iter().map(|video| video.role).collect::<Vec<_>>(), vec![
+		VideoRole::Sample,
+		VideoRole::Trailer
+	]);
+	assert!(items[1].extras.is_empty());
+	assert_eq!(items[2].name(), "Brat 2");
+	assert_eq!(items[2].extras.len(), 1);
+	assert!(items[3].is_extra());
+}
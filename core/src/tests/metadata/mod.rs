@@ -0,0 +1,55 @@
+use std::fs;
+
+use crate::{
+	errors::MediaOrderError,
+	media::TitleInfo,
+	metadata::{write_tags, MetadataTags, StreamTags},
+	tests::temp_dir,
+};
+
+#[test]
+fn metadata_tags() {
+	let title = TitleInfo {
+		imdb_id: "tt0124315".to_owned(),
+		title: "Brat".to_owned(),
+		year: Some(1997),
+		..Default::default()
+	};
+	let tags = MetadataTags::from(&title)
+		.language(1, "rus")
+		.default_flag(1, true)
+		.forced_flag(2, true);
+
+	assert_eq!(tags.imdb_id.as_deref(), Some("tt0124315"));
+	assert_eq!(tags.streams[&1], StreamTags {
+		language: Some("rus".to_owned()),
+		default: Some(true),
+		forced: None,
+	});
+	assert_eq!(tags.streams[&2].forced, Some(true));
+	assert!(MetadataTags::default().is_empty());
+}
+
+#[test]
+fn source_kept_on_failure() {
+	let dir = temp_dir("metadata");
+	let tags = MetadataTags {
+		title: Some("Brat".to_owned()),
+		..Default::default()
+	};
+
+	let avi = dir.join("Brat.1997.avi");
+	fs::write(&avi, "not a video").unwrap();
+	assert!(matches!(
+		write_tags(&avi, &tags),
+		Err(MediaOrderError::UnsupportedContainerError(_))
+	));
+
+	let mkv = dir.join("Brat.1997.mkv");
+	fs::write(&mkv, "not a video").unwrap();
+	assert!(write_tags(&mkv, &tags).is_err());
+	assert_eq!(fs::read_to_string(&mkv).unwrap(), "not a video");
+	assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+	fs::remove_dir_all(dir).unwrap();
+}
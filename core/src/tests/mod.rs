@@ -1 +1,84 @@
+pub mod disc;
+pub mod hook;
+pub mod identify;
+pub mod integrity;
+pub mod languages;
+pub mod library;
+pub mod media;
+pub mod metadata;
+pub mod nfo;
+pub mod organise;
+pub mod overrides;
+pub mod query;
+pub mod report;
+pub mod rules;
+pub mod scanner;
+pub mod series;
 pub mod video;
+pub mod view;
+pub mod watch;
+
+use std::{fs, path::PathBuf};
+
+use crate::library::entities::files;
+
+/// Creates empty temporary folder for the test.
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("media-order-{}-{name}", std::process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+/// Matroska header with a video track, recognised as a video which fails to probe.
+pub(crate) fn mkv() -> Vec<u8> {
+	let mut mkv = vec![0x1A, 0x45, 0xDF, 0xA3, 0x8B, 0x42, 0x82, 0x88];
+	mkv.extend(b"matroska");
+	mkv.extend([
+		0x18, 0x53, 0x80, 0x67, 0x90, 0x16, 0x54, 0xAE, 0x6B, 0x8B, 0xAE, 0x89, 0x86, 0x87,
+	]);
+	mkv.extend(b"V_MPEG4");
+	mkv.resize(64, 0);
+	mkv
+}
+
+/// Library record of the main file without the scanned details.
+pub(crate) fn record(path: &str, kind: &str) -> files::Model {
+	files::Model {
+		path: path.to_owned(),
+		kind: kind.to_owned(),
+		format: None,
+		size: 0,
+		modified: 0,
+		scanned: 0,
+		error: None,
+		name: None,
+		year: None,
+		part: None,
+		role: Some("main".to_owned()),
+		lang: None,
+		ext: None,
+		venc: None,
+		aenc: None,
+		vres: None,
+		vqual: None,
+		duration: None,
+		name_english: None,
+		imdb_id: None,
+		genres: None,
+		rating: None,
+		audio_langs: None,
+		height: None,
+		warnings: None,
+		integrity: None,
+		integrity_issues: None,
+		decode_errors: None,
+		checked: None,
+		season: None,
+		episode: None,
+		last_episode: None,
+		fingerprint: None,
+		title: None,
+		original_title: None,
+	}
+}
@@ -0,0 +1,150 @@
+use std::fs;
+
+use file_format::FileFormat;
+
+use crate::{
+	media::{group_parts, TitleInfo},
+	nfo::{nfo_path, render, write, write_item, write_show, NfoHint, NfoKind},
+	tests::temp_dir,
+	video::Video,
+};
+
+fn brat() -> TitleInfo {
+	TitleInfo {
+		imdb_id: "tt0124315".to_owned(),
+		title: "Brother".to_owned(),
+		original_title: Some("Брат".to_owned()),
+		year: Some(1997),
+		genres: vec!["Crime".to_owned(), "Drama".to_owned()],
+		runtime: Some(96),
+		rating: Some(8.3),
+	}
+}
+
+#[test]
+fn render_and_parse_nfo() {
+	let nfo = render(NfoKind::Movie, &brat(), None).unwrap();
+	assert!(nfo.starts_with(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#));
+	assert!(nfo.contains("\t<runtime>96</runtime>\n\t<genre>Crime</genre>\n\t<genre>Drama</genre>"));
+	assert!(nfo.contains(r#"<uniqueid type="imdb" default="true">tt0124315</uniqueid>"#));
+
+	assert_eq!(NfoHint::parse(&nfo), NfoHint {
+		imdb_id: Some("tt0124315".to_owned()),
+		title: Some("Brother".to_owned()),
+		original_title: Some("Брат".to_owned()),
+		year: Some(1997),
+		show_title: None,
+	});
+
+	let episode = render(NfoKind::Episode { season: 1, episode: 2 }, &brat(), None).unwrap();
+	assert!(episode.contains("<episodedetails>\n\t<showtitle>Brother</showtitle>"));
+	assert!(episode.contains("<season>1</season>\n\t<episode>2</episode>"));
+	// the series id is not the id of the episode
+	assert!(!episode.contains("uniqueid"));
+	assert!(!episode.contains("<title>"));
+	let hint = NfoHint::parse(&episode);
+	assert_eq!(hint.imdb_id, None);
+	assert_eq!(hint.show_title.as_deref(), Some("Brother"));
+
+	// scene NFO with the IMDb link only
+	let scene = NfoHint::parse("  RELEASE INFO\n  imdb....: https://www.imdb.com/title/tt0124315/\n");
+	assert_eq!(scene.imdb_id.as_deref(), Some("tt0124315"));
+	assert_eq!(scene.title, None);
+}
+
+#[test]
+fn write_nfo_next_to_video() {
+	let dir = temp_dir("nfo");
+	let folder = dir.join("Brat.1997.1080p");
+	fs::create_dir_all(&folder).unwrap();
+	let path = folder.join("Brat.1997.1080p.mkv");
+	fs::write(&path, "video").unwrap();
+
+	let mut video = Video::new(path.clone(), FileFormat::MatroskaVideo);
+	video.discover().unwrap();
+	let items = group_parts(vec![video]);
+	let nfo = nfo_path(&items[0], NfoKind::Movie).unwrap();
+	assert_eq!(nfo, folder.join("movie.nfo"));
+	assert_eq!(
+		nfo_path(&items[0], NfoKind::Episode { season: 1, episode: 1 }),
+		Some(folder.join("Brat.1997.1080p.nfo"))
+	);
+
+	assert!(write(&nfo, NfoKind::Movie, &brat(), Some(items[0].main())).unwrap());
+	let hint = NfoHint::find(items[0].main()).unwrap();
+	assert_eq!(hint.imdb_id.as_deref(), Some("tt0124315"));
+
+	let mut video = Video::new(path.clone(), FileFormat::MatroskaVideo);
+	video.discover().unwrap();
+	hint.apply(&mut video);
+	assert_eq!(video.imdb_id.as_deref(), Some("tt0124315"));
+	assert_eq!(video.name_english.as_deref(), Some("Brother"));
+
+	// NFO of another title is not overwritten
+	let other = TitleInfo {
+		imdb_id: "tt0238883".to_owned(),
+		title: "Brother 2".to_owned(),
+		..Default::default()
+	};
+	assert!(!write(&nfo, NfoKind::Movie, &other, None).unwrap());
+	assert!(fs::read_to_string(&nfo).unwrap().contains("tt0124315"));
+	assert_eq!(nfo_path(&items[0], NfoKind::TvShow), Some(folder.join("tvshow.nfo")));
+
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn shared_folder_nfo_is_not_applied() {
+	let dir = temp_dir("nfo-shared");
+	let films = dir.join("Films");
+	fs::create_dir_all(&films).unwrap();
+	fs::write(films.join("movie.nfo"), render(NfoKind::Movie, &brat(), None).unwrap()).unwrap();
+	let path = films.join("Terminator.1984.mkv");
+	fs::write(&path, "video").unwrap();
+
+	let mut video = Video::new(path, FileFormat::MatroskaVideo);
+	video.discover().unwrap();
+	assert_eq!(NfoHint::find(&video), None);
+
+	// the identified item gets the NFO next to the video in the shared folder
+	let items = group_parts(vec![video]);
+	let terminator = TitleInfo {
+		imdb_id: "tt0088247".to_owned(),
+		title: "The Terminator".to_owned(),
+		..Default::default()
+	};
+	let written = write_item(&items[0], &terminator).unwrap();
+	assert_eq!(written, Some(films.join("Terminator.1984.nfo")));
+	let hint = NfoHint::find(items[0].main()).unwrap();
+	assert_eq!(hint.imdb_id.as_deref(), Some("tt0088247"));
+
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn write_show_nfo_in_show_folder() {
+	let dir = temp_dir("nfo-show");
+	let season = dir.join("Brat").join("Season 1");
+	fs::create_dir_all(&season).unwrap();
+	let path = season.join("Brat.S01E02.mkv");
+	fs::write(&path, "video").unwrap();
+
+	let mut video = Video::new(path, FileFormat::MatroskaVideo);
+	video.discover().unwrap();
+	let items = group_parts(vec![video]);
+	let written = write_item(&items[0], &brat()).unwrap();
+	assert_eq!(written, Some(season.join("Brat.S01E02.nfo")));
+	let episode = NfoHint::find(items[0].main()).unwrap();
+	assert_eq!(episode.imdb_id, None);
+	assert_eq!(episode.show_title.as_deref(), Some("Brother"));
+	// the episode NFO written before is replaced
+	assert!(write_item(&items[0], &brat()).unwrap().is_some());
+
+	let show = write_show(&items[0], &brat()).unwrap();
+	assert_eq!(show, Some(dir.join("Brat").join("tvshow.nfo")));
+	let nfo = fs::read_to_string(dir.join("Brat").join("tvshow.nfo")).unwrap();
+	assert!(nfo.contains("<tvshow>\n\t<title>Brother</title>"));
+	assert!(nfo.contains(r#"<uniqueid type="imdb" default="true">tt0124315</uniqueid>"#));
+
+	fs::remove_dir_all(dir).unwrap();
+}
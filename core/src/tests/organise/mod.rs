@@ -0,0 +1,107 @@
+use std::{fs, os::unix::fs::MetadataExt, path::PathBuf};
+
+use file_format::FileFormat;
+
+use crate::{
+	errors::MediaOrderError,
+	media::group_parts,
+	organise::{place, plan, PlaceMode, Placement, RenameTemplate},
+	tests::temp_dir,
+	video::Video,
+};
+
+fn discovered(path: &str) -> Video {
+	let mut video = Video::new(PathBuf::from(path), FileFormat::MatroskaVideo);
+	video.discover().unwrap();
+	video
+}
+
+#[test]
+fn render_template() {
+	let template = RenameTemplate::default();
+	assert_eq!(
+		template.render(&discovered("/dl/Brat.1997.1080p.mkv"), None),
+		PathBuf::from("Brat (1997)/Brat (1997)")
+	);
+	// missing year leaves no empty brackets
+	assert_eq!(template.render(&discovered("/dl/Brat.mkv"), None), PathBuf::from("Brat/Brat"));
+	assert_eq!(
+		template.render(&discovered("/dl/Terminator.1984.CD2.avi"), None),
+		PathBuf::from("Terminator (1984)/Terminator (1984) - cd2")
+	);
+
+	let template: RenameTemplate = "{category}/{name} [{year}] {part}".parse().unwrap();
+	assert_eq!(
+		template.render(&discovered("/dl/Brat.1997.mkv"), Some("movies")),
+		PathBuf::from("movies/Brat [1997]")
+	);
+	assert_eq!(template.render(&discovered("/dl/Brat.mkv"), None), PathBuf::from("Brat"));
+	assert!("{name}/{title}".parse::<RenameTemplate>().is_err());
+}
+
+#[test]
+fn plan_parts_and_extras() {
+	let items = group_parts(vec![
+		discovered("/dl/Terminator.1984.CD1.avi"),
+		discovered("/dl/Terminator.1984.CD2.avi"),
+		discovered("/dl/Sample/Terminator.1984.sample.avi"),
+	]);
+	let root = PathBuf::from("/library");
+	let placements = plan(&items[0], &root, &RenameTemplate::default(), None);
+
+	assert_eq!(placements, vec![
+		Placement {
+			source: PathBuf::from("/dl/Terminator.1984.CD1.avi"),
+			target: PathBuf::from("/library/Terminator (1984)/Terminator (1984) - cd1.avi"),
+		},
+		Placement {
+			source: PathBuf::from("/dl/Terminator.1984.CD2.avi"),
+			target: PathBuf::from("/library/Terminator (1984)/Terminator (1984) - cd2.avi"),
+		},
+		Placement {
+			source: PathBuf::from("/dl/Sample/Terminator.1984.sample.avi"),
+			target: PathBuf::from("/library/Terminator (1984)/Samples/Terminator.1984.sample.avi"),
+		},
+	]);
+}
+
+#[test]
+fn place_files() {
+	let dir = temp_dir("organise");
+	let source = dir.join("download/Brat.1997.mkv");
+	fs::create_dir_all(source.parent().unwrap()).unwrap();
+	fs::write(&source, "video").unwrap();
+
+	let linked = Placement {
+		source: source.clone(),
+		target: dir.join("library/Brat (1997)/Brat (1997).mkv"),
+	};
+	place(&linked, PlaceMode::Hardlink).unwrap();
+	assert_eq!(
+		fs::metadata(&source).unwrap().ino(),
+		fs::metadata(&linked.target).unwrap().ino()
+	);
+	// placing again is fine as the target is the same file
+	place(&linked, PlaceMode::Hardlink).unwrap();
+
+	let copied = Placement {
+		source: source.clone(),
+		target: linked.target.clone(),
+	};
+	fs::remove_file(&linked.target).unwrap();
+	fs::write(&copied.target, "other").unwrap();
+	assert!(matches!(
+		place(&copied, PlaceMode::Copy),
+		Err(MediaOrderError::TargetExistsError(_))
+	));
+
+	let moved = Placement {
+		source: source.clone(),
+		target: dir.join("library/Brat/Brat.mkv"),
+	};
+	place(&moved, PlaceMode::Move).unwrap();
+	assert!(!source.exists());
+	assert_eq!(fs::read_to_string(&moved.target).unwrap(), "video");
+
+	fs::remove_dir_all(dir).unwrap();
+}
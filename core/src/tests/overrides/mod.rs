@@ -0,0 +1,271 @@
+use std::{fs, path::PathBuf};
+
+use file_format::FileFormat;
+
+use crate::{
+	config::ScanConfig,
+	library::Library,
+	media::TitleInfo,
+	overrides::{fingerprint, Override, OverrideFile, OVERRIDE_FILE},
+	rules::ScanRules,
+	tests::{mkv, temp_dir},
+	video::Video,
+};
+
+#[test]
+fn parse_and_apply_override_file() {
+	let file = OverrideFile::parse(
+		r#"
+		season_offset = 1
+
+		[files."Brat.2.2000.mkv"]
+		imdb_id = "tt0238883"
+		title = "Brat 2"
+		year = 2000
+		"#,
+	)
+	.unwrap();
+	assert_eq!(file.folder.season_offset, Some(1));
+	assert!(file.file("Brat.mkv").is_none());
+
+	let forced = file.file("Brat.2.2000.mkv").unwrap().clone().or(file.folder.clone());
+	let mut video = Video::new(PathBuf::from("/films/Brat.2.2000.mkv"), FileFormat::MatroskaVideo);
+	video.name_original = "Brat".to_owned();
+	video.name_english = Some("Brother".to_owned());
+	video.season = Some(2);
+	forced.apply(&mut video);
+	assert_eq!(video.name_original, "Brat 2");
+	assert_eq!(video.name_english, None);
+	assert_eq!(video.year, Some(2000));
+	assert_eq!(video.imdb_id.as_deref(), Some("tt0238883"));
+	assert_eq!(video.season, Some(3));
+	assert_eq!(
+		forced.to_string(),
+		"imdb tt0238883, title \"Brat 2\", year 2000, season offset +1"
+	);
+
+	assert!(OverrideFile::parse("year = \"two thousand\"").is_err());
+}
+
+#[tokio::test]
+async fn overrides_follow_files() {
+	let root = temp_dir("overrides");
+	fs::create_dir_all(root.join("films")).unwrap();
+	let film = root.join("films/Brat.mkv");
+	fs::write(&film, "brat").unwrap();
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+
+	let forced = Override {
+		imdb_id: Some("tt0124315".to_owned()),
+		..Override::default()
+	};
+	library.set_override(&film, &forced).await.unwrap();
+	library
+		.set_override(&root.join("films"), &Override {
+			year: Some(1997),
+			..Override::default()
+		})
+		.await
+		.unwrap();
+	fs::write(
+		root.join("films").join(OVERRIDE_FILE),
+		"title = \"Brother\"\nyear = 1990\n[files.\"Brat.mkv\"]\nimdb_id = \"tt0000001\"\n",
+	)
+	.unwrap();
+
+	// own override first, then the folder file entry, then the folders with the database ones first
+	let resolved = library
+		.resolve_override(&film, None, &mut library.override_cache().await.unwrap())
+		.await
+		.unwrap();
+	assert_eq!(resolved, Override {
+		imdb_id: Some("tt0124315".to_owned()),
+		title: Some("Brother".to_owned()),
+		year: Some(1997),
+		..Override::default()
+	});
+
+	// matching doesn't replace the forced title
+	let title = TitleInfo {
+		imdb_id: "tt0000002".to_owned(),
+		..TitleInfo::default()
+	};
+	assert!(!library
+		.set_title(&film, &title, &mut library.override_cache().await.unwrap())
+		.await
+		.unwrap());
+
+	// renamed by the library
+	let renamed = root.join("films/Brother.mkv");
+	fs::rename(&film, &renamed).unwrap();
+	library.rename(&film, &renamed).await.unwrap();
+	assert_eq!(library.get_override(&renamed).await.unwrap(), Some(forced.clone()));
+	assert_eq!(library.get_override(&film).await.unwrap(), None);
+
+	// moved outside of the library, found by the fingerprint
+	let moved = root.join("Brat (1997).mkv");
+	fs::rename(&renamed, &moved).unwrap();
+	let resolved = library
+		.resolve_override(
+			&moved,
+			fingerprint(&moved).await.as_deref(),
+			&mut library.override_cache().await.unwrap(),
+		)
+		.await
+		.unwrap();
+	assert_eq!(resolved.imdb_id.as_deref(), Some("tt0124315"));
+	assert_eq!(library.get_override(&moved).await.unwrap(), Some(forced));
+	assert_eq!(library.overrides().await.unwrap().len(), 2);
+
+	assert!(library.remove_override(&moved).await.unwrap());
+	assert!(!library.remove_override(&moved).await.unwrap());
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn ignored_files_are_not_stored() {
+	let root = temp_dir("overrides-ignored");
+	for file in ["a/1.txt", "a/2.txt", "b/3.txt", "b/4.txt"] {
+		let path = root.join(file);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, "text file").unwrap();
+	}
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let scan = || library.scan(root.clone(), ScanConfig::default(), ScanRules::default());
+	scan().await.unwrap();
+	assert!(library.get(&root.join("a/1.txt")).await.unwrap().is_some());
+
+	let ignored = Override {
+		ignore: Some(true),
+		..Override::default()
+	};
+	library.set_override(&root.join("a"), &ignored).await.unwrap();
+	library
+		.set_override(&root.join("a/2.txt"), &Override {
+			ignore: Some(false),
+			..Override::default()
+		})
+		.await
+		.unwrap();
+	fs::write(root.join("b").join(OVERRIDE_FILE), "[files.\"3.txt\"]\nignore = true\n").unwrap();
+	scan().await.unwrap();
+
+	let mut paths: Vec<String> = library.files().await.unwrap().into_iter().map(|file| file.path).collect();
+	paths.sort();
+	let expected: Vec<String> = ["a/2.txt", "b/4.txt"]
+		.iter()
+		.map(|file| root.join(file).to_string_lossy().into_owned())
+		.collect();
+	assert_eq!(paths, expected);
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn file_entries_apply_to_subfolders() {
+	let root = temp_dir("overrides-subfolders");
+	fs::create_dir_all(root.join("films/Brat/Extras")).unwrap();
+	let film = root.join("films/Brat/Extras/Interview.mkv");
+	fs::write(&film, "interview").unwrap();
+	fs::write(
+		root.join("films").join(OVERRIDE_FILE),
+		"year = 2000\n[files.\"Brat\"]\nimdb_id = \"tt0124315\"\nyear = 1997\n",
+	)
+	.unwrap();
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+
+	let resolved = library
+		.resolve_override(&film, None, &mut library.override_cache().await.unwrap())
+		.await
+		.unwrap();
+	assert_eq!(resolved, Override {
+		imdb_id: Some("tt0124315".to_owned()),
+		year: Some(1997),
+		..Override::default()
+	});
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn overrides_are_loaded_once() {
+	let root = temp_dir("overrides-cache");
+	let film = root.join("Brat.mkv");
+	fs::write(&film, mkv()).unwrap();
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let forced = Override {
+		imdb_id: Some("tt0124315".to_owned()),
+		..Override::default()
+	};
+	library.set_override(&film, &forced).await.unwrap();
+
+	// resolved from the loaded overrides, the database isn't read again
+	let mut overrides = library.override_cache().await.unwrap();
+	assert!(library.remove_override(&film).await.unwrap());
+	assert_eq!(library.resolve_override(&film, None, &mut overrides).await.unwrap(), forced);
+
+	// the cache follows the moved file
+	library.set_override(&film, &forced).await.unwrap();
+	let fingerprint = fingerprint(&film).await;
+	library
+		.resolve_override(&film, fingerprint.as_deref(), &mut library.override_cache().await.unwrap())
+		.await
+		.unwrap();
+	let mut overrides = library.override_cache().await.unwrap();
+	let moved = root.join("Brother.mkv");
+	fs::rename(&film, &moved).unwrap();
+	let resolved = library
+		.resolve_override(&moved, fingerprint.as_deref(), &mut overrides)
+		.await
+		.unwrap();
+	assert_eq!(resolved, forced);
+	assert_eq!(library.resolve_override(&moved, None, &mut overrides).await.unwrap(), forced);
+	assert_eq!(library.get_override(&moved).await.unwrap(), Some(forced));
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn fingerprint_new_and_modified_videos() {
+	let root = temp_dir("overrides-fingerprints");
+	fs::write(root.join("Brat.mkv"), mkv()).unwrap();
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let scan = || library.scan(root.clone(), ScanConfig::default(), ScanRules::default());
+	let fingerprint_of = |name: &str| {
+		let path = root.join(name);
+		let library = &library;
+		async move { library.get(&path).await.unwrap().unwrap().fingerprint }
+	};
+
+	// nothing to follow without the file overrides
+	scan().await.unwrap();
+	assert_eq!(fingerprint_of("Brat.mkv").await, None);
+
+	let mut heat = mkv();
+	heat.push(1);
+	fs::write(root.join("Heat.mkv"), heat).unwrap();
+	library
+		.set_override(&root.join("Heat.mkv"), &Override {
+			year: Some(1995),
+			..Override::default()
+		})
+		.await
+		.unwrap();
+	let mut amelie = mkv();
+	amelie.push(2);
+	fs::write(root.join("Amelie.mkv"), &amelie).unwrap();
+	scan().await.unwrap();
+	// the unchanged file keeps its record, the new ones are fingerprinted
+	assert_eq!(fingerprint_of("Brat.mkv").await, None);
+	assert!(fingerprint_of("Amelie.mkv").await.is_some());
+	assert!(fingerprint_of("Heat.mkv").await.is_some());
+
+	let mut brat = mkv();
+	brat.extend(b"modified");
+	fs::write(root.join("Brat.mkv"), brat).unwrap();
+	scan().await.unwrap();
+	assert_eq!(fingerprint_of("Brat.mkv").await, fingerprint(&root.join("Brat.mkv")).await);
+
+	fs::remove_dir_all(root).unwrap();
+}
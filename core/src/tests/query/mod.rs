@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use sea_orm::EntityTrait;
+
+use crate::{
+	library::{entities::files, query::Query, Library, KIND_FILE, KIND_VIDEO},
+	media::TitleInfo,
+	tests,
+};
+
+fn record(path: &str, name: &str, year: i32, vres: &str, venc: &str, audio: &str) -> files::Model {
+	files::Model {
+		size: 700 << 20,
+		name: Some(name.to_owned()),
+		year: Some(year),
+		venc: Some(venc.to_owned()),
+		vres: Some(vres.to_owned()).filter(|vres| !vres.is_empty()),
+		duration: Some(5400.0),
+		audio_langs: Some(audio.to_owned()).filter(|audio| !audio.is_empty()),
+		..tests::record(path, KIND_VIDEO)
+	}
+}
+
+async fn library() -> Library {
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let big = files::Model {
+		size: 8 << 30,
+		height: Some(1080),
+		duration: Some(7620.0),
+		..record("/films/Brat 2.mkv", "Brat 2", 2000, "", "h264", "rus,eng")
+	};
+	let text = tests::record("/films/readme.txt", KIND_FILE);
+
+	files::Entity::insert_many(
+		[
+			record("/films/Brat.avi", "Brat", 1997, "720p", "XviD", "rus"),
+			record("/films/Heat.avi", "Heat", 1995, "720p", "DivX", ""),
+			record("/films/Amelie.mkv", "Amelie", 2001, "720p", "XviD", "fra,eng"),
+			big,
+			text,
+		]
+		.map(files::ActiveModel::from),
+	)
+	.exec(library.db())
+	.await
+	.unwrap();
+	library
+}
+
+async fn search(library: &Library, query: &str) -> Vec<String> {
+	let query: Query = query.parse().unwrap();
+	let query = query.with_sort("path").unwrap();
+	library
+		.search(&query)
+		.await
+		.unwrap()
+		.into_iter()
+		.map(|record| record.name.unwrap_or_default())
+		.collect()
+}
+
+#[tokio::test]
+async fn search_by_fields() {
+	let library = library().await;
+
+	assert_eq!(search(&library, "vres:720p venc:xvid year<2005").await, ["Amelie", "Brat"]);
+	assert_eq!(search(&library, "kind:video -audio:eng").await, ["Brat", "Heat"]);
+	assert_eq!(search(&library, "audio:none").await, ["Heat", ""]);
+	assert_eq!(search(&library, "year:1995..1997").await, ["Brat", "Heat"]);
+	assert_eq!(search(&library, "year:2000.. venc:xvid,h264").await, ["Amelie", "Brat 2"]);
+	assert_eq!(search(&library, "year!=1997 kind:video").await, ["Amelie", "Brat 2", "Heat"]);
+	assert_eq!(search(&library, "brat").await, ["Brat 2", "Brat"]);
+	assert_eq!(search(&library, r#"name:"brat 2""#).await, ["Brat 2"]);
+	assert_eq!(search(&library, "vres:1080p").await, ["Brat 2"]);
+	assert_eq!(search(&library, "res>=1080").await, ["Brat 2"]);
+	assert_eq!(search(&library, "size>1G").await, ["Brat 2"]);
+	assert_eq!(search(&library, "duration>=2h").await, ["Brat 2"]);
+	assert_eq!(search(&library, "duration<100").await.len(), 3);
+	assert_eq!(search(&library, "path:.mkv").await, ["Amelie", "Brat 2"]);
+}
+
+#[tokio::test]
+async fn search_by_title_and_sort() {
+	let library = library().await;
+	let title = TitleInfo {
+		imdb_id: "tt0124315".to_owned(),
+		title: "Brother".to_owned(),
+		genres: vec!["Crime".to_owned(), "Drama".to_owned()],
+		rating: Some(7.8),
+		..Default::default()
+	};
+	assert!(library
+		.set_title(
+			Path::new("/films/Brat.avi"),
+			&title,
+			&mut library.override_cache().await.unwrap()
+		)
+		.await
+		.unwrap());
+	assert_eq!(search(&library, "genre:drama rating>=7").await, ["Brat"]);
+	assert_eq!(search(&library, "imdb:tt0124315").await, ["Brat"]);
+	assert_eq!(search(&library, "kind:video imdb:none").await.len(), 3);
+
+	let query = "kind:video"
+		.parse::<Query>()
+		.unwrap()
+		.with_sort("-year,name")
+		.unwrap()
+		.with_limit(Some(2));
+	let names: Vec<_> = library
+		.search(&query)
+		.await
+		.unwrap()
+		.into_iter()
+		.filter_map(|record| record.name)
+		.collect();
+	assert_eq!(names, ["Amelie", "Brat 2"]);
+}
+
+#[test]
+fn invalid_queries() {
+	assert!("yaer>2000".parse::<Query>().is_err());
+	assert!("year>old".parse::<Query>().is_err());
+	assert!("venc>xvid".parse::<Query>().is_err());
+	assert!("year:..".parse::<Query>().is_err());
+	assert!(Query::default().with_sort("-colour").is_err());
+}
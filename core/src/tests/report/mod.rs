@@ -0,0 +1,159 @@
+use std::fs;
+
+use crate::{
+	config::ScanConfig,
+	library::{entities::files, Library, KIND_ERROR, KIND_FILE, KIND_VIDEO},
+	report::Report,
+	rules::ScanRules,
+	tests::{self, temp_dir},
+};
+
+fn record(path: &str, kind: &str, size: i64) -> files::Model {
+	files::Model {
+		size,
+		..tests::record(path, kind)
+	}
+}
+
+fn video(path: &str, name: &str, year: i32, vres: &str, size: i64) -> files::Model {
+	files::Model {
+		name: Some(name.to_owned()),
+		year: Some(year),
+		vres: Some(vres.to_owned()),
+		venc: Some("xvid".to_owned()),
+		..record(path, KIND_VIDEO, size)
+	}
+}
+
+#[test]
+fn report_statistics_and_health() {
+	let truncated = files::Model {
+		warnings: Some("no-year,truncated".to_owned()),
+		year: None,
+		..video("/films/Heat.avi", "Heat", 0, "720p", 100)
+	};
+	let identified = files::Model {
+		imdb_id: Some("tt0124315".to_owned()),
+		..video("/films/Brat.avi", "Brat", 1997, "720p", 700)
+	};
+	let records = [
+		identified,
+		video("/films/old/Brat (1997).mkv", "brat", 1997, "1080p", 4000),
+		video("/films/Amelie.avi", "Amelie", 2001, "720p", 0),
+		truncated,
+		files::Model {
+			error: Some("Can't read video metadata: e".to_owned()),
+			..video("/films/Broken.avi", "Broken", 1990, "720p", 10)
+		},
+		files::Model {
+			role: Some("sample".to_owned()),
+			..video("/films/Brat-sample.avi", "Brat", 1997, "720p", 5)
+		},
+		record("/films/readme.txt", KIND_FILE, 20),
+		files::Model {
+			error: Some("denied".to_owned()),
+			..record("/films/locked", KIND_ERROR, 0)
+		},
+	];
+	let report = Report::new(&records);
+
+	assert_eq!((report.files, report.videos, report.size), (8, 6, 4835));
+	let resolution: Vec<_> = report
+		.by_resolution
+		.iter()
+		.map(|bucket| (bucket.key.as_str(), bucket.files, bucket.size))
+		.collect();
+	assert_eq!(resolution, [("1080p", 1, 4000), ("720p", 5, 815)]);
+	let decades: Vec<_> = report
+		.by_decade
+		.iter()
+		.map(|bucket| (bucket.key.as_str(), bucket.files))
+		.collect();
+	assert_eq!(decades, [("1990s", 4), ("2000s", 1), ("unknown", 1)]);
+
+	assert_eq!(report.empty, ["/films/Amelie.avi"]);
+	assert_eq!(report.truncated, ["/films/Heat.avi"]);
+	assert_eq!(report.warnings[0].detail, "no-year");
+	assert_eq!(report.probe_failures[0].path, "/films/Broken.avi");
+	assert_eq!(report.scan_errors[0].detail, "denied");
+	assert!(!report.unidentified.contains(&"/films/Brat.avi".to_owned()));
+	assert!(!report.unidentified.contains(&"/films/Brat-sample.avi".to_owned()));
+
+	// unidentified copy matches by the name and year
+	assert_eq!(report.duplicates.len(), 1);
+	assert_eq!(report.duplicates[0].paths, ["/films/old/Brat (1997).mkv", "/films/Brat.avi"]);
+	assert_eq!(report.wasted, 700);
+
+	let text = report.text();
+	assert!(text.contains("Truncated files (1):\n\t/films/Heat.avi\n"));
+	let html = report.html();
+	assert!(html.starts_with("<!DOCTYPE html>") && html.contains("<style>") && !html.contains("<script"));
+	let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+	assert_eq!(json["by_codec"][0]["key"], "xvid");
+}
+
+fn part(path: &str, part: i32, size: i64) -> files::Model {
+	files::Model {
+		part: Some(part),
+		..video(path, "Brat", 1997, "720p", size)
+	}
+}
+
+#[test]
+fn multipart_release_is_one_copy() {
+	let records = [
+		part("/films/Brat/Brat.1997.CD1.avi", 1, 700),
+		part("/films/Brat/Brat.1997.CD2.avi", 2, 700),
+		files::Model {
+			imdb_id: Some("tt0124315".to_owned()),
+			..video("/films/old/Brat.1997.mkv", "Brat", 1997, "1080p", 4000)
+		},
+	];
+	let report = Report::new(&records);
+	assert_eq!(report.duplicates.len(), 1);
+	assert_eq!(report.duplicates[0].title, "tt0124315");
+	assert_eq!(report.duplicates[0].paths, [
+		"/films/old/Brat.1997.mkv",
+		"/films/Brat/Brat.1997.CD1.avi",
+		"/films/Brat/Brat.1997.CD2.avi"
+	]);
+	assert_eq!(report.wasted, 1400);
+}
+
+#[test]
+fn multipart_copies_are_reported_once() {
+	let records = [
+		part("/films/a/Brat.1997.CD1.avi", 1, 700),
+		part("/films/a/Brat.1997.CD2.avi", 2, 700),
+		part("/films/b/Brat.1997.CD1.avi", 1, 500),
+		part("/films/b/Brat.1997.CD2.avi", 2, 600),
+	];
+	let report = Report::new(&records);
+	assert_eq!(report.duplicates.len(), 1);
+	assert_eq!(report.duplicates[0].title, "brat (1997)");
+	assert_eq!(report.duplicates[0].paths[..2], [
+		"/films/a/Brat.1997.CD1.avi",
+		"/films/a/Brat.1997.CD2.avi"
+	]);
+	// the waste of the smaller copy as a whole
+	assert_eq!(report.wasted, 1100);
+}
+
+#[tokio::test]
+async fn probe_failure_keeps_the_video() {
+	let root = temp_dir("report");
+	fs::write(root.join("Brat (1997).avi"), b"RIFF\x10\0\0\0AVI LIST\x04\0\0\0hdrl").unwrap();
+
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let (_, videos) = library
+		.scan(root.clone(), ScanConfig::default(), ScanRules::default())
+		.await
+		.unwrap();
+	assert_eq!(videos.len(), 1);
+	let record = library.get(&root.join("Brat (1997).avi")).await.unwrap().unwrap();
+	assert_eq!(record.kind, KIND_VIDEO);
+	assert_eq!(record.name.as_deref(), Some("Brat"));
+	assert!(record.error.unwrap().starts_with("Can't read video metadata"));
+
+	fs::remove_dir_all(root).unwrap();
+}
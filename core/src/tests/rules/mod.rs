@@ -0,0 +1,65 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use tokio::sync::mpsc;
+
+use crate::{config::ScanConfig, rules::ScanRules, scanner::Scanner, tests::temp_dir};
+
+async fn scan(root: &PathBuf, rules: ScanRules) -> Vec<PathBuf> {
+	let config = ScanConfig {
+		progress_interval: Duration::from_secs(60),
+		..ScanConfig::default()
+	};
+	let (tx, mut rx) = mpsc::channel(config.queue_size);
+	let scanner = tokio::spawn(Scanner::new(config).with_rules(rules).run(root.clone(), tx));
+
+	let mut files = vec![];
+	while let Some((path, _)) = rx.recv().await {
+		files.push(path.strip_prefix(root).unwrap().to_path_buf());
+	}
+	scanner.await.unwrap().unwrap();
+	files.sort();
+	files
+}
+
+#[tokio::test]
+async fn scan_with_rules_and_ignore_files() {
+	let root = temp_dir("rules");
+	for (file, content) in [
+		("film.mkv", "film content"),
+		("small.mkv", "1"),
+		(".hidden.mkv", "hidden content"),
+		("@eaDir/film.mkv", "thumbnails"),
+		("Brat/Sample/sample.mkv", "sample content"),
+		("Brat/trash/film.mkv", "film content"),
+		("Brat/film.nfo", "not a video"),
+		("Brat/.mediaorderignore", "*.txt\n!keep.txt\n"),
+		("Brat/a.txt", "ignored text"),
+		("Brat/keep.txt", "kept text"),
+		("Brat/extras/b.txt", "ignored text"),
+	] {
+		let path = root.join(file);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, content).unwrap();
+	}
+	std::os::unix::fs::symlink(&root, root.join("Brat/loop")).unwrap();
+
+	let mut rules = ScanRules::default().exclude_regex(&["/trash/"]).unwrap();
+	rules.min_size = 5;
+	// samples are kept for the role classification
+	assert_eq!(scan(&root, rules.clone()).await, vec![
+		PathBuf::from("Brat/Sample/sample.mkv"),
+		PathBuf::from("Brat/film.nfo"),
+		PathBuf::from("Brat/keep.txt"),
+		PathBuf::from("film.mkv"),
+	]);
+
+	// symlink to the root is followed only once
+	rules.follow_symlinks = true;
+	rules = rules.include(&["*.mkv"]).unwrap();
+	assert_eq!(scan(&root, rules).await, vec![
+		PathBuf::from("Brat/Sample/sample.mkv"),
+		PathBuf::from("film.mkv"),
+	]);
+
+	fs::remove_dir_all(root).unwrap();
+}
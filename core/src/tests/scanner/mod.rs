@@ -0,0 +1,63 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use tokio::sync::mpsc;
+
+use crate::{
+	config::ScanConfig,
+	scanner::Scanner,
+	tests::{mkv, temp_dir},
+	types::FSEntry,
+};
+
+#[tokio::test]
+async fn scan_tree_with_bounded_workers() {
+	let root = temp_dir("scanner");
+	for folder in ["a", "a/b", "c"] {
+		fs::create_dir_all(root.join(folder)).unwrap();
+		for file in ["1.txt", "2.txt"] {
+			fs::write(root.join(folder).join(file), "text file").unwrap();
+		}
+	}
+
+	let config = ScanConfig {
+		workers: 2,
+		device_workers: 1,
+		queue_size: 1,
+		progress_interval: Duration::from_secs(60),
+	};
+	// channel of one entry makes the scanner wait for the consumer
+	let (tx, mut rx) = mpsc::channel(config.queue_size);
+	let scanner = tokio::spawn(Scanner::new(config).run(root.clone(), tx));
+
+	let mut files = vec![];
+	while let Some((path, entry)) = rx.recv().await {
+		assert!(matches!(entry, Ok(FSEntry::File(_))));
+		files.push(path.strip_prefix(&root).unwrap().to_path_buf());
+	}
+	files.sort();
+
+	let progress = scanner.await.unwrap().unwrap();
+	assert_eq!(files.len(), 6);
+	assert_eq!(files[0], PathBuf::from("a/1.txt"));
+	assert_eq!(progress.files, 6);
+	assert_eq!(progress.folders, 4);
+	assert_eq!(progress.errors, 0);
+	assert_eq!(progress.bytes, 6 * 9);
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn collect_videos_without_storing() {
+	let root = temp_dir("scanner-videos");
+	// the probe fails but the name is parsed
+	fs::write(root.join("Brat.1997.mkv"), mkv()).unwrap();
+	fs::write(root.join("readme.txt"), "text file").unwrap();
+
+	let (progress, videos) = Scanner::new(ScanConfig::default()).videos(root.clone()).await.unwrap();
+	assert_eq!(progress.files, 2);
+	assert_eq!(videos.len(), 1);
+	assert_eq!(videos[0].name_original, "Brat");
+
+	fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,103 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use media_order_imdb::provider::{fixture::FixtureProvider, EpisodeMetadata};
+
+use crate::{
+	library::{entities::files, KIND_VIDEO},
+	series::{compare_official, shows, text, EpisodeMap},
+	tests::record,
+};
+
+fn episode(path: &str, name: &str, season: i32, episode: i32, last_episode: i32) -> files::Model {
+	files::Model {
+		name: Some(name.to_owned()),
+		season: Some(season),
+		episode: Some(episode),
+		last_episode: Some(last_episode),
+		..record(path, KIND_VIDEO)
+	}
+}
+
+fn records() -> Vec<files::Model> {
+	vec![
+		episode("/tv/Office/S01E01.avi", "The Office", 1, 1, 1),
+		episode("/tv/Office/S01E02.avi", "The Office", 1, 2, 3),
+		episode("/tv/Office/S01E05.avi", "The Office", 1, 5, 5),
+		episode("/tv/Office/S02E01.avi", "The Office", 2, 1, 1),
+		episode("/tv/Office/S02E01.720p.avi", "the office", 2, 1, 1),
+		episode("/tv/Office/S00E01.avi", "The Office", 0, 1, 1),
+		episode("/tv/Sherlock/S01E01.mkv", "Sherlock", 1, 1, 1),
+		files::Model {
+			season: None,
+			episode: None,
+			..episode("/films/Brat.avi", "Brat", 0, 0, 0)
+		},
+	]
+}
+
+#[test]
+fn gaps_and_duplicates_by_numbering() {
+	let shows = shows(&records());
+	assert_eq!(shows.len(), 2);
+	let office = &shows[1];
+	assert_eq!(office.name, "The Office");
+	assert_eq!(office.seasons.iter().map(|season| season.number).collect::<Vec<_>>(), [0, 1, 2]);
+
+	let (specials, first, second) = (&office.seasons[0], &office.seasons[1], &office.seasons[2]);
+	assert_eq!(specials.extra, [1]);
+	assert_eq!(first.episodes.keys().copied().collect::<Vec<_>>(), [1, 2, 3, 5]);
+	assert_eq!(first.missing, [4]);
+	assert_eq!(second.duplicates, [1]);
+	assert!(office.has_gaps());
+	assert!(!shows[0].has_gaps());
+
+	let text = text(&shows);
+	assert!(text.contains("\tSeason 1: 4 episodes; missing S01E04\n"));
+	assert!(text.contains("\tSpecials: 1 episodes; extra S00E01\n"));
+}
+
+#[test]
+fn compare_with_official_episodes() {
+	let mut shows = shows(&records());
+	let office = &mut shows[1];
+	let official: EpisodeMap = BTreeMap::from([
+		(1, BTreeSet::from([1, 2, 3, 4, 5, 6])),
+		(2, BTreeSet::from([1, 2])),
+		(3, BTreeSet::from([1])),
+	]);
+	office.compare(&official);
+
+	assert!(office.official);
+	let missing: Vec<_> = office
+		.seasons
+		.iter()
+		.map(|season| (season.number, season.missing.clone()))
+		.collect();
+	assert_eq!(missing, [(0, vec![]), (1, vec![4, 6]), (2, vec![2]), (3, vec![1])]);
+	// specials not in the official list
+	assert_eq!(office.seasons[0].extra, [1]);
+}
+
+#[tokio::test]
+async fn compare_identified_shows() {
+	let mut records = records();
+	records[1].imdb_id = Some("tt0386676".to_owned());
+	let mut provider = FixtureProvider::new("fixture");
+	for (season, episode) in [(1, 1), (1, 2), (1, 3), (1, 4), (1, 5), (2, 1)] {
+		provider = provider.with_episode("tt0386676", EpisodeMetadata {
+			tconst: format!("tt10{season}{episode}"),
+			season,
+			episode,
+			title: None,
+		});
+	}
+	let mut shows = shows(&records);
+	compare_official(&mut shows, &provider).await.unwrap();
+
+	let (sherlock, office) = (&shows[0], &shows[1]);
+	assert_eq!(office.imdb_id.as_deref(), Some("tt0386676"));
+	assert!(office.official);
+	assert_eq!(office.seasons[1].missing, [4]);
+	// unknown shows keep the numbering of the files
+	assert!(!sherlock.official);
+}
@@ -8,7 +8,7 @@ use file_format::FileFormat;
 use serde::{Deserialize, Serialize};
 use simple_logger::SimpleLogger;
 
-use crate::video::Video;
+use crate::video::{Video, VideoRole};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 struct VideoFileName {
@@ -43,3 +43,97 @@ fn check_parse_file_name() {
 		assert_eq!(video.year, test_struct.year);
 	}
 }
+
+#[test]
+fn check_parse_part_markers() {
+	let cases = [
+		("Alien Vs Predator (www.kinokopilka.ru)(CD 2).avi", Some(2), "Alien Vs Predator"),
+		("Terminator.1984.CD1.XviD.avi", Some(1), "Terminator"),
+		("61. V Vuz ne duem. part 2.avi", Some(2), "61 V Vuz ne duem"),
+		("Brat.1997.DVDRip.avi.001", Some(1), "Brat"),
+		(
+			"Harry.Potter.and.the.Deathly.Hallows.Part.1.2010.mkv",
+			None,
+			"Harry Potter and the Deathly Hallows Part 1",
+		),
+		("Люди X.2.avi", None, "Люди X 2"),
+		("Film.2005.DVD5.avi", None, "Film"),
+		("Film.DVD9.avi", None, "Film DVD9"),
+		("Film DVD 2.avi", Some(2), "Film"),
+		("part 2.avi", None, "part 2"),
+	];
+
+	for (file_name, part, name) in cases {
+		let mut video = Video::new(PathBuf::from(file_name), FileFormat::MatroskaVideo);
+
+		video.discover().unwrap();
+		assert_eq!(video.part, part, "{file_name}");
+		assert_eq!(video.name_original, name, "{file_name}");
+	}
+}
+
+#[test]
+fn check_parse_episode_markers() {
+	let cases = [
+		("Breaking.Bad.S01E02.720p.BluRay.x264.mkv", "Breaking Bad", Some((1, 2, 2))),
+		("The Office - S02E05E06 - Halloween.avi", "The Office", Some((2, 5, 6))),
+		("Sherlock.s03e01-e03.mkv", "Sherlock", Some((3, 1, 3))),
+		("Brigada.1x07.avi", "Brigada", Some((1, 7, 7))),
+		("Doctor.Who.2005.S00E01.mkv", "Doctor Who", Some((0, 1, 1))),
+		("Terminator.1984.CD1.XviD.avi", "Terminator", None),
+	];
+
+	for (file_name, name, episode) in cases {
+		let mut video = Video::new(PathBuf::from(file_name), FileFormat::MatroskaVideo);
+
+		video.discover().unwrap();
+		assert_eq!(video.name_original, name, "{file_name}");
+		let parsed = video
+			.episode
+			.map(|episode| (video.season.unwrap(), episode, video.last_episode.unwrap()));
+		assert_eq!(parsed, episode, "{file_name}");
+	}
+}
+
+#[test]
+fn check_video_role() {
+	let cases = [
+		("/films/Brat.1997/Brat.1997.DVDRip.avi", VideoRole::Main),
+		("/films/Brat.1997/Brat.1997.sample.avi", VideoRole::Sample),
+		("/films/Brat.1997/Sample/brat.avi", VideoRole::Sample),
+		("/films/Brat.1997/Brat-trailer.mp4", VideoRole::Trailer),
+		("/films/Brat.1997/Brat.1997.Trailer.mp4", VideoRole::Trailer),
+		("/films/Brat.1997/Trailers/Teaser.mp4", VideoRole::Trailer),
+		("/films/Brat.1997/Deleted Scenes/Scene 1.mkv", VideoRole::DeletedScene),
+		("/films/Brat.1997/Brat.Behind.The.Scenes.mkv", VideoRole::BehindTheScenes),
+		("/films/Brat.1997/Extras/Interview.mkv", VideoRole::Extra),
+		("/films/Brat.1997/Brat-featurette.mkv", VideoRole::Extra),
+		("/films/Brat.1997/Brat-interview.mkv", VideoRole::Extra),
+		("/films/The.Interview.2014.mkv", VideoRole::Main),
+		("/films/Bonus.Family.2015.mkv", VideoRole::Main),
+		("/films/Deleted.2019.mkv", VideoRole::Main),
+	];
+
+	for (path, role) in cases {
+		let mut video = Video::new(PathBuf::from(path), FileFormat::MatroskaVideo);
+
+		video.discover().unwrap();
+		assert_eq!(video.role, role, "{path}");
+	}
+
+	// the shows named by the role markers
+	let cases = [
+		("/series/Trailer Park Boys/Trailer Park Boys S01E01.mkv", "Trailer Park Boys"),
+		("/series/Extras/Extras S01E01.mkv", "Extras"),
+	];
+	for (path, name) in cases {
+		let mut video = Video::new(PathBuf::from(path), FileFormat::MatroskaVideo);
+
+		video.discover().unwrap();
+		assert_eq!(video.role, VideoRole::Main, "{path}");
+		assert_eq!(
+			(video.name_original.as_str(), video.season, video.episode),
+			(name, Some(1), Some(1))
+		);
+	}
+}
@@ -0,0 +1,97 @@
+use std::{fs, os::unix::fs::MetadataExt, path::Path};
+
+use file_format::FileFormat;
+
+use crate::{
+	media::{group_parts, MediaItem},
+	tests::temp_dir,
+	video::Video,
+	view::{OrganisedView, ViewStats},
+};
+
+fn items(paths: &[&Path]) -> Vec<MediaItem> {
+	group_parts(paths.iter().map(|path| {
+		let mut video = Video::new(path.to_path_buf(), FileFormat::MatroskaVideo);
+		video.discover().unwrap();
+		video
+	}))
+}
+
+#[test]
+fn sync_and_prune_view() {
+	let dir = temp_dir("view");
+	let downloads = dir.join("downloads");
+	fs::create_dir_all(&downloads).unwrap();
+	let brat = downloads.join("Brat.1997.mkv");
+	let terminator = downloads.join("Terminator.1984.mkv");
+	fs::write(&brat, "brat").unwrap();
+	fs::write(&terminator, "terminator").unwrap();
+
+	let root = dir.join("view");
+	let foreign = root.join("Alien (1979)/Alien (1979).mkv");
+	fs::create_dir_all(foreign.parent().unwrap()).unwrap();
+	fs::write(&foreign, "not made by the view").unwrap();
+	let alien = downloads.join("Alien.1979.mkv");
+	fs::write(&alien, "alien").unwrap();
+
+	let view = OrganisedView::new(root.clone());
+	let stats = view.sync(&items(&[&brat, &terminator, &alien])).unwrap();
+	assert_eq!(stats, ViewStats {
+		linked: 2,
+		kept: 0,
+		pruned: 0,
+		conflicts: 1
+	});
+	let brat_link = root.join("Brat (1997)/Brat (1997).mkv");
+	assert_eq!(fs::metadata(&brat_link).unwrap().ino(), fs::metadata(&brat).unwrap().ino());
+	assert_eq!(fs::read_to_string(&foreign).unwrap(), "not made by the view");
+
+	// source disappeared
+	fs::remove_file(&terminator).unwrap();
+	let stats = view.sync(&items(&[&brat])).unwrap();
+	assert_eq!((stats.kept, stats.pruned), (1, 1));
+	assert!(!root.join("Terminator (1984)").exists());
+	assert!(foreign.exists());
+
+	// source replaced by the new file
+	fs::remove_file(&brat).unwrap();
+	fs::write(&brat, "brat remux").unwrap();
+	let stats = view.sync(&items(&[&brat])).unwrap();
+	assert_eq!(stats.linked, 1);
+	assert_eq!(fs::read_to_string(&brat_link).unwrap(), "brat remux");
+
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn sync_scanned_subfolder() {
+	let dir = temp_dir("view-subfolder");
+	let films = dir.join("films");
+	let series = dir.join("series");
+	fs::create_dir_all(&films).unwrap();
+	fs::create_dir_all(&series).unwrap();
+	let brat = films.join("Brat.1997.mkv");
+	let terminator = films.join("Terminator.1984.mkv");
+	let brigada = series.join("Brigada.2002.mkv");
+	for path in [&brat, &terminator, &brigada] {
+		fs::write(path, "video").unwrap();
+	}
+
+	let root = dir.join("view");
+	let view = OrganisedView::new(root.clone());
+	assert_eq!(view.sync(&items(&[&brat, &terminator, &brigada])).unwrap().linked, 3);
+
+	// links to the sources outside of the scanned folder survive
+	fs::remove_file(&terminator).unwrap();
+	let stats = view.sync_under(&items(&[&brat]), &films).unwrap();
+	assert_eq!((stats.kept, stats.pruned), (1, 1));
+	assert!(root.join("Brigada (2002)/Brigada (2002).mkv").exists());
+	assert!(!root.join("Terminator (1984)").exists());
+
+	// and stay managed by the full sync
+	let stats = view.sync(&items(&[&brat])).unwrap();
+	assert_eq!((stats.kept, stats.pruned), (1, 1));
+	assert!(!root.join("Brigada (2002)").exists());
+
+	fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,132 @@
+use std::{fs, time::Duration};
+
+use tokio::sync::oneshot;
+
+use crate::{
+	config::{ScanConfig, WatchConfig},
+	library::{Library, KIND_DISC},
+	rules::ScanRules,
+	tests::{mkv, temp_dir},
+	view::OrganisedView,
+	watch::LibraryWatcher,
+};
+
+async fn wait_for<F: std::future::Future<Output = bool>>(check: impl Fn() -> F) -> bool {
+	for _ in 0..100 {
+		if check().await {
+			return true;
+		}
+		tokio::time::sleep(Duration::from_millis(50)).await;
+	}
+	false
+}
+
+#[tokio::test]
+async fn watch_created_and_deleted_files() {
+	let root = temp_dir("watch");
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let file = root.join("movie.txt");
+
+	let (stop_tx, stop_rx) = oneshot::channel::<()>();
+	let watcher = LibraryWatcher::new(&library, root.clone(), ScanConfig::default(), ScanRules::default())
+		.with_config(WatchConfig {
+			settle: Duration::from_millis(100),
+		});
+	let watch = watcher.run(async {
+		let _ = stop_rx.await;
+	});
+
+	let check = async {
+		// give the watcher time to start
+		tokio::time::sleep(Duration::from_millis(200)).await;
+		fs::write(&file, "text file").unwrap();
+		assert!(wait_for(|| async { library.get(&file).await.unwrap().is_some() }).await);
+
+		fs::remove_file(&file).unwrap();
+		assert!(wait_for(|| async { library.get(&file).await.unwrap().is_none() }).await);
+		stop_tx.send(()).unwrap();
+	};
+
+	let (result, _) = tokio::join!(watch, check);
+	result.unwrap();
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn watch_disc_folders() {
+	let root = temp_dir("watch-disc");
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let disc = root.join("Brat.1997.BDRip");
+	let stream = disc.join("BDMV/STREAM/00001.m2ts");
+
+	let (stop_tx, stop_rx) = oneshot::channel::<()>();
+	let watcher = LibraryWatcher::new(&library, root.clone(), ScanConfig::default(), ScanRules::default())
+		.with_config(WatchConfig {
+			settle: Duration::from_millis(100),
+		});
+	let watch = watcher.run(async {
+		let _ = stop_rx.await;
+	});
+
+	let check = async {
+		tokio::time::sleep(Duration::from_millis(200)).await;
+		fs::create_dir_all(stream.parent().unwrap()).unwrap();
+		fs::write(&stream, [0; 16]).unwrap();
+		// the stream is stored as the disc, not as a loose file
+		assert!(
+			wait_for(|| async {
+				library.get(&disc).await.unwrap().is_some_and(|record| record.kind == KIND_DISC)
+			})
+			.await
+		);
+		assert!(library.get(&stream).await.unwrap().is_none());
+		stop_tx.send(()).unwrap();
+	};
+
+	let (result, _) = tokio::join!(watch, check);
+	result.unwrap();
+
+	fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn watch_syncs_view() {
+	let dir = temp_dir("watch-view");
+	let root = dir.join("library");
+	fs::create_dir_all(&root).unwrap();
+	let library = Library::connect("sqlite::memory:").await.unwrap();
+	let film = root.join("Brat.1997.mkv");
+	let link = dir.join("view/Brat (1997)/Brat (1997).mkv");
+
+	let (stop_tx, stop_rx) = oneshot::channel::<()>();
+	let watcher = LibraryWatcher::new(&library, root.clone(), ScanConfig::default(), ScanRules::default())
+		.with_config(WatchConfig {
+			settle: Duration::from_millis(100),
+		})
+		.with_view(OrganisedView::new(dir.join("view")));
+	let watch = watcher.run(async {
+		let _ = stop_rx.await;
+	});
+
+	let check = async {
+		tokio::time::sleep(Duration::from_millis(200)).await;
+		fs::write(&film, mkv()).unwrap();
+		assert!(wait_for(|| async { link.exists() }).await);
+
+		// the renamed file is relinked, the old link is pruned
+		let renamed = root.join("Brother.1997.mkv");
+		fs::rename(&film, &renamed).unwrap();
+		let relinked = dir.join("view/Brother (1997)/Brother (1997).mkv");
+		assert!(wait_for(|| async { relinked.exists() && !link.exists() }).await);
+
+		fs::remove_file(&renamed).unwrap();
+		assert!(wait_for(|| async { !relinked.exists() }).await);
+		stop_tx.send(()).unwrap();
+	};
+
+	let (result, _) = tokio::join!(watch, check);
+	result.unwrap();
+
+	fs::remove_dir_all(dir).unwrap();
+}
@@ -2,9 +2,12 @@ use std::{fs, path::PathBuf};
 
 use async_trait::async_trait;
 use file_format::FileFormat;
+use log::warn;
 
 use crate::{
+	disc::Disc,
 	errors::{MediaOrderError, Result},
+	nfo::NfoHint,
 	video::Video,
 };
 
@@ -18,6 +21,7 @@ pub trait FromPath: Sized {
 #[derive(Debug)]
 pub enum FSEntry {
 	Video(Video),
+	DiscImage(Disc),
 	File((PathBuf, FileFormat)),
 	Folder(Vec<PathBuf>),
 	Unknown(PathBuf),
@@ -28,14 +32,24 @@ impl FromPath for FSEntry {
 	type Error = MediaOrderError;
 
 	async fn from_path(path: PathBuf) -> Result<FSEntry> {
+		tokio::task::spawn_blocking(move || FSEntry::read(path)).await?
+	}
+}
+
+impl FSEntry {
+	/// Reads the entry with blocking file system and ffmpeg calls, use [`FromPath::from_path`] in async code.
+	pub fn read(path: PathBuf) -> Result<FSEntry> {
 		match (path.is_dir(), FileFormat::from_file(&path)) {
-			(true, _) => Ok(fs::read_dir(&path)
-				.map(|entries| {
-					FSEntry::Folder(
-						entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect(),
-					)
-				})
-				.unwrap_or_else(|_| FSEntry::Unknown(path))),
+			(true, _) => match Disc::from_folder(&path)? {
+				Some(disc) => Ok(FSEntry::DiscImage(disc)),
+				None => Ok(fs::read_dir(&path)
+					.map(|entries| {
+						FSEntry::Folder(
+							entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect(),
+						)
+					})
+					.unwrap_or_else(|_| FSEntry::Unknown(path))),
+			},
 			(false, Ok(format)) => Ok(FSEntry::try_from((path, format))?),
 			_ => Ok(FSEntry::Unknown(path)),
 		}
@@ -47,10 +61,19 @@ impl TryFrom<(PathBuf, FileFormat)> for FSEntry {
 
 	fn try_from((path, format): (PathBuf, FileFormat)) -> Result<Self> {
 		match format.kind() {
+			file_format::Kind::Disk if format == FileFormat::Iso9660 => {
+				Ok(Self::DiscImage(Disc::from_iso(path)?))
+			}
 			file_format::Kind::Video => {
 				let mut video = Video::new(path, format);
-				video.read_ffmpeg_content()?;
+				if let Err(err) = video.read_ffmpeg_content() {
+					warn!("{:?}: {err}", video.path);
+					video.probe_error = Some(err.to_string());
+				}
 				video.discover()?;
+				if let Some(hint) = NfoHint::find(&video) {
+					hint.apply(&mut video);
+				}
 				Ok(Self::Video(video))
 			}
 			_ => Ok(Self::File((path, format))),
@@ -6,8 +6,17 @@ use chrono::{Datelike, Local};
 use ffmpeg::format::context::Input as ffmpegContext;
 use file_format::FileFormat;
 use log::debug;
+use regex::Regex;
 
-use crate::errors::{MediaOrderError, Result};
+use crate::{
+	errors::{MediaOrderError, Result},
+	integrity,
+	library::entities::files,
+};
+
+pub const WARNING_NO_NAME: &str = "no-name";
+pub const WARNING_NO_YEAR: &str = "no-year";
+pub const WARNING_TRUNCATED: &str = "truncated";
 
 #[derive(Clone, Debug)]
 pub struct Lang {
@@ -15,6 +24,16 @@ pub struct Lang {
 	code: &'static str,
 }
 
+impl Lang {
+	pub fn name(&self) -> &'static str {
+		self.name
+	}
+
+	pub fn code(&self) -> &'static str {
+		self.code
+	}
+}
+
 fn check_lang(str: &str, langs: &Vec<Lang>) -> Option<Lang> {
 	let str = str.to_lowercase();
 	for lang in langs {
@@ -125,6 +144,187 @@ fn remove_trash(parts: &mut Vec<&str>) {
 	}
 }
 
+/// Role of the video in the release folder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum VideoRole {
+	#[default]
+	Main,
+	Sample,
+	Trailer,
+	Extra,
+	DeletedScene,
+	BehindTheScenes,
+}
+
+lazy_static! {
+	static ref ROLE_MARKERS: Vec<(&'static str, VideoRole)> = vec![
+		("sample", VideoRole::Sample),
+		("trailer", VideoRole::Trailer),
+		("teaser", VideoRole::Trailer),
+		("deleted scenes", VideoRole::DeletedScene),
+		("deleted scene", VideoRole::DeletedScene),
+		("deleted", VideoRole::DeletedScene),
+		("behind the scenes", VideoRole::BehindTheScenes),
+		("behindthescenes", VideoRole::BehindTheScenes),
+		("making of", VideoRole::BehindTheScenes),
+		("featurette", VideoRole::Extra),
+		("interview", VideoRole::Extra),
+		("extras", VideoRole::Extra),
+		("bonus", VideoRole::Extra),
+	];
+}
+
+lazy_static! {
+	/// Markers which are common words of the titles (`The Interview`), only accepted as the `-interview` like
+	/// suffix, the videos in the extras folders get the role from the folder.
+	static ref SUFFIX_ROLE_MARKERS: Vec<&'static str> = vec!["deleted", "interview", "bonus"];
+}
+
+lazy_static! {
+	/// Markers which are the show titles (`Trailer Park Boys`, `Extras`), only accepted as the last word or the
+	/// `-trailer` like suffix of the name, and never for the episodes.
+	static ref TITLE_ROLE_MARKERS: Vec<&'static str> = vec!["trailer", "extras"];
+}
+
+lazy_static! {
+	static ref ROLE_FOLDERS: Vec<(&'static str, VideoRole)> = vec![
+		("sample", VideoRole::Sample),
+		("samples", VideoRole::Sample),
+		("trailers", VideoRole::Trailer),
+		("deleted scenes", VideoRole::DeletedScene),
+		("behind the scenes", VideoRole::BehindTheScenes),
+		("featurettes", VideoRole::Extra),
+		("interviews", VideoRole::Extra),
+		("extras", VideoRole::Extra),
+		("bonus", VideoRole::Extra),
+		("shorts", VideoRole::Extra),
+	];
+}
+
+impl VideoRole {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			VideoRole::Main => "main",
+			VideoRole::Sample => "sample",
+			VideoRole::Trailer => "trailer",
+			VideoRole::Extra => "extra",
+			VideoRole::DeletedScene => "deleted",
+			VideoRole::BehindTheScenes => "behindthescenes",
+		}
+	}
+
+	/// Folder name for the role extras in the organised library (as Plex and Jellyfin expect them).
+	pub fn folder_name(&self) -> &'static str {
+		match self {
+			VideoRole::Main => "",
+			VideoRole::Sample => "Samples",
+			VideoRole::Trailer => "Trailers",
+			VideoRole::Extra => "Extras",
+			VideoRole::DeletedScene => "Deleted Scenes",
+			VideoRole::BehindTheScenes => "Behind The Scenes",
+		}
+	}
+
+	/// Longest expected duration (seconds), longer video is the main feature even with the role in the name.
+	pub fn max_duration(&self) -> Option<f64> {
+		match self {
+			VideoRole::Main => None,
+			VideoRole::Sample | VideoRole::Trailer => Some(600.0),
+			_ => Some(3600.0),
+		}
+	}
+
+	/// Role by the folder name, like `Sample`, `Trailers` or `Deleted Scenes`.
+	pub fn from_folder(name: &str) -> Option<VideoRole> {
+		let name = name.to_lowercase();
+		ROLE_FOLDERS.iter().find(|(folder, _)| name == *folder).map(|(_, role)| *role)
+	}
+
+	/// Role by the marker word in the name parts (`sample`, `Deleted Scenes`) or by the `-trailer` like suffix.
+	fn from_name(parts: &[&str]) -> Option<VideoRole> {
+		let name = format!(" {} ", parts.join(" ").to_lowercase());
+		let is_episode = parts.iter().any(|part| parse_episode(part).is_some());
+		ROLE_MARKERS
+			.iter()
+			.find(|(marker, _)| {
+				let suffix = name.contains(&format!("-{marker} "));
+				if TITLE_ROLE_MARKERS.contains(marker) {
+					let last = parts.len() > 1 && name.ends_with(&format!(" {marker} "));
+					return !is_episode && (suffix || last);
+				}
+				(!SUFFIX_ROLE_MARKERS.contains(marker) && name.contains(&format!(" {marker} "))) || suffix
+			})
+			.map(|(_, role)| *role)
+	}
+}
+
+impl std::str::FromStr for VideoRole {
+	type Err = MediaOrderError;
+
+	fn from_str(str: &str) -> Result<Self> {
+		[
+			VideoRole::Main,
+			VideoRole::Sample,
+			VideoRole::Trailer,
+			VideoRole::Extra,
+			VideoRole::DeletedScene,
+			VideoRole::BehindTheScenes,
+		]
+		.into_iter()
+		.find(|role| role.as_str() == str)
+		.ok_or_else(|| MediaOrderError::ParseError("role".to_owned(), str.to_owned()))
+	}
+}
+
+lazy_static! {
+	static ref PART_MARKERS: Vec<&'static str> = vec!["cd", "dvd", "disc", "disk"];
+}
+
+lazy_static! {
+	/// Markers also glued to the number (`CD1`), `DVD5` and `DVD9` are the disc types instead.
+	static ref GLUED_PART_MARKERS: Vec<&'static str> = vec!["cd", "disc", "disk"];
+}
+
+lazy_static! {
+	static ref PART_WORDS: Vec<&'static str> = vec!["part", "pt", "часть"];
+}
+
+fn parse_part_number(str: &str) -> Option<u8> {
+	if !str.is_empty() && str.chars().all(|c| c.is_ascii_digit()) {
+		str.parse().ok()
+	} else {
+		None
+	}
+}
+
+lazy_static! {
+	/// `S01E02`, `s01e02-e03`, `S01E01E02E03`.
+	static ref EPISODE_SE: Regex = Regex::new(r"(?i)^s(\d{1,3})e(\d{1,3})((?:-?e\d{1,3})*)$").unwrap();
+	/// `1x02`, `1x02-03`.
+	static ref EPISODE_X: Regex = Regex::new(r"(?i)^(\d{1,2})x(\d{2,3})(?:-(\d{2,3}))?$").unwrap();
+}
+
+/// Season, first and last episode numbers of the episode marker.
+fn parse_episode(str: &str) -> Option<(u16, u16, u16)> {
+	if let Some(captures) = EPISODE_SE.captures(str) {
+		let first = captures[2].parse().ok()?;
+		let last = captures[3]
+			.rsplit(['e', 'E'])
+			.next()
+			.and_then(|last| last.parse().ok())
+			.unwrap_or(first);
+		return Some((captures[1].parse().ok()?, first, last.max(first)));
+	}
+	let captures = EPISODE_X.captures(str)?;
+	let first = captures[2].parse().ok()?;
+	let last = captures.get(3).and_then(|last| last.as_str().parse().ok()).unwrap_or(first);
+	Some((captures[1].parse().ok()?, first, last.max(first)))
+}
+
+fn is_bracket(str: &str) -> bool {
+	str.chars().all(|c| BRACKETS.contains(&c))
+}
+
 lazy_static! {
 	static ref VIDEO_EXTENSIONS: Vec<&'static str> = vec!["avi", "mkv", "mp4", "m4v", "mov"];
 }
@@ -148,6 +348,8 @@ pub struct Video {
 	pub name_english: Option<String>,
 	pub ffmpeg_context: Option<ffmpegContext>,
 	pub year: Option<u16>,
+	pub part: Option<u8>,
+	pub role: VideoRole,
 	pub genre: Option<String>,
 	pub lang: Option<Vec<Lang>>,
 	pub ext: Option<String>,
@@ -155,6 +357,49 @@ pub struct Video {
 	pub aenc: Option<String>,
 	pub vres: Option<String>,
 	pub vqual: Option<String>,
+	/// IMDb id from the NFO file next to the video.
+	pub imdb_id: Option<String>,
+	/// Why ffmpeg failed to probe the file, the name is parsed anyway.
+	pub probe_error: Option<String>,
+	pub season: Option<u16>,
+	pub episode: Option<u16>,
+	/// Last episode of the multi-episode file (`S01E01E02`), same as `episode` for the single one.
+	pub last_episode: Option<u16>,
+}
+
+/// Stream of the container as probed by ffmpeg.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamInfo {
+	pub index: usize,
+	pub medium: ffmpeg::media::Type,
+	pub codec: String,
+	pub language: Option<String>,
+	pub title: Option<String>,
+	pub default: bool,
+	pub forced: bool,
+	pub width: u32,
+	pub height: u32,
+	pub channels: u16,
+	/// Duration in seconds.
+	pub duration: Option<f64>,
+}
+
+impl StreamInfo {
+	pub fn new(index: usize, medium: ffmpeg::media::Type) -> Self {
+		Self {
+			index,
+			medium,
+			codec: String::new(),
+			language: None,
+			title: None,
+			default: false,
+			forced: false,
+			width: 0,
+			height: 0,
+			channels: 0,
+			duration: None,
+		}
+	}
 }
 
 impl Video {
@@ -170,6 +415,8 @@ impl Video {
 			ffmpeg_context: None,
 			name_english: None,
 			year: None,
+			part: None,
+			role: VideoRole::Main,
 			genre: None,
 			lang: None,
 			ext: None,
@@ -177,18 +424,140 @@ impl Video {
 			aenc: None,
 			vres: None,
 			vqual: None,
+			imdb_id: None,
+			probe_error: None,
+			season: None,
+			episode: None,
+			last_episode: None,
+		}
+	}
+
+	/// Video of the library record with the parsed details only, to group the stored files like the scanned
+	/// ones.
+	pub fn from_record(record: &files::Model) -> Self {
+		let number = |value: Option<i32>| value.and_then(|value| u16::try_from(value).ok());
+		Self {
+			name_original: record.name.clone().unwrap_or_default(),
+			name_english: record.name_english.clone(),
+			year: number(record.year),
+			part: record.part.and_then(|part| u8::try_from(part).ok()),
+			role: record.role.as_deref().and_then(|role| role.parse().ok()).unwrap_or_default(),
+			imdb_id: record.imdb_id.clone(),
+			season: number(record.season),
+			episode: number(record.episode),
+			last_episode: number(record.last_episode),
+			..Self::new(PathBuf::from(&record.path), FileFormat::ArbitraryBinaryData)
 		}
 	}
 
 	pub fn read_ffmpeg_content(&mut self) -> Result<()> {
-		self.ffmpeg_context =
-			Some(ffmpeg::format::input(&self.path).map_err(|_| MediaOrderError::VideoMetadata)?);
+		self.ffmpeg_context = Some(
+			ffmpeg::format::input(&self.path)
+				.map_err(|err| MediaOrderError::VideoMetadata(err.to_string()))?,
+		);
 		debug!("{}", self.get_full_ffmpeg_context());
 		Ok(())
 	}
 
 	pub fn discover(&mut self) -> Result<()> {
-		self.parse_file_name()
+		self.parse_file_name()?;
+		self.classify_role();
+		Ok(())
+	}
+
+	/// Duration in seconds from the ffmpeg context.
+	pub fn duration(&self) -> Option<f64> {
+		self.ffmpeg_context
+			.as_ref()
+			.map(|ffmpeg_context| ffmpeg_context.duration())
+			.filter(|&duration| duration > 0)
+			.map(|duration| duration as f64 / f64::from(ffmpeg::ffi::AV_TIME_BASE))
+	}
+
+	/// File of the `size` is noticeably smaller than the container bit rate and duration promise (incomplete
+	/// download or copy). Unknown for the not probed videos.
+	pub fn is_truncated(&self, size: u64) -> bool {
+		let (Some(ffmpeg_context), Some(duration)) = (&self.ffmpeg_context, self.duration()) else {
+			return false;
+		};
+		integrity::is_truncated(ffmpeg_context.bit_rate(), duration, size)
+	}
+
+	pub fn is_episode(&self) -> bool {
+		self.episode.is_some()
+	}
+
+	/// Problems of the parsed name: `no-name`, `no-year` (only for the main feature, not for episodes).
+	pub fn warnings(&self) -> Vec<&'static str> {
+		let mut warnings = vec![];
+		if self.name_original.is_empty() {
+			warnings.push(WARNING_NO_NAME);
+		}
+		if self.year.is_none() && self.role == VideoRole::Main && !self.is_episode() {
+			warnings.push(WARNING_NO_YEAR);
+		}
+		warnings
+	}
+
+	/// Streams of the ffmpeg context, empty when the video is not probed.
+	pub fn streams(&self) -> Vec<StreamInfo> {
+		let Some(ffmpeg_context) = &self.ffmpeg_context else {
+			return vec![];
+		};
+
+		ffmpeg_context
+			.streams()
+			.map(|stream| {
+				let metadata = stream.metadata();
+				let disposition = stream.disposition();
+				let mut info = StreamInfo::new(stream.index(), stream.parameters().medium());
+				info.language = metadata.get("language").map(String::from);
+				info.title = metadata.get("title").map(String::from);
+				info.default = disposition.contains(ffmpeg::format::stream::Disposition::DEFAULT);
+				info.forced = disposition.contains(ffmpeg::format::stream::Disposition::FORCED);
+				info.duration = Some(stream.duration() as f64 * f64::from(stream.time_base()))
+					.filter(|&duration| duration > 0.0);
+
+				if let Ok(codec) = ffmpeg::codec::context::Context::from_parameters(stream.parameters()) {
+					info.codec = codec.id().name().to_owned();
+					match codec.medium() {
+						ffmpeg::media::Type::Video => {
+							if let Ok(video) = codec.decoder().video() {
+								info.width = video.width();
+								info.height = video.height();
+							}
+						}
+						ffmpeg::media::Type::Audio => {
+							if let Ok(audio) = codec.decoder().audio() {
+								info.channels = audio.channels();
+							}
+						}
+						_ => {}
+					}
+				}
+				info
+			})
+			.collect()
+	}
+
+	/// Folder role wins over the role from the name, the role is reset to main when the video is too long for it.
+	/// The folder named as the video (the `Extras` show) is its release folder rather than the role one.
+	fn classify_role(&mut self) {
+		let folder = self
+			.path
+			.parent()
+			.and_then(|parent| parent.file_name())
+			.and_then(|name| name.to_str())
+			.filter(|folder| !folder.eq_ignore_ascii_case(&self.name_original));
+		if let Some(role) = folder.and_then(VideoRole::from_folder) {
+			self.role = role;
+		}
+
+		if let (Some(max_duration), Some(duration)) = (self.role.max_duration(), self.duration()) {
+			if duration > max_duration {
+				self.role = VideoRole::Main;
+			}
+		}
 	}
 
 	fn _check_set_year(&mut self, file_name: &str) -> Option<usize> {
@@ -219,6 +588,29 @@ impl Video {
 		None
 	}
 
+	/// Checks if part at index `i` is a multi-part marker (`CD1`, `cd 2`, `DVD 1`, `disc1`, `part 3`), returns part
+	/// number and index where marker starts. Words like "part" are only accepted when the year was not met
+	/// yet (going from the end), because "Part 1" before the year is usually a part of the title.
+	fn check_part(&self, parts: &[&str], i: usize) -> Option<(u8, usize)> {
+		let part = parts[i].to_lowercase();
+
+		if let Some(number) = parse_part_number(&part) {
+			let prev = parts[i - 1].to_lowercase();
+			if PART_MARKERS.contains(&prev.as_str())
+				|| (self.year.is_none() && PART_WORDS.contains(&prev.as_str()))
+			{
+				return Some((number, i - 1));
+			}
+			return None;
+		}
+
+		let number = GLUED_PART_MARKERS
+			.iter()
+			.chain(PART_WORDS.iter().filter(|_| self.year.is_none()))
+			.find_map(|marker| part.strip_prefix(marker).and_then(parse_part_number))?;
+		Some((number, i))
+	}
+
 	fn split_by_separators<'a>(&self, file_name: &'a str) -> Vec<&'a str> {
 		let mut parts = vec![];
 		let mut left = 0;
@@ -255,9 +647,26 @@ impl Video {
 			.to_os_string()
 			.into_string()?;
 
+		self.parse_name(&file_name)
+	}
+
+	/// Parses name, year and other release info from the given name, which may differ from the file name
+	/// (disc folder name for DVD/Blu-ray structures).
+	pub(crate) fn parse_name(&mut self, file_name: &str) -> Result<()> {
 		// dbg!(&file_name);
 
-		let mut parts = self.split_by_separators(&file_name);
+		let mut parts = self.split_by_separators(file_name);
+
+		// remove split volume number (film.avi.001)
+		if parts.len() > 2 && parts.last().unwrap().len() == 3 {
+			let ext = parts[parts.len() - 2].to_lowercase();
+			if VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+				self.part = parse_part_number(parts.last().unwrap());
+				if self.part.is_some() {
+					parts.pop();
+				}
+			}
+		}
 
 		// remove extension
 		let ext = parts.last().unwrap().to_lowercase();
@@ -266,12 +675,36 @@ impl Video {
 			parts.pop();
 		}
 
+		if let Some(role) = VideoRole::from_name(&parts) {
+			self.role = role;
+			// "Brat-trailer" is a trailer of "Brat"
+			for part in parts.iter_mut() {
+				if let Some((name, suffix)) = part.rsplit_once('-') {
+					let suffix = suffix.to_lowercase();
+					if !name.is_empty() && ROLE_MARKERS.iter().any(|(marker, _)| *marker == suffix) {
+						*part = name;
+					}
+				}
+			}
+		}
+
 		// remove trash
 		remove_trash(&mut parts);
 
 		let mut name_end = parts.len();
 
 		for (i, part) in parts.iter().enumerate().skip(1).rev() {
+			// episode title goes after the marker
+			if self.episode.is_none() {
+				if let Some((season, first, last)) = parse_episode(part) {
+					self.season = Some(season);
+					self.episode = Some(first);
+					self.last_episode = Some(last);
+					name_end = i;
+					continue;
+				}
+			}
+
 			if self.year.is_none() && part.len() == 4 {
 				if let Ok(year) = part.parse::<u16>() {
 					if year >= 1920 && year <= *CUR_YEAR {
@@ -282,6 +715,15 @@ impl Video {
 				}
 			}
 
+			if self.part.is_none() && parts[i + 1..name_end].iter().all(|part| is_bracket(part)) {
+				// the marker is kept in the name when nothing else is left (`part 2.avi`)
+				if let Some((number, start)) = self.check_part(&parts, i).filter(|(_, start)| *start > 0) {
+					self.part = Some(number);
+					name_end = start;
+					continue;
+				}
+			}
+
 			let part = part.trim_end_matches(',').to_lowercase();
 
 			if let Some(lang) = check_lang(&part, &LANG2) {
@@ -345,12 +787,17 @@ impl Video {
 			}
 		}
 
+		if self.episode.is_some() {
+			// `Show - S01E02 - Title`
+			self.name_original = self.name_original.trim_end_matches([' ', '-']).to_owned();
+		}
+
 		// dbg!(&self.name_original);
 		// dbg!(&parts);
 		// dbg!(self);
 
 		//TODO:
-		// 1. Series detection (S\d\dE\d\d, seriya, serija, серия, ete., evristic (numbers in folder))
+		// 1. Series detection by words (seriya, serija, серия, ete., evristic (numbers in folder))
 		// 2. Separate name part in brackets (also try to detect language in both parts)
 		// 3. Removing commas
 		Ok(())
@@ -465,6 +912,8 @@ impl std::fmt::Debug for Video {
 			.field("name_original", &self.name_original)
 			.field("name_english", &self.name_english)
 			.field("year", &self.year)
+			.field("part", &self.part)
+			.field("role", &self.role)
 			.field("genre", &self.genre)
 			.field("lang", &self.lang)
 			.field("ext", &self.ext)
@@ -472,6 +921,11 @@ impl std::fmt::Debug for Video {
 			.field("aenc", &self.aenc)
 			.field("vres", &self.vres)
 			.field("vqual", &self.vqual)
+			.field("imdb_id", &self.imdb_id)
+			.field("probe_error", &self.probe_error)
+			.field("season", &self.season)
+			.field("episode", &self.episode)
+			.field("last_episode", &self.last_episode)
 			.finish()
 	}
 }
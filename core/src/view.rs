@@ -0,0 +1,179 @@
+use std::{
+	collections::HashMap,
+	fmt,
+	fs,
+	os::unix::fs::MetadataExt,
+	path::{Path, PathBuf},
+};
+
+use log::{debug, info, warn};
+
+use crate::{
+	errors::{MediaOrderError, Result},
+	media::MediaItem,
+	organise::{is_same_file, place, plan, PlaceMode, Placement, RenameTemplate},
+};
+
+/// File in the view root listing the links made by the view (with their sources), only these are replaced or
+/// pruned.
+pub const MANIFEST_FILE: &str = ".mediaorder-view";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ViewStats {
+	pub linked: u64,
+	pub kept: u64,
+	pub pruned: u64,
+	pub conflicts: u64,
+}
+
+impl fmt::Display for ViewStats {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{} linked, {} kept, {} pruned, {} conflicts",
+			self.linked, self.kept, self.pruned, self.conflicts
+		)
+	}
+}
+
+/// Organised library made of links to the original files, which stay in place (e.g. for seeding). Files are
+/// hardlinked when the view is on the same file system, symlinked otherwise.
+pub struct OrganisedView {
+	root: PathBuf,
+	template: RenameTemplate,
+}
+
+fn remove(path: &Path) -> std::io::Result<()> {
+	if fs::symlink_metadata(path)?.is_dir() {
+		fs::remove_dir_all(path)
+	} else {
+		fs::remove_file(path)
+	}
+}
+
+impl OrganisedView {
+	pub fn new(root: PathBuf) -> Self {
+		Self {
+			root,
+			template: RenameTemplate::default(),
+		}
+	}
+
+	pub fn with_template(mut self, template: RenameTemplate) -> Self {
+		self.template = template;
+		self
+	}
+
+	/// Links of the previous sync by the target, the manifests written before the sources were listed have none.
+	fn manifest(&self) -> HashMap<PathBuf, Option<PathBuf>> {
+		fs::read_to_string(self.root.join(MANIFEST_FILE))
+			.map(|manifest| {
+				manifest
+					.lines()
+					.map(|line| match line.split_once('\t') {
+						Some((target, source)) => (self.root.join(target), Some(PathBuf::from(source))),
+						None => (self.root.join(line), None),
+					})
+					.collect()
+			})
+			.unwrap_or_default()
+	}
+
+	fn write_manifest(&self, targets: &HashMap<PathBuf, Option<PathBuf>>) -> Result<()> {
+		let mut lines: Vec<String> = targets
+			.iter()
+			.filter_map(|(target, source)| {
+				let target = target.strip_prefix(&self.root).ok()?.to_string_lossy();
+				Some(match source {
+					Some(source) => format!("{target}\t{}", source.display()),
+					None => target.into_owned(),
+				})
+			})
+			.collect();
+		lines.sort();
+		fs::write(self.root.join(MANIFEST_FILE), lines.join("\n"))?;
+		Ok(())
+	}
+
+	fn link_mode(&self, source: &Path) -> PlaceMode {
+		match (fs::metadata(source), fs::metadata(&self.root)) {
+			(Ok(source), Ok(root)) if source.dev() == root.dev() => PlaceMode::Hardlink,
+			_ => PlaceMode::Symlink,
+		}
+	}
+
+	/// Links the items into the view by the template. Links of the previous sync which are not planned any more
+	/// (source disappeared or renamed) are pruned with the folders left empty, links pointing to the replaced
+	/// sources are relinked. Existing paths not made by the view are never touched.
+	pub fn sync(&self, items: &[MediaItem]) -> Result<ViewStats> {
+		self.sync_scoped(items, None)
+	}
+
+	/// Syncs the items found under the `scanned` path, only the links to the sources under it are pruned and the
+	/// rest of the view is kept.
+	pub fn sync_under(&self, items: &[MediaItem], scanned: &Path) -> Result<ViewStats> {
+		self.sync_scoped(items, Some(scanned))
+	}
+
+	fn sync_scoped(&self, items: &[MediaItem], scope: Option<&Path>) -> Result<ViewStats> {
+		fs::create_dir_all(&self.root)?;
+		let previous = self.manifest();
+		let mut targets = HashMap::new();
+		let mut stats = ViewStats::default();
+
+		for Placement { source, target } in
+			items.iter().flat_map(|item| plan(item, &self.root, &self.template, None))
+		{
+			let managed = previous.contains_key(&target);
+			if fs::symlink_metadata(&target).is_ok() {
+				if managed && (source.is_dir() || is_same_file(&source, &target)) {
+					stats.kept += 1;
+					targets.insert(target, Some(source));
+					continue;
+				}
+				if !managed {
+					warn!("Not linking {:?}: {:?} already exists", source, target);
+					stats.conflicts += 1;
+					continue;
+				}
+				debug!("Relinking {:?}", target);
+				remove(&target)?;
+			}
+
+			let placement = Placement { source, target };
+			match place(&placement, self.link_mode(&placement.source)) {
+				Ok(()) => stats.linked += 1,
+				Err(MediaOrderError::TargetExistsError(target)) => {
+					warn!("Not linking {:?}: {:?} already exists", placement.source, target);
+					stats.conflicts += 1;
+					continue;
+				}
+				Err(err) => return Err(err),
+			}
+			targets.insert(placement.target, Some(placement.source));
+		}
+
+		for (stale, source) in previous {
+			if targets.contains_key(&stale) || fs::symlink_metadata(&stale).is_err() {
+				continue;
+			}
+			// the partial sync keeps the links to the sources outside of the scanned path and to the unknown ones
+			if scope.is_some_and(|scope| !source.as_ref().is_some_and(|source| source.starts_with(scope))) {
+				targets.insert(stale, source);
+				continue;
+			}
+			debug!("Pruning {:?}", stale);
+			remove(&stale)?;
+			stats.pruned += 1;
+			for folder in stale.ancestors().skip(1).take_while(|folder| *folder != self.root) {
+				if fs::remove_dir(folder).is_err() {
+					break;
+				}
+			}
+		}
+
+		self.write_manifest(&targets)?;
+		info!("View {:?} synced: {}", self.root, stats);
+		Ok(stats)
+	}
+}
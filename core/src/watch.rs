@@ -0,0 +1,189 @@
+use std::{
+	collections::HashMap,
+	future::Future,
+	path::{Path, PathBuf},
+	time::{Duration, Instant},
+};
+
+use log::{debug, error, info, warn};
+use notify::{
+	event::{ModifyKind, RenameMode},
+	Event,
+	EventKind,
+	RecursiveMode,
+	Watcher,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+	config::{ScanConfig, WatchConfig},
+	disc::Disc,
+	errors::Result,
+	library::Library,
+	media::group_parts,
+	rules::ScanRules,
+	view::OrganisedView,
+};
+
+struct Pending {
+	last_event: Instant,
+	size: Option<u64>,
+}
+
+/// Keeps the library store in sync with the file system changes under the root.
+pub struct LibraryWatcher<'a> {
+	library: &'a Library,
+	root: PathBuf,
+	scan_config: ScanConfig,
+	rules: ScanRules,
+	config: WatchConfig,
+	view: Option<OrganisedView>,
+	pending: HashMap<PathBuf, Pending>,
+}
+
+async fn file_size(path: &Path) -> Option<u64> {
+	tokio::fs::metadata(path).await.ok().map(|metadata| metadata.len())
+}
+
+impl<'a> LibraryWatcher<'a> {
+	pub fn new(library: &'a Library, root: PathBuf, scan_config: ScanConfig, rules: ScanRules) -> Self {
+		Self {
+			library,
+			root,
+			scan_config,
+			rules,
+			config: WatchConfig::default(),
+			view: None,
+			pending: HashMap::new(),
+		}
+	}
+
+	pub fn with_config(mut self, config: WatchConfig) -> Self {
+		self.config = config;
+		self
+	}
+
+	/// Keeps the view in sync with the updated paths.
+	pub fn with_view(mut self, view: OrganisedView) -> Self {
+		self.view = Some(view);
+		self
+	}
+
+	/// Watches the root until `stop` completes. Changed paths are rescanned (or removed from the library) once
+	/// they got no events and kept the same size for [`WatchConfig::settle`] time, renames are applied to the
+	/// library records right away. Failed updates are logged and the watching goes on.
+	pub async fn run(mut self, stop: impl Future<Output = ()>) -> Result<()> {
+		let (tx, mut rx) = mpsc::unbounded_channel();
+		let mut watcher = notify::recommended_watcher(move |event| {
+			let _ = tx.send(event);
+		})?;
+		watcher.watch(&self.root, RecursiveMode::Recursive)?;
+		info!("Watching {:?}", self.root);
+
+		let mut interval = tokio::time::interval(self.config.settle.min(Duration::from_secs(1)));
+		tokio::pin!(stop);
+
+		loop {
+			tokio::select! {
+				_ = &mut stop => break,
+				event = rx.recv() => match event {
+					Some(Ok(event)) => {
+						if let Err(err) = self.handle_event(event).await {
+							error!("Watch event failed: {err}");
+						}
+					}
+					Some(Err(err)) => warn!("Watch error: {err}"),
+					None => break,
+				},
+				_ = interval.tick() => {
+					for path in self.settled().await {
+						// the failed path is picked up by its next change or the next scan
+						if let Err(err) = self.update(&path).await {
+							error!("Updating {:?} failed: {err}", path);
+						}
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Marks the path changed, the changes inside `VIDEO_TS` and `BDMV` rescan the whole disc folder.
+	async fn touch(&mut self, path: PathBuf) {
+		let path = Disc::root_of(&path).map(Path::to_path_buf).unwrap_or(path);
+		let size = file_size(&path).await;
+		self.pending.insert(path, Pending {
+			last_event: Instant::now(),
+			size,
+		});
+	}
+
+	async fn handle_event(&mut self, event: Event) -> Result<()> {
+		debug!("{:?}", event);
+		match event.kind {
+			EventKind::Access(_) | EventKind::Modify(ModifyKind::Metadata(_)) => {}
+			EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+				let moved = self.library.rename(&event.paths[0], &event.paths[1]).await?;
+				debug!("Moved {moved} records from {:?} to {:?}", event.paths[0], event.paths[1]);
+				// new name changes parsed info, so the path is rescanned anyway, the old one prunes its links
+				self.touch(event.paths[0].clone()).await;
+				self.touch(event.paths[1].clone()).await;
+			}
+			_ => {
+				for path in event.paths {
+					self.touch(path).await;
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Takes paths without changes for the settle time, nested paths of the settled folders are dropped as
+	/// they are scanned with the folder.
+	async fn settled(&mut self) -> Vec<PathBuf> {
+		let mut settled = vec![];
+
+		for (path, pending) in self.pending.iter_mut() {
+			if pending.last_event.elapsed() < self.config.settle {
+				continue;
+			}
+			let size = file_size(path).await;
+			if size != pending.size {
+				pending.size = size;
+				pending.last_event = Instant::now();
+				continue;
+			}
+			settled.push(path.clone());
+		}
+
+		for path in &settled {
+			self.pending.remove(path);
+		}
+		settled.sort();
+		settled.dedup_by(|path, parent| path.starts_with(parent));
+		settled
+	}
+
+	async fn update(&self, path: &Path) -> Result<()> {
+		let items = if tokio::fs::symlink_metadata(path).await.is_ok()
+			&& self.rules.accepts_path(&self.root, path).await
+		{
+			let (progress, videos) = self
+				.library
+				.scan(path.to_path_buf(), self.scan_config.clone(), self.rules.clone())
+				.await?;
+			info!("Updated {:?}: {}", path, progress);
+			group_parts(videos)
+		} else {
+			let removed = self.library.remove(path).await?;
+			info!("Removed {:?}: {} records", path, removed);
+			vec![]
+		};
+		// only the links to the files under the path are replaced or pruned
+		if let Some(view) = &self.view {
+			view.sync_under(&items, path)?;
+		}
+		Ok(())
+	}
+}
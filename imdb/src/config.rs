@@ -0,0 +1,130 @@
+use std::{
+	collections::HashMap,
+	env,
+	fmt,
+	fs,
+	path::{self, Path, PathBuf},
+	str::FromStr,
+};
+
+use sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
+
+use crate::errors::{ImdbError, Result};
+
+/// Name of the in-memory database location.
+const MEMORY: &str = ":memory:";
+const DEFAULT_FILE: &str = "local_imdb.sqlite";
+
+/// Reads and parses the config value, `default` is used when the value is not set.
+fn value_or<T: FromStr>(value: &impl Fn(&str) -> Option<String>, name: &str, default: T) -> Result<T> {
+	match value(name) {
+		Some(value) => value.parse().map_err(|_| ImdbError::ConfigError(name.to_owned(), value)),
+		None => Ok(default),
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DatabaseLocation {
+	/// Absolute path of the SQLite file.
+	File(PathBuf),
+	/// Private database of the connection pool, e.g. for the tests.
+	Memory,
+}
+
+impl DatabaseLocation {
+	/// File location, the relative path is resolved against the `base` folder.
+	fn parse(location: &str, base: &Path) -> Result<Self> {
+		if location == MEMORY {
+			return Ok(DatabaseLocation::Memory);
+		}
+		Ok(DatabaseLocation::File(path::absolute(base.join(location))?))
+	}
+}
+
+impl fmt::Display for DatabaseLocation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			DatabaseLocation::File(path) => write!(f, "{}", path.display()),
+			DatabaseLocation::Memory => f.write_str(MEMORY),
+		}
+	}
+}
+
+/// Local IMDb database and its connection pool.
+#[derive(Clone, Debug)]
+pub struct DatabaseConfig {
+	pub location: DatabaseLocation,
+	/// Opened without creating or writing the file, e.g. for the lookups next to a running import.
+	pub read_only: bool,
+	/// Set when the database is writable, WAL lets the lookups read during the imports.
+	pub journal_mode: SqliteJournalMode,
+	pub synchronous: SqliteSynchronous,
+	/// Max number of pooled connections, the in-memory database has one.
+	pub pool_size: u32,
+}
+
+impl DatabaseConfig {
+	/// Database file, the relative path is resolved against the current folder.
+	pub fn file(path: impl AsRef<Path>) -> Result<Self> {
+		Ok(Self {
+			location: DatabaseLocation::File(path::absolute(path)?),
+			..Self::memory()
+		})
+	}
+
+	pub fn memory() -> Self {
+		Self {
+			location: DatabaseLocation::Memory,
+			read_only: false,
+			journal_mode: SqliteJournalMode::Wal,
+			synchronous: SqliteSynchronous::Normal,
+			pool_size: 4,
+		}
+	}
+
+	pub fn read_only(mut self) -> Self {
+		self.read_only = true;
+		self
+	}
+
+	/// Config from the `IMDB_DATABASE*` environment variables, see [`DatabaseConfig::from_file`].
+	pub fn from_env() -> Result<Self> {
+		Self::from_values(|name| env::var(name).ok(), &env::current_dir()?)
+	}
+
+	/// Config from the file of `NAME=value` lines with the names of the environment variables, empty lines
+	/// and `#` comments are skipped. The relative database path is resolved against the file folder.
+	///
+	/// - `IMDB_DATABASE`: database file or `:memory:`, `local_imdb.sqlite` by default;
+	/// - `IMDB_DATABASE_READ_ONLY`: `true` or `false`;
+	/// - `IMDB_DATABASE_JOURNAL`: SQLite journal mode, `wal` by default;
+	/// - `IMDB_DATABASE_SYNCHRONOUS`: `off`, `normal` (default), `full` or `extra`;
+	/// - `IMDB_DATABASE_POOL_SIZE`: max number of connections, 4 by default.
+	pub fn from_file(file_path: &Path) -> Result<Self> {
+		let content = fs::read_to_string(file_path)?;
+		let mut values = HashMap::new();
+		for line in content.lines().map(str::trim) {
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let (name, value) = line
+				.split_once('=')
+				.ok_or_else(|| ImdbError::ConfigError(file_path.display().to_string(), line.to_owned()))?;
+			values.insert(name.trim().to_owned(), value.trim().to_owned());
+		}
+		let base = path::absolute(file_path)?;
+		Self::from_values(|name| values.get(name).cloned(), base.parent().unwrap_or(Path::new("/")))
+	}
+
+	fn from_values(value: impl Fn(&str) -> Option<String>, base: &Path) -> Result<Self> {
+		let default = Self::memory();
+		let location = value("IMDB_DATABASE").unwrap_or_else(|| DEFAULT_FILE.to_owned());
+		Ok(Self {
+			location: DatabaseLocation::parse(&location, base)?,
+			read_only: value_or(&value, "IMDB_DATABASE_READ_ONLY", default.read_only)?,
+			journal_mode: value_or(&value, "IMDB_DATABASE_JOURNAL", default.journal_mode)?,
+			synchronous: value_or(&value, "IMDB_DATABASE_SYNCHRONOUS", default.synchronous)?,
+			pool_size: value_or(&value, "IMDB_DATABASE_POOL_SIZE", default.pool_size)?.max(1),
+		})
+	}
+}
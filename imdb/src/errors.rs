@@ -4,6 +4,26 @@ use thiserror::Error;
 pub enum ImdbError {
 	#[error(transparent)]
 	SeaOrmMigration(#[from] sea_orm_migration::DbErr),
+	#[error(transparent)]
+	SqlxError(#[from] sqlx::Error),
+	#[error(transparent)]
+	IoError(#[from] std::io::Error),
+	#[error(transparent)]
+	JsonError(#[from] serde_json::Error),
+	#[error(transparent)]
+	UrlError(#[from] url::ParseError),
+	#[error("Metadata provider {0}: {1}")]
+	ProviderError(String, String),
+	#[error("Invalid config value {0}={1}")]
+	ConfigError(String, String),
+	#[error("Database {0} is not at the latest version, pending migrations {1:?}")]
+	MigrationPendingError(String, Vec<String>),
+	#[error("Unexpected header {0:?}, expected {1:?}")]
+	HeaderError(String, String),
+	#[error("Line {0}: expected {1} columns, found {2}")]
+	ColumnCountError(usize, usize, usize),
+	#[error("Line {0}, column {1}: can't decode {2:?}")]
+	ColumnError(usize, String, String),
 }
 
 pub type Result<T> = std::result::Result<T, ImdbError>;
@@ -1,4 +1,6 @@
 #[cfg(test)] mod tests;
 
+pub mod config;
 pub mod errors;
 pub mod local;
+pub mod provider;
@@ -0,0 +1,217 @@
+use std::{collections::HashMap, path::Path};
+
+use async_trait::async_trait;
+use sea_orm::{
+	sea_query::OnConflict,
+	ActiveValue,
+	ColumnTrait,
+	ConnectionTrait,
+	DatabaseConnection,
+	EntityTrait,
+	QueryFilter,
+	QueryOrder,
+	QuerySelect,
+};
+
+use crate::{
+	errors::Result,
+	local::{
+		entities::basics,
+		refresh::Table,
+		title::{id, tconst},
+		tsv::{self, join_array, split_array, Fields, Row},
+		BATCH_SIZE,
+	},
+};
+
+/// Row of `title.basics.tsv`, the title id is stored without the `tt` prefix. Title type is `movie`,
+/// `tvSeries`, `tvEpisode` and so on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleBasics {
+	pub title_id: String,
+	pub title_type: String,
+	pub primary_title: String,
+	pub original_title: String,
+	pub is_adult: bool,
+	pub start_year: Option<u16>,
+	pub end_year: Option<u16>,
+	/// Minutes.
+	pub runtime: Option<u32>,
+	pub genres: Vec<String>,
+}
+
+impl Row for TitleBasics {
+	const COLUMNS: &'static [&'static str] = &[
+		"tconst",
+		"titleType",
+		"primaryTitle",
+		"originalTitle",
+		"isAdult",
+		"startYear",
+		"endYear",
+		"runtimeMinutes",
+		"genres",
+	];
+
+	fn decode(fields: &mut Fields) -> Result<Self> {
+		Ok(TitleBasics {
+			title_id: fields.id("tt")?,
+			title_type: fields.required()?,
+			primary_title: fields.required()?,
+			original_title: fields.required()?,
+			is_adult: fields.required()?,
+			start_year: fields.optional()?,
+			end_year: fields.optional()?,
+			runtime: fields.optional()?,
+			genres: fields.array()?,
+		})
+	}
+}
+
+impl From<basics::Model> for TitleBasics {
+	fn from(model: basics::Model) -> Self {
+		TitleBasics {
+			title_id: model.id,
+			title_type: model.title_type,
+			primary_title: model.primary_title,
+			original_title: model.original_title,
+			is_adult: model.is_adult,
+			start_year: model.start_year.map(|year| year as u16),
+			end_year: model.end_year.map(|year| year as u16),
+			runtime: model.runtime.map(|runtime| runtime as u32),
+			genres: split_array(model.genres),
+		}
+	}
+}
+
+#[async_trait]
+impl Table for TitleBasics {
+	type Key = String;
+
+	const DATASET: &'static str = "title.basics";
+
+	fn key(&self) -> Self::Key {
+		self.title_id.clone()
+	}
+
+	fn tconst(&self) -> Option<String> {
+		Some(tconst(&self.title_id))
+	}
+
+	async fn find<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<Vec<Self>> {
+		let found = basics::Entity::find()
+			.filter(basics::Column::Id.is_in(keys.iter().cloned()))
+			.all(db)
+			.await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn page<C: ConnectionTrait>(db: &C, after: Option<&Self::Key>, limit: u64) -> Result<Vec<Self>> {
+		let mut query = basics::Entity::find();
+		if let Some(after) = after {
+			query = query.filter(basics::Column::Id.gt(after.as_str()));
+		}
+		let found = query.order_by_asc(basics::Column::Id).limit(limit).all(db).await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn upsert<C: ConnectionTrait>(db: &C, rows: &[Self]) -> Result<()> {
+		fill_basics_table(db, rows).await
+	}
+
+	async fn delete<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<()> {
+		for batch in keys.chunks(BATCH_SIZE) {
+			basics::Entity::delete_many()
+				.filter(basics::Column::Id.is_in(batch.iter().cloned()))
+				.exec(db)
+				.await?;
+		}
+		Ok(())
+	}
+}
+
+pub fn parse_gzip_file(file_path: &Path) -> Result<Vec<TitleBasics>> {
+	tsv::read_gzip_file(file_path)
+}
+
+/// Inserts the titles, replacing the already imported ones.
+pub async fn fill_basics_table<C: ConnectionTrait>(db: &C, titles: &[TitleBasics]) -> Result<()> {
+	for batch in titles.chunks(BATCH_SIZE) {
+		let models = batch.iter().map(|title| basics::ActiveModel {
+			id: ActiveValue::Set(title.title_id.clone()),
+			title_type: ActiveValue::Set(title.title_type.clone()),
+			primary_title: ActiveValue::Set(title.primary_title.clone()),
+			original_title: ActiveValue::Set(title.original_title.clone()),
+			is_adult: ActiveValue::Set(title.is_adult),
+			start_year: ActiveValue::Set(title.start_year.map(i32::from)),
+			end_year: ActiveValue::Set(title.end_year.map(i32::from)),
+			runtime: ActiveValue::Set(title.runtime.map(|runtime| runtime as i32)),
+			genres: ActiveValue::Set(join_array(&title.genres)),
+		});
+		basics::Entity::insert_many(models)
+			.on_conflict(
+				OnConflict::column(basics::Column::Id)
+					.update_columns([
+						basics::Column::TitleType,
+						basics::Column::PrimaryTitle,
+						basics::Column::OriginalTitle,
+						basics::Column::IsAdult,
+						basics::Column::StartYear,
+						basics::Column::EndYear,
+						basics::Column::Runtime,
+						basics::Column::Genres,
+					])
+					.to_owned(),
+			)
+			.exec(db)
+			.await?;
+	}
+	Ok(())
+}
+
+/// Titles by their tconsts, the unknown titles are absent.
+pub async fn find_basics(db: &DatabaseConnection, titles: &[String]) -> Result<HashMap<String, TitleBasics>> {
+	let mut found = HashMap::new();
+	for batch in titles.chunks(BATCH_SIZE) {
+		let models = basics::Entity::find()
+			.filter(basics::Column::Id.is_in(batch.iter().map(|title| id(title))))
+			.all(db)
+			.await?;
+		for model in models {
+			found.insert(tconst(&model.id), TitleBasics::from(model));
+		}
+	}
+	Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_title_basics() {
+		let parsed: TitleBasics =
+			tsv::decode_line(1, "tt0124315\tmovie\tBrat\tBrat\t0\t1997\t\\N\t100\tAction,Crime,Drama")
+				.unwrap();
+		assert_eq!(parsed.title_type, "movie");
+		assert_eq!(
+			(parsed.start_year, parsed.end_year, parsed.runtime),
+			(Some(1997), None, Some(100))
+		);
+		assert_eq!(parsed.genres, ["Action", "Crime", "Drama"]);
+		assert_eq!(
+			TitleBasics::from(basics::Model {
+				id: "0124315".to_owned(),
+				title_type: "movie".to_owned(),
+				primary_title: "Brat".to_owned(),
+				original_title: "Brat".to_owned(),
+				is_adult: false,
+				start_year: Some(1997),
+				end_year: None,
+				runtime: Some(100),
+				genres: join_array(&parsed.genres),
+			}),
+			parsed
+		);
+	}
+}
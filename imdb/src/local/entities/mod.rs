@@ -2,4 +2,11 @@
 
 pub mod prelude;
 
+pub mod akas;
+pub mod basics;
+pub mod episodes;
+pub mod imports;
+pub mod names;
+pub mod principals;
+pub mod ratings;
 pub mod titles;
@@ -1,3 +1,12 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.4
 
-pub use super::titles::Entity as Titles;
+pub use super::{
+	akas::Entity as Akas,
+	basics::Entity as Basics,
+	episodes::Entity as Episodes,
+	imports::Entity as Imports,
+	names::Entity as Names,
+	principals::Entity as Principals,
+	ratings::Entity as Ratings,
+	titles::Entity as Titles,
+};
@@ -0,0 +1,196 @@
+use std::{collections::BTreeMap, path::Path};
+
+use async_trait::async_trait;
+use sea_orm::{
+	sea_query::OnConflict,
+	ActiveValue,
+	ColumnTrait,
+	ConnectionTrait,
+	DatabaseConnection,
+	EntityTrait,
+	QueryFilter,
+	QueryOrder,
+	QuerySelect,
+};
+
+use crate::{
+	errors::Result,
+	local::{
+		entities::episodes,
+		refresh::Table,
+		title::{id, tconst},
+		tsv::{self, Fields, Row},
+		BATCH_SIZE,
+	},
+};
+
+/// Row of `title.episode.tsv`, ids are stored without the `tt` prefix like the akas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Episode {
+	pub title_id: String,
+	pub parent_id: String,
+	pub season: Option<u16>,
+	pub episode: Option<u16>,
+}
+
+/// Episode tconsts by the episode number by the season number.
+pub type EpisodeMap = BTreeMap<u16, BTreeMap<u16, String>>;
+
+impl Row for Episode {
+	const COLUMNS: &'static [&'static str] = &["tconst", "parentTconst", "seasonNumber", "episodeNumber"];
+
+	fn decode(fields: &mut Fields) -> Result<Self> {
+		Ok(Episode {
+			title_id: fields.id("tt")?,
+			parent_id: fields.id("tt")?,
+			season: fields.optional()?,
+			episode: fields.optional()?,
+		})
+	}
+}
+
+pub fn parse_gzip_file(file_path: &Path) -> Result<Vec<Episode>> {
+	tsv::read_gzip_file(file_path)
+}
+
+impl From<episodes::Model> for Episode {
+	fn from(model: episodes::Model) -> Self {
+		Episode {
+			title_id: model.id,
+			parent_id: model.parent_id,
+			season: model.season.map(|season| season as u16),
+			episode: model.episode.map(|episode| episode as u16),
+		}
+	}
+}
+#[async_trait]
+impl Table for Episode {
+	type Key = String;
+
+	const DATASET: &'static str = "title.episode";
+
+	fn key(&self) -> Self::Key {
+		self.title_id.clone()
+	}
+
+	fn tconst(&self) -> Option<String> {
+		Some(tconst(&self.title_id))
+	}
+
+	async fn find<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<Vec<Self>> {
+		let found = episodes::Entity::find()
+			.filter(episodes::Column::Id.is_in(keys.iter().cloned()))
+			.all(db)
+			.await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn page<C: ConnectionTrait>(db: &C, after: Option<&Self::Key>, limit: u64) -> Result<Vec<Self>> {
+		let mut query = episodes::Entity::find();
+		if let Some(after) = after {
+			query = query.filter(episodes::Column::Id.gt(after.as_str()));
+		}
+		let found = query.order_by_asc(episodes::Column::Id).limit(limit).all(db).await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn upsert<C: ConnectionTrait>(db: &C, rows: &[Self]) -> Result<()> {
+		fill_episodes_table(db, rows).await
+	}
+
+	async fn delete<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<()> {
+		for batch in keys.chunks(BATCH_SIZE) {
+			episodes::Entity::delete_many()
+				.filter(episodes::Column::Id.is_in(batch.iter().cloned()))
+				.exec(db)
+				.await?;
+		}
+		Ok(())
+	}
+}
+
+/// Inserts the episodes, replacing the already imported ones.
+pub async fn fill_episodes_table<C: ConnectionTrait>(db: &C, episodes: &[Episode]) -> Result<()> {
+	for batch in episodes.chunks(BATCH_SIZE) {
+		let models = batch.iter().map(|episode| episodes::ActiveModel {
+			id: ActiveValue::Set(episode.title_id.clone()),
+			parent_id: ActiveValue::Set(episode.parent_id.clone()),
+			season: ActiveValue::Set(episode.season.map(i32::from)),
+			episode: ActiveValue::Set(episode.episode.map(i32::from)),
+		});
+		episodes::Entity::insert_many(models)
+			.on_conflict(
+				OnConflict::column(episodes::Column::Id)
+					.update_columns([
+						episodes::Column::ParentId,
+						episodes::Column::Season,
+						episodes::Column::Episode,
+					])
+					.to_owned(),
+			)
+			.exec(db)
+			.await?;
+	}
+	Ok(())
+}
+
+/// Tconst of the episode of the series tconst.
+pub async fn find_episode(
+	db: &DatabaseConnection,
+	series: &str,
+	season: u16,
+	episode: u16,
+) -> Result<Option<String>> {
+	let found = episodes::Entity::find()
+		.filter(episodes::Column::ParentId.eq(id(series)))
+		.filter(episodes::Column::Season.eq(i32::from(season)))
+		.filter(episodes::Column::Episode.eq(i32::from(episode)))
+		.one(db)
+		.await?;
+	Ok(found.map(|found| tconst(&found.id)))
+}
+
+/// Series tconst of the episode tconst.
+pub async fn find_series(db: &DatabaseConnection, episode: &str) -> Result<Option<String>> {
+	let found = episodes::Entity::find_by_id(id(episode)).one(db).await?;
+	Ok(found.map(|found| tconst(&found.parent_id)))
+}
+
+/// All numbered episodes of the series tconst, empty for unknown series.
+pub async fn episode_map(db: &DatabaseConnection, series: &str) -> Result<EpisodeMap> {
+	let found = episodes::Entity::find()
+		.filter(episodes::Column::ParentId.eq(id(series)))
+		.filter(episodes::Column::Season.is_not_null())
+		.filter(episodes::Column::Episode.is_not_null())
+		.order_by_asc(episodes::Column::Season)
+		.order_by_asc(episodes::Column::Episode)
+		.all(db)
+		.await?;
+
+	let mut map = EpisodeMap::new();
+	for found in found {
+		if let (Some(season), Some(episode)) = (found.season, found.episode) {
+			map.entry(season as u16).or_default().insert(episode as u16, tconst(&found.id));
+		}
+	}
+	Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_episode() {
+		let parsed: Episode = tsv::decode_line(1, "tt0664801\ttt0386676\t1\t12").unwrap();
+		assert_eq!(parsed, Episode {
+			title_id: "0664801".to_owned(),
+			parent_id: "0386676".to_owned(),
+			season: Some(1),
+			episode: Some(12),
+		});
+		let parsed: Episode = tsv::decode_line(1, "tt1135941\ttt0386676\t\\N\t\\N").unwrap();
+		assert_eq!((parsed.season, parsed.episode), (None, None));
+		assert!(tsv::decode_line::<Episode>(1, "tt0664801\ttt0386676\t1\t99999").is_err());
+	}
+}
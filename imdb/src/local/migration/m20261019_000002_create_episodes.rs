@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Episodes::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Episodes::Id).string().not_null().primary_key())
+					.col(ColumnDef::new(Episodes::ParentId).string().not_null())
+					.col(ColumnDef::new(Episodes::Season).integer())
+					.col(ColumnDef::new(Episodes::Episode).integer())
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("idx_episode_parent")
+					.table(Episodes::Table)
+					.col(Episodes::ParentId)
+					.col(Episodes::Season)
+					.col(Episodes::Episode)
+					.to_owned(),
+			)
+			.await?;
+
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.drop_index(Index::drop().name("idx_episode_parent").table(Episodes::Table).to_owned())
+			.await?;
+
+		manager.drop_table(Table::drop().table(Episodes::Table).to_owned()).await?;
+
+		Ok(())
+	}
+}
+
+#[derive(DeriveIden)]
+enum Episodes {
+	Table,
+	Id,
+	ParentId,
+	Season,
+	Episode,
+}
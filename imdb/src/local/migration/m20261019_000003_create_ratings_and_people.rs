@@ -0,0 +1,123 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Ratings::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Ratings::Id).string().not_null().primary_key())
+					.col(ColumnDef::new(Ratings::Average).double().not_null())
+					.col(ColumnDef::new(Ratings::Votes).integer().not_null())
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_table(
+				Table::create()
+					.table(Names::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Names::Id).string().not_null().primary_key())
+					.col(ColumnDef::new(Names::Name).string().not_null())
+					.col(ColumnDef::new(Names::BirthYear).integer())
+					.col(ColumnDef::new(Names::DeathYear).integer())
+					.col(ColumnDef::new(Names::Professions).string())
+					.col(ColumnDef::new(Names::KnownFor).string())
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("idx_name")
+					.table(Names::Table)
+					.col(Names::Name)
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_table(
+				Table::create()
+					.table(Principals::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Principals::TitleId).string().not_null())
+					.col(ColumnDef::new(Principals::Ordering).integer().not_null())
+					.col(ColumnDef::new(Principals::PersonId).string().not_null())
+					.col(ColumnDef::new(Principals::Category).string().not_null())
+					.col(ColumnDef::new(Principals::Job).string())
+					.col(ColumnDef::new(Principals::Characters).string())
+					.primary_key(Index::create().col(Principals::TitleId).col(Principals::Ordering))
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_index(
+				Index::create()
+					.if_not_exists()
+					.name("idx_principal_person")
+					.table(Principals::Table)
+					.col(Principals::PersonId)
+					.col(Principals::Category)
+					.to_owned(),
+			)
+			.await?;
+
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.drop_index(Index::drop().name("idx_principal_person").table(Principals::Table).to_owned())
+			.await?;
+		manager.drop_table(Table::drop().table(Principals::Table).to_owned()).await?;
+
+		manager
+			.drop_index(Index::drop().name("idx_name").table(Names::Table).to_owned())
+			.await?;
+		manager.drop_table(Table::drop().table(Names::Table).to_owned()).await?;
+
+		manager.drop_table(Table::drop().table(Ratings::Table).to_owned()).await?;
+
+		Ok(())
+	}
+}
+
+#[derive(DeriveIden)]
+enum Ratings {
+	Table,
+	Id,
+	Average,
+	Votes,
+}
+
+#[derive(DeriveIden)]
+enum Names {
+	Table,
+	Id,
+	Name,
+	BirthYear,
+	DeathYear,
+	Professions,
+	KnownFor,
+}
+
+#[derive(DeriveIden)]
+enum Principals {
+	Table,
+	TitleId,
+	Ordering,
+	PersonId,
+	Category,
+	Job,
+	Characters,
+}
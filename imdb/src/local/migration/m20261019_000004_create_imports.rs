@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Imports::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Imports::Dataset).string().not_null().primary_key())
+					.col(ColumnDef::new(Imports::Checksum).string().not_null())
+					.col(ColumnDef::new(Imports::Imported).big_integer().not_null())
+					.col(ColumnDef::new(Imports::Rows).big_integer().not_null())
+					.to_owned(),
+			)
+			.await?;
+
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager.drop_table(Table::drop().table(Imports::Table).to_owned()).await?;
+
+		Ok(())
+	}
+}
+
+#[derive(DeriveIden)]
+enum Imports {
+	Table,
+	Dataset,
+	Checksum,
+	Imported,
+	Rows,
+}
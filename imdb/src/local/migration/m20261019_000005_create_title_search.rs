@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.create_table(
+				Table::create()
+					.table(Basics::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Basics::Id).string().not_null().primary_key())
+					.col(ColumnDef::new(Basics::TitleType).string().not_null())
+					.col(ColumnDef::new(Basics::PrimaryTitle).string().not_null())
+					.col(ColumnDef::new(Basics::OriginalTitle).string().not_null())
+					.col(ColumnDef::new(Basics::IsAdult).boolean().not_null())
+					.col(ColumnDef::new(Basics::StartYear).integer())
+					.col(ColumnDef::new(Basics::EndYear).integer())
+					.col(ColumnDef::new(Basics::Runtime).integer())
+					.col(ColumnDef::new(Basics::Genres).string())
+					.to_owned(),
+			)
+			.await?;
+
+		manager
+			.create_table(
+				Table::create()
+					.table(Akas::Table)
+					.if_not_exists()
+					.col(ColumnDef::new(Akas::TitleId).string().not_null())
+					.col(ColumnDef::new(Akas::Ordering).integer().not_null())
+					.col(ColumnDef::new(Akas::Name).string().not_null())
+					.col(ColumnDef::new(Akas::Region).string())
+					.col(ColumnDef::new(Akas::Language).string())
+					.col(ColumnDef::new(Akas::Types).string())
+					.col(ColumnDef::new(Akas::Attributes).string())
+					.col(ColumnDef::new(Akas::IsOriginalTitle).boolean().not_null())
+					.primary_key(Index::create().col(Akas::TitleId).col(Akas::Ordering))
+					.to_owned(),
+			)
+			.await?;
+
+		// normalized names of the akas and the primary titles, filled by `search::rebuild_index`
+		manager
+			.get_connection()
+			.execute_unprepared(
+				"CREATE VIRTUAL TABLE IF NOT EXISTS title_search USING fts5(title_id UNINDEXED, name, tokenize = \
+				 'trigram')",
+			)
+			.await?;
+
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		manager
+			.get_connection()
+			.execute_unprepared("DROP TABLE IF EXISTS title_search")
+			.await?;
+		manager.drop_table(Table::drop().table(Akas::Table).to_owned()).await?;
+		manager.drop_table(Table::drop().table(Basics::Table).to_owned()).await?;
+
+		Ok(())
+	}
+}
+
+#[derive(DeriveIden)]
+enum Basics {
+	Table,
+	Id,
+	TitleType,
+	PrimaryTitle,
+	OriginalTitle,
+	IsAdult,
+	StartYear,
+	EndYear,
+	Runtime,
+	Genres,
+}
+
+#[derive(DeriveIden)]
+enum Akas {
+	Table,
+	TitleId,
+	Ordering,
+	Name,
+	Region,
+	Language,
+	Types,
+	Attributes,
+	IsOriginalTitle,
+}
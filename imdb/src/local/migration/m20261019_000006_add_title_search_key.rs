@@ -0,0 +1,31 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+	async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		// FTS5 tables can't be altered, the index is refilled by `search::rebuild_index`
+		let db = manager.get_connection();
+		db.execute_unprepared("DROP TABLE IF EXISTS title_search").await?;
+		db.execute_unprepared(
+			"CREATE VIRTUAL TABLE title_search USING fts5(title_id UNINDEXED, name UNINDEXED, key, tokenize = \
+			 'trigram')",
+		)
+		.await?;
+
+		Ok(())
+	}
+
+	async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+		let db = manager.get_connection();
+		db.execute_unprepared("DROP TABLE IF EXISTS title_search").await?;
+		db.execute_unprepared(
+			"CREATE VIRTUAL TABLE title_search USING fts5(title_id UNINDEXED, name, tokenize = 'trigram')",
+		)
+		.await?;
+
+		Ok(())
+	}
+}
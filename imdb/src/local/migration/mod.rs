@@ -1,12 +1,24 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20231030_000001_create_table;
+mod m20261019_000002_create_episodes;
+mod m20261019_000003_create_ratings_and_people;
+mod m20261019_000004_create_imports;
+mod m20261019_000005_create_title_search;
+mod m20261019_000006_add_title_search_key;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
 	fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-		vec![Box::new(m20231030_000001_create_table::Migration)]
+		vec![
+			Box::new(m20231030_000001_create_table::Migration),
+			Box::new(m20261019_000002_create_episodes::Migration),
+			Box::new(m20261019_000003_create_ratings_and_people::Migration),
+			Box::new(m20261019_000004_create_imports::Migration),
+			Box::new(m20261019_000005_create_title_search::Migration),
+			Box::new(m20261019_000006_add_title_search_key::Migration),
+		]
 	}
 }
@@ -1,25 +1,101 @@
+pub mod basics;
 pub mod entities;
+pub mod episode;
 pub mod migration;
+pub mod name;
+pub mod normalize;
+pub mod principal;
+pub mod rating;
+pub mod refresh;
+pub mod search;
+pub mod subset;
 pub mod title;
+pub mod translit;
+pub mod tsv;
 
-use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use std::{collections::HashSet, str::FromStr};
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, SqlxSqliteConnector, Statement};
 use sea_orm_migration::prelude::*;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::{
+	config::{DatabaseConfig, DatabaseLocation},
+	errors::{ImdbError, Result},
+	local::migration::MigratorTrait,
+};
+
+/// Rows inserted or ids bound by one statement, SQLite allows up to 32766 bound values.
+pub(crate) const BATCH_SIZE: usize = 1000;
 
-use crate::{errors::Result, local::migration::MigratorTrait};
+/// Connection pool of the configured database, the missing file is created when `create` is set.
+async fn connect(config: &DatabaseConfig, create: bool) -> Result<DatabaseConnection> {
+	let (options, pool) = match &config.location {
+		DatabaseLocation::File(path) => (
+			SqliteConnectOptions::new()
+				.filename(path)
+				.create_if_missing(create && !config.read_only),
+			SqlitePoolOptions::new().max_connections(config.pool_size),
+		),
+		// the in-memory database lives while its connection is open
+		DatabaseLocation::Memory => (
+			SqliteConnectOptions::from_str("sqlite::memory:")?,
+			SqlitePoolOptions::new()
+				.max_connections(1)
+				.idle_timeout(None)
+				.max_lifetime(None),
+		),
+	};
+	let mut options = options.read_only(config.read_only).synchronous(config.synchronous);
+	if !config.read_only {
+		options = options.journal_mode(config.journal_mode);
+	}
+	Ok(SqlxSqliteConnector::from_sqlx_sqlite_pool(pool.connect_with(options).await?))
+}
 
-pub async fn create_database(db_file_name: &str) -> Result<DatabaseConnection> {
-	let db_url = format!("sqlite://{}.sqlite?mode=rwc", db_file_name);
-	let connect_options = ConnectOptions::new(db_url).to_owned();
+/// Fails unless all the migrations are applied to the database, which is only read.
+pub async fn check_migrations(db: &DatabaseConnection, location: &DatabaseLocation) -> Result<()> {
+	let installed = db
+		.query_one(Statement::from_string(
+			DbBackend::Sqlite,
+			"SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'seaql_migrations'",
+		))
+		.await?
+		.is_some();
+	let mut applied = HashSet::new();
+	if installed {
+		let rows = db
+			.query_all(Statement::from_string(
+				DbBackend::Sqlite,
+				"SELECT version FROM seaql_migrations",
+			))
+			.await?;
+		for row in rows {
+			applied.insert(row.try_get::<String>("", "version")?);
+		}
+	}
+	let pending: Vec<String> = migration::Migrator::migrations()
+		.iter()
+		.map(|migration| migration.name().to_owned())
+		.filter(|name| !applied.contains(name))
+		.collect();
+	if pending.is_empty() {
+		Ok(())
+	} else {
+		Err(ImdbError::MigrationPendingError(location.to_string(), pending))
+	}
+}
 
-	let db = Database::connect(connect_options).await?;
+/// Opens the database creating it when it is missing and applies the pending migrations.
+pub async fn create_database(config: &DatabaseConfig) -> Result<DatabaseConnection> {
+	let db = connect(config, true).await?;
 	migration::Migrator::up(&db, None).await?;
 	Ok(db)
 }
 
-pub async fn open_database(db_file_name: &str) -> Result<DatabaseConnection> {
-	let db_url = format!("sqlite://{}.sqlite?mode=rwc", db_file_name);
-	let connect_options = ConnectOptions::new(db_url).to_owned();
-
-	let db = Database::connect(connect_options).await?;
+/// Opens the existing database, which must be at the latest migration.
+pub async fn open_database(config: &DatabaseConfig) -> Result<DatabaseConnection> {
+	let db = connect(config, false).await?;
+	check_migrations(&db, &config.location).await?;
 	Ok(db)
 }
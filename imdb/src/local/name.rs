@@ -0,0 +1,184 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use sea_orm::{
+	sea_query::{Expr, LikeExpr, OnConflict},
+	ActiveValue,
+	ColumnTrait,
+	ConnectionTrait,
+	DatabaseConnection,
+	EntityTrait,
+	QueryFilter,
+	QueryOrder,
+	QuerySelect,
+};
+
+use crate::{
+	errors::Result,
+	local::{
+		entities::names,
+		refresh::Table,
+		tsv::{self, join_array, split_array, Fields, Row},
+		BATCH_SIZE,
+	},
+};
+
+/// Row of `name.basics.tsv`, the person id is stored without the `nm` prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Name {
+	pub person_id: String,
+	pub name: String,
+	pub birth_year: Option<u16>,
+	pub death_year: Option<u16>,
+	pub professions: Vec<String>,
+	/// Tconsts of the best known titles.
+	pub known_for: Vec<String>,
+}
+
+impl Row for Name {
+	const COLUMNS: &'static [&'static str] = &[
+		"nconst",
+		"primaryName",
+		"birthYear",
+		"deathYear",
+		"primaryProfession",
+		"knownForTitles",
+	];
+
+	fn decode(fields: &mut Fields) -> Result<Self> {
+		Ok(Name {
+			person_id: fields.id("nm")?,
+			name: fields.required()?,
+			birth_year: fields.optional()?,
+			death_year: fields.optional()?,
+			professions: fields.array()?,
+			known_for: fields.array()?,
+		})
+	}
+}
+
+pub fn parse_gzip_file(file_path: &Path) -> Result<Vec<Name>> {
+	tsv::read_gzip_file(file_path)
+}
+
+impl From<names::Model> for Name {
+	fn from(model: names::Model) -> Self {
+		Name {
+			person_id: model.id,
+			name: model.name,
+			birth_year: model.birth_year.map(|year| year as u16),
+			death_year: model.death_year.map(|year| year as u16),
+			professions: split_array(model.professions),
+			known_for: split_array(model.known_for),
+		}
+	}
+}
+#[async_trait]
+impl Table for Name {
+	type Key = String;
+
+	const DATASET: &'static str = "name.basics";
+
+	fn key(&self) -> Self::Key {
+		self.person_id.clone()
+	}
+
+	fn tconst(&self) -> Option<String> {
+		None
+	}
+
+	async fn find<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<Vec<Self>> {
+		let found = names::Entity::find()
+			.filter(names::Column::Id.is_in(keys.iter().cloned()))
+			.all(db)
+			.await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn page<C: ConnectionTrait>(db: &C, after: Option<&Self::Key>, limit: u64) -> Result<Vec<Self>> {
+		let mut query = names::Entity::find();
+		if let Some(after) = after {
+			query = query.filter(names::Column::Id.gt(after.as_str()));
+		}
+		let found = query.order_by_asc(names::Column::Id).limit(limit).all(db).await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn upsert<C: ConnectionTrait>(db: &C, rows: &[Self]) -> Result<()> {
+		fill_names_table(db, rows).await
+	}
+
+	async fn delete<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<()> {
+		for batch in keys.chunks(BATCH_SIZE) {
+			names::Entity::delete_many()
+				.filter(names::Column::Id.is_in(batch.iter().cloned()))
+				.exec(db)
+				.await?;
+		}
+		Ok(())
+	}
+}
+
+/// Inserts the people, replacing the already imported ones.
+pub async fn fill_names_table<C: ConnectionTrait>(db: &C, names: &[Name]) -> Result<()> {
+	for batch in names.chunks(BATCH_SIZE) {
+		let models = batch.iter().map(|name| names::ActiveModel {
+			id: ActiveValue::Set(name.person_id.clone()),
+			name: ActiveValue::Set(name.name.clone()),
+			birth_year: ActiveValue::Set(name.birth_year.map(i32::from)),
+			death_year: ActiveValue::Set(name.death_year.map(i32::from)),
+			professions: ActiveValue::Set(join_array(&name.professions)),
+			known_for: ActiveValue::Set(join_array(&name.known_for)),
+		});
+		names::Entity::insert_many(models)
+			.on_conflict(
+				OnConflict::column(names::Column::Id)
+					.update_columns([
+						names::Column::Name,
+						names::Column::BirthYear,
+						names::Column::DeathYear,
+						names::Column::Professions,
+						names::Column::KnownFor,
+					])
+					.to_owned(),
+			)
+			.exec(db)
+			.await?;
+	}
+	Ok(())
+}
+
+/// People with the name, case insensitive for the latin letters only (SQLite `LIKE`).
+pub async fn find_people(db: &DatabaseConnection, name: &str) -> Result<Vec<names::Model>> {
+	let escaped = name.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+	Ok(names::Entity::find()
+		.filter(Expr::col((names::Entity, names::Column::Name)).like(LikeExpr::new(escaped).escape('\\')))
+		.all(db)
+		.await?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_person() {
+		let parsed: Name = tsv::decode_line(
+			1,
+			"nm0050256\tAleksei Balabanov\t1959\t2013\tdirector,writer,producer\ttt0124315,tt0238883",
+		)
+		.unwrap();
+		assert_eq!(parsed, Name {
+			person_id: "0050256".to_owned(),
+			name: "Aleksei Balabanov".to_owned(),
+			birth_year: Some(1959),
+			death_year: Some(2013),
+			professions: vec!["director".to_owned(), "writer".to_owned(), "producer".to_owned()],
+			known_for: vec!["tt0124315".to_owned(), "tt0238883".to_owned()],
+		});
+		let parsed: Name = tsv::decode_line(1, "nm0000001\tSomebody\t\\N\t\\N\t\\N\t\\N").unwrap();
+		assert_eq!(parsed.birth_year, None);
+		assert!(parsed.professions.is_empty() && parsed.known_for.is_empty());
+		assert_eq!(tsv::join_array(&parsed.professions), None);
+	}
+}
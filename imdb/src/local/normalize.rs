@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::local::translit::fold;
+
+/// Leading articles moved to the end, so "The Matrix" and "Matrix, The" normalize the same.
+const ARTICLES: [&str; 16] = [
+	"the", "a", "an", "le", "la", "les", "l", "der", "die", "das", "el", "los", "las", "il", "lo", "gli",
+];
+
+/// Apostrophes and the romanized soft and hard signs, dropped without splitting the word.
+const SIGN_MARKS: [char; 5] = ['\'', '’', '`', 'ʹ', 'ʺ'];
+
+/// Title for the search index and queries: lowercase without diacritics and punctuation, `ё` is `е`, the
+/// leading article is moved to the end.
+pub fn normalize(title: &str) -> String {
+	let plain: String = title
+		.nfd()
+		.filter(|c| !is_combining_mark(*c) && !SIGN_MARKS.contains(c))
+		.flat_map(char::to_lowercase)
+		.map(|c| if c.is_alphanumeric() { c } else { ' ' })
+		.collect();
+	let mut words: Vec<&str> = plain.split_whitespace().collect();
+	if words.len() > 1 && ARTICLES.contains(&words[0]) {
+		words.rotate_left(1);
+	}
+	words.join(" ")
+}
+
+/// Normalized title folded to the same Latin key for the Cyrillic and its romanizations, see [`fold`].
+pub fn search_key(title: &str) -> String {
+	fold(&normalize(title))
+}
+
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+	let padded: Vec<char> = format!("  {text} ").chars().collect();
+	padded.windows(3).map(|window| [window[0], window[1], window[2]]).collect()
+}
+
+/// Dice coefficient of the trigrams of the normalized titles, from 0 for nothing common to 1 for equal.
+pub fn similarity(a: &str, b: &str) -> f64 {
+	let (a, b) = (trigrams(a), trigrams(b));
+	if a.is_empty() || b.is_empty() {
+		return 0.0;
+	}
+	2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_normalize() {
+		assert_eq!(normalize("The Matrix"), "matrix the");
+		assert_eq!(normalize("Matrix, The"), "matrix the");
+		assert_eq!(normalize("Amélie"), "amelie");
+		assert_eq!(normalize("Ёлки-палки!"), "елки палки");
+		assert_eq!(normalize("Léon: The Professional"), "leon the professional");
+		assert_eq!(normalize("The"), "the");
+		assert_eq!(normalize("  "), "");
+		assert_eq!(normalize("Sud'ba"), "sudba");
+		assert_eq!(normalize("Sudʹba"), "sudba");
+	}
+
+	#[test]
+	fn test_search_key() {
+		assert_eq!(search_key("Ironiya sudby"), search_key("Ирония судьбы"));
+		assert_eq!(search_key("Ironiâ sudʹby"), search_key("Ирония судьбы"));
+		assert_eq!(search_key("Ёлки"), search_key("Yolki"));
+		assert_eq!(search_key("Zhmurki"), search_key("Жмурки"));
+	}
+
+	#[test]
+	fn test_similarity() {
+		assert_eq!(similarity("brat", "brat"), 1.0);
+		assert!(similarity("brat", "brat 2") > similarity("brat", "brother"));
+		assert_eq!(similarity("", "brat"), 0.0);
+	}
+}
@@ -0,0 +1,225 @@
+use std::{
+	collections::{BTreeSet, HashSet},
+	path::Path,
+};
+
+use async_trait::async_trait;
+use sea_orm::{
+	sea_query::OnConflict,
+	ActiveValue,
+	ColumnTrait,
+	Condition,
+	ConnectionTrait,
+	DatabaseConnection,
+	EntityTrait,
+	QueryFilter,
+	QueryOrder,
+	QuerySelect,
+};
+
+use crate::{
+	errors::Result,
+	local::{
+		entities::principals,
+		name::find_people,
+		refresh::Table,
+		title::{id, tconst},
+		tsv::{self, Fields, Row},
+		BATCH_SIZE,
+	},
+};
+
+/// Row of `title.principals.tsv`, the ids are stored without prefixes. Category is `director`, `actor`,
+/// `writer` and so on, characters are a JSON array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+	pub title_id: String,
+	pub ordering: u32,
+	pub person_id: String,
+	pub category: String,
+	pub job: Option<String>,
+	pub characters: Option<String>,
+}
+
+impl Row for Principal {
+	const COLUMNS: &'static [&'static str] =
+		&["tconst", "ordering", "nconst", "category", "job", "characters"];
+
+	fn decode(fields: &mut Fields) -> Result<Self> {
+		Ok(Principal {
+			title_id: fields.id("tt")?,
+			ordering: fields.required()?,
+			person_id: fields.id("nm")?,
+			category: fields.required()?,
+			job: fields.optional()?,
+			characters: fields.optional()?,
+		})
+	}
+}
+
+pub fn parse_gzip_file(file_path: &Path) -> Result<Vec<Principal>> {
+	tsv::read_gzip_file(file_path)
+}
+
+impl From<principals::Model> for Principal {
+	fn from(model: principals::Model) -> Self {
+		Principal {
+			title_id: model.title_id,
+			ordering: model.ordering as u32,
+			person_id: model.person_id,
+			category: model.category,
+			job: model.job,
+			characters: model.characters,
+		}
+	}
+}
+#[async_trait]
+impl Table for Principal {
+	type Key = (String, u32);
+
+	const DATASET: &'static str = "title.principals";
+
+	fn key(&self) -> Self::Key {
+		(self.title_id.clone(), self.ordering)
+	}
+
+	fn tconst(&self) -> Option<String> {
+		Some(tconst(&self.title_id))
+	}
+
+	async fn find<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<Vec<Self>> {
+		let mut found = vec![];
+		for batch in keys.chunks(BATCH_SIZE / 2) {
+			let condition = batch.iter().fold(Condition::any(), |condition, (title_id, ordering)| {
+				condition.add(
+					principals::Column::TitleId
+						.eq(title_id.as_str())
+						.and(principals::Column::Ordering.eq(*ordering as i32)),
+				)
+			});
+			found.extend(
+				principals::Entity::find()
+					.filter(condition)
+					.all(db)
+					.await?
+					.into_iter()
+					.map(Self::from),
+			);
+		}
+		Ok(found)
+	}
+
+	async fn page<C: ConnectionTrait>(db: &C, after: Option<&Self::Key>, limit: u64) -> Result<Vec<Self>> {
+		let mut query = principals::Entity::find();
+		if let Some((title_id, ordering)) = after {
+			query = query.filter(
+				Condition::any().add(principals::Column::TitleId.gt(title_id.as_str())).add(
+					principals::Column::TitleId
+						.eq(title_id.as_str())
+						.and(principals::Column::Ordering.gt(*ordering as i32)),
+				),
+			);
+		}
+		let found = query
+			.order_by_asc(principals::Column::TitleId)
+			.order_by_asc(principals::Column::Ordering)
+			.limit(limit)
+			.all(db)
+			.await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn upsert<C: ConnectionTrait>(db: &C, rows: &[Self]) -> Result<()> {
+		fill_principals_table(db, rows).await
+	}
+
+	async fn delete<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<()> {
+		for batch in keys.chunks(BATCH_SIZE / 2) {
+			let condition = batch.iter().fold(Condition::any(), |condition, (title_id, ordering)| {
+				condition.add(
+					principals::Column::TitleId
+						.eq(title_id.as_str())
+						.and(principals::Column::Ordering.eq(*ordering as i32)),
+				)
+			});
+			principals::Entity::delete_many().filter(condition).exec(db).await?;
+		}
+		Ok(())
+	}
+}
+
+/// Inserts the principals, replacing the already imported ones.
+pub async fn fill_principals_table<C: ConnectionTrait>(db: &C, principals: &[Principal]) -> Result<()> {
+	for batch in principals.chunks(BATCH_SIZE) {
+		let models = batch.iter().map(|principal| principals::ActiveModel {
+			title_id: ActiveValue::Set(principal.title_id.clone()),
+			ordering: ActiveValue::Set(principal.ordering as i32),
+			person_id: ActiveValue::Set(principal.person_id.clone()),
+			category: ActiveValue::Set(principal.category.clone()),
+			job: ActiveValue::Set(principal.job.clone()),
+			characters: ActiveValue::Set(principal.characters.clone()),
+		});
+		principals::Entity::insert_many(models)
+			.on_conflict(
+				OnConflict::columns([principals::Column::TitleId, principals::Column::Ordering])
+					.update_columns([
+						principals::Column::PersonId,
+						principals::Column::Category,
+						principals::Column::Job,
+						principals::Column::Characters,
+					])
+					.to_owned(),
+			)
+			.exec(db)
+			.await?;
+	}
+	Ok(())
+}
+
+/// Tconsts of the titles where the person with the name is in the category, e.g. the films directed by
+/// somebody. Only the titles `within` the given tconsts (e.g. the library titles) are returned, if any.
+pub async fn titles_with_person(
+	db: &DatabaseConnection,
+	name: &str,
+	category: &str,
+	within: Option<&[String]>,
+) -> Result<Vec<String>> {
+	let people: Vec<String> = find_people(db, name).await?.into_iter().map(|person| person.id).collect();
+	let mut titles = BTreeSet::new();
+	for batch in people.chunks(BATCH_SIZE) {
+		let found = principals::Entity::find()
+			.filter(principals::Column::PersonId.is_in(batch.iter().cloned()))
+			.filter(principals::Column::Category.eq(category))
+			.all(db)
+			.await?;
+		titles.extend(found.into_iter().map(|principal| principal.title_id));
+	}
+
+	let within: Option<HashSet<&str>> = within.map(|within| within.iter().map(|title| id(title)).collect());
+	Ok(titles
+		.into_iter()
+		.filter(|title| within.as_ref().is_none_or(|within| within.contains(title.as_str())))
+		.map(|title| tconst(&title))
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_principal() {
+		let parsed: Principal = tsv::decode_line(1, "tt0124315\t1\tnm0050256\tdirector\t\\N\t\\N").unwrap();
+		assert_eq!(parsed, Principal {
+			title_id: "0124315".to_owned(),
+			ordering: 1,
+			person_id: "0050256".to_owned(),
+			category: "director".to_owned(),
+			job: None,
+			characters: None,
+		});
+		let parsed: Principal =
+			tsv::decode_line(1, "tt0124315\t2\tnm0091800\tactor\t\\N\t[\"Danila Bagrov\"]").unwrap();
+		assert_eq!(parsed.characters, Some("[\"Danila Bagrov\"]".to_owned()));
+	}
+}
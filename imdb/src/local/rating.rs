@@ -0,0 +1,203 @@
+use std::{collections::HashMap, path::Path};
+
+use async_trait::async_trait;
+use sea_orm::{
+	sea_query::OnConflict,
+	ActiveValue,
+	ColumnTrait,
+	ConnectionTrait,
+	DatabaseConnection,
+	EntityTrait,
+	QueryFilter,
+	QueryOrder,
+	QuerySelect,
+};
+
+use crate::{
+	errors::Result,
+	local::{
+		entities::ratings,
+		refresh::Table,
+		title::{id, tconst},
+		tsv::{self, Fields, Row},
+		BATCH_SIZE,
+	},
+};
+
+/// Votes of the most popular titles, they get the full popularity prior.
+const POPULAR_VOTES: f64 = 1_000_000.0;
+
+/// Row of `title.ratings.tsv`, the title id is stored without the `tt` prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+	pub title_id: String,
+	pub average: f64,
+	pub votes: u32,
+}
+
+impl Rating {
+	pub fn tconst(&self) -> String {
+		tconst(&self.title_id)
+	}
+
+	/// Popularity prior of the title for matching, see [`popularity`].
+	pub fn popularity(&self) -> f64 {
+		popularity(self.votes)
+	}
+}
+
+impl From<ratings::Model> for Rating {
+	fn from(model: ratings::Model) -> Self {
+		Rating {
+			title_id: model.id,
+			average: model.average,
+			votes: model.votes as u32,
+		}
+	}
+}
+
+/// Prior weight of a title with the number of votes from 0 for unknown titles to 1 for the most popular
+/// ones, on the log scale so a thousand votes weigh half of a million.
+pub fn popularity(votes: u32) -> f64 {
+	((f64::from(votes) + 1.0).log10() / POPULAR_VOTES.log10()).min(1.0)
+}
+
+impl Row for Rating {
+	const COLUMNS: &'static [&'static str] = &["tconst", "averageRating", "numVotes"];
+
+	fn decode(fields: &mut Fields) -> Result<Self> {
+		Ok(Rating {
+			title_id: fields.id("tt")?,
+			average: fields.required()?,
+			votes: fields.required()?,
+		})
+	}
+}
+
+pub fn parse_gzip_file(file_path: &Path) -> Result<Vec<Rating>> {
+	tsv::read_gzip_file(file_path)
+}
+
+#[async_trait]
+impl Table for Rating {
+	type Key = String;
+
+	const DATASET: &'static str = "title.ratings";
+
+	fn key(&self) -> Self::Key {
+		self.title_id.clone()
+	}
+
+	fn tconst(&self) -> Option<String> {
+		Some(self.tconst())
+	}
+
+	async fn find<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<Vec<Self>> {
+		let found = ratings::Entity::find()
+			.filter(ratings::Column::Id.is_in(keys.iter().cloned()))
+			.all(db)
+			.await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn page<C: ConnectionTrait>(db: &C, after: Option<&Self::Key>, limit: u64) -> Result<Vec<Self>> {
+		let mut query = ratings::Entity::find();
+		if let Some(after) = after {
+			query = query.filter(ratings::Column::Id.gt(after.as_str()));
+		}
+		let found = query.order_by_asc(ratings::Column::Id).limit(limit).all(db).await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn upsert<C: ConnectionTrait>(db: &C, rows: &[Self]) -> Result<()> {
+		fill_ratings_table(db, rows).await
+	}
+
+	async fn delete<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<()> {
+		for batch in keys.chunks(BATCH_SIZE) {
+			ratings::Entity::delete_many()
+				.filter(ratings::Column::Id.is_in(batch.iter().cloned()))
+				.exec(db)
+				.await?;
+		}
+		Ok(())
+	}
+}
+
+/// Inserts the ratings, replacing the already imported ones.
+pub async fn fill_ratings_table<C: ConnectionTrait>(db: &C, ratings: &[Rating]) -> Result<()> {
+	for batch in ratings.chunks(BATCH_SIZE) {
+		let models = batch.iter().map(|rating| ratings::ActiveModel {
+			id: ActiveValue::Set(rating.title_id.clone()),
+			average: ActiveValue::Set(rating.average),
+			votes: ActiveValue::Set(rating.votes as i32),
+		});
+		ratings::Entity::insert_many(models)
+			.on_conflict(
+				OnConflict::column(ratings::Column::Id)
+					.update_columns([ratings::Column::Average, ratings::Column::Votes])
+					.to_owned(),
+			)
+			.exec(db)
+			.await?;
+	}
+	Ok(())
+}
+
+/// Ratings of the titles by their tconsts, the titles without ratings are absent.
+pub async fn find_ratings(db: &DatabaseConnection, titles: &[String]) -> Result<HashMap<String, Rating>> {
+	let mut found = HashMap::new();
+	for batch in titles.chunks(BATCH_SIZE) {
+		let models = ratings::Entity::find()
+			.filter(ratings::Column::Id.is_in(batch.iter().map(|title| id(title))))
+			.all(db)
+			.await?;
+		for model in models {
+			let rating = Rating::from(model);
+			found.insert(rating.tconst(), rating);
+		}
+	}
+	Ok(found)
+}
+
+/// Best rated of the titles (e.g. the library titles not watched yet) with at least `min_votes` votes, the
+/// more voted first among the equally rated.
+pub async fn top_rated(
+	db: &DatabaseConnection,
+	titles: &[String],
+	min_votes: u32,
+	limit: usize,
+) -> Result<Vec<Rating>> {
+	let mut top: Vec<Rating> = find_ratings(db, titles)
+		.await?
+		.into_values()
+		.filter(|rating| rating.votes >= min_votes)
+		.collect();
+	top.sort_by(|a, b| b.average.total_cmp(&a.average).then(b.votes.cmp(&a.votes)));
+	top.truncate(limit);
+	Ok(top)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_rating() {
+		let parsed: Rating = tsv::decode_line(1, "tt0124315\t7.8\t41234").unwrap();
+		assert_eq!(parsed, Rating {
+			title_id: "0124315".to_owned(),
+			average: 7.8,
+			votes: 41234,
+		});
+		assert!(tsv::decode_line::<Rating>(1, "tt0124315\t\\N\t41234").is_err());
+	}
+
+	#[test]
+	fn test_popularity() {
+		assert_eq!(popularity(0), 0.0);
+		assert!((popularity(999) - 0.5).abs() < 1e-9);
+		assert!(popularity(40_000) < popularity(700_000));
+		assert_eq!(popularity(5_000_000), 1.0);
+	}
+}
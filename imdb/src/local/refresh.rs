@@ -0,0 +1,327 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs::File,
+	hash::Hash,
+	io::{self, BufReader},
+	path::Path,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use log::{info, warn};
+use sea_orm::{
+	sea_query::OnConflict,
+	ActiveValue,
+	ConnectionTrait,
+	DatabaseConnection,
+	EntityTrait,
+	Statement,
+	TransactionTrait,
+	Value,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+	errors::{ImdbError, Result},
+	local::{
+		basics::TitleBasics,
+		entities::imports,
+		episode::Episode,
+		name::Name,
+		principal::Principal,
+		rating::Rating,
+		search,
+		title::Title,
+		tsv::{self, Dataset},
+		BATCH_SIZE,
+	},
+};
+
+/// Temporary table of the keys found in the dump, the imported rows missing from it are deleted.
+const STAGING_TABLE: &str = "refresh_keys";
+
+/// Key of the row, the id and the ordering (0 for the rows keyed by the id alone) in the staging table.
+pub trait RowKey: Clone + Eq + Hash + Send + Sync {
+	fn parts(&self) -> (&str, u32);
+}
+
+impl RowKey for String {
+	fn parts(&self) -> (&str, u32) {
+		(self, 0)
+	}
+}
+
+impl RowKey for (String, u32) {
+	fn parts(&self) -> (&str, u32) {
+		(&self.0, self.1)
+	}
+}
+
+/// Dataset imported into a table, its rows keyed by the tconst (and ordering) for the refresh.
+#[async_trait]
+pub trait Table: tsv::Row + Clone + PartialEq + Send + Sync + 'static {
+	type Key: RowKey;
+
+	/// Dataset name in the import log, the file name without `.tsv.gz`.
+	const DATASET: &'static str;
+
+	fn key(&self) -> Self::Key;
+
+	/// Tconst of the title the row belongs to, the rows of the titles matched in the library are kept.
+	fn tconst(&self) -> Option<String>;
+
+	/// Imported rows with the keys.
+	async fn find<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<Vec<Self>>;
+
+	/// Imported rows following the key (from the first one without it) in the order of the keys.
+	async fn page<C: ConnectionTrait>(db: &C, after: Option<&Self::Key>, limit: u64) -> Result<Vec<Self>>;
+
+	/// Inserts the rows, replacing the existing ones with the same keys.
+	async fn upsert<C: ConnectionTrait>(db: &C, rows: &[Self]) -> Result<()>;
+
+	async fn delete<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<()>;
+}
+
+/// Numbers of the applied changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Refresh {
+	pub inserted: usize,
+	pub updated: usize,
+	pub deleted: usize,
+	/// Rows absent from the dump but kept for the matched titles.
+	pub kept: usize,
+}
+
+impl Refresh {
+	pub fn is_empty(&self) -> bool {
+		*self == Refresh::default()
+	}
+}
+
+fn statement<C: ConnectionTrait>(db: &C, sql: String, values: Vec<Value>) -> Statement {
+	Statement::from_sql_and_values(db.get_database_backend(), sql, values)
+}
+
+/// Adds the keys of the dump rows to the staging table.
+async fn stage<C: ConnectionTrait, K: RowKey>(db: &C, keys: &[K]) -> Result<()> {
+	for batch in keys.chunks(BATCH_SIZE / 2) {
+		let mut values = Vec::with_capacity(batch.len() * 2);
+		for key in batch {
+			let (id, ordering) = key.parts();
+			values.push(id.into());
+			values.push(i64::from(ordering).into());
+		}
+		let rows = vec!["(?, ?)"; batch.len()].join(", ");
+		let sql = format!("INSERT OR IGNORE INTO {STAGING_TABLE} (id, ordering) VALUES {rows}");
+		db.execute(statement(db, sql, values)).await?;
+	}
+	Ok(())
+}
+
+/// Staged keys between the first and the last key, inclusive.
+async fn staged<C: ConnectionTrait, K: RowKey>(
+	db: &C,
+	first: &K,
+	last: &K,
+) -> Result<HashSet<(String, u32)>> {
+	let ((first_id, first_ordering), (last_id, last_ordering)) = (first.parts(), last.parts());
+	let sql = format!(
+		"SELECT id, ordering FROM {STAGING_TABLE} WHERE (id, ordering) >= (?, ?) AND (id, ordering) <= (?, ?)"
+	);
+	let values = vec![
+		first_id.into(),
+		i64::from(first_ordering).into(),
+		last_id.into(),
+		i64::from(last_ordering).into(),
+	];
+	let mut keys = HashSet::new();
+	for row in db.query_all(statement(db, sql, values)).await? {
+		let ordering: i64 = row.try_get("", "ordering")?;
+		keys.insert((row.try_get("", "id")?, ordering as u32));
+	}
+	Ok(keys)
+}
+
+/// Inserts the new and the changed rows of the dump batch and stages their keys.
+async fn apply_batch<T: Table, C: ConnectionTrait>(
+	db: &C,
+	rows: Vec<T>,
+	refresh: &mut Refresh,
+) -> Result<()> {
+	let keys: Vec<T::Key> = rows.iter().map(T::key).collect();
+	let existing: HashMap<T::Key, T> =
+		T::find(db, &keys).await?.into_iter().map(|row| (row.key(), row)).collect();
+	let mut changed = vec![];
+	for row in rows {
+		match existing.get(&row.key()) {
+			None => refresh.inserted += 1,
+			Some(old) if *old != row => refresh.updated += 1,
+			Some(_) => continue,
+		}
+		changed.push(row);
+	}
+	T::upsert(db, &changed).await?;
+	stage(db, &keys).await
+}
+
+/// Deletes the imported rows missing from the staged keys page by page, except the rows of the `keep`
+/// tconsts.
+async fn delete_missing<T: Table, C: ConnectionTrait>(
+	db: &C,
+	keep: &HashSet<String>,
+	refresh: &mut Refresh,
+) -> Result<()> {
+	let mut after = None;
+	loop {
+		let page = T::page(db, after.as_ref(), BATCH_SIZE as u64).await?;
+		let (Some(first), Some(last)) = (page.first(), page.last()) else {
+			return Ok(());
+		};
+		let staged = staged(db, &first.key(), &last.key()).await?;
+		after = Some(last.key());
+		let mut deleted = vec![];
+		for row in page {
+			let key = row.key();
+			let (id, ordering) = key.parts();
+			if staged.contains(&(id.to_owned(), ordering)) {
+				continue;
+			}
+			if row.tconst().is_some_and(|tconst| keep.contains(&tconst)) {
+				refresh.kept += 1;
+			} else {
+				deleted.push(key);
+			}
+		}
+		T::delete(db, &deleted).await?;
+		refresh.deleted += deleted.len();
+	}
+}
+
+/// SHA-256 of the dump file.
+pub fn checksum(file_path: &Path) -> Result<String> {
+	let mut hasher = Sha256::new();
+	io::copy(&mut BufReader::new(File::open(file_path)?), &mut hasher)?;
+	Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Last import of the dataset.
+pub async fn last_import(db: &DatabaseConnection, dataset: &str) -> Result<Option<imports::Model>> {
+	Ok(imports::Entity::find_by_id(dataset).one(db).await?)
+}
+
+/// Imports the gzipped dump applying only the changes against the already imported rows. The dump is read
+/// and compared batch by batch, its keys are staged in a temporary table to find the deleted rows, so neither
+/// the dump nor the table is loaded whole. The rows of the `keep` tconsts (e.g. matched in the library) are
+/// never deleted, so the matches stay valid when a title disappears from the dump. The dump with the checksum
+/// of the last import is skipped, returning none.
+pub async fn refresh<T: Table>(
+	db: &DatabaseConnection,
+	file_path: &Path,
+	keep: &HashSet<String>,
+) -> Result<Option<Refresh>> {
+	let checksum = checksum(file_path)?;
+	if last_import(db, T::DATASET)
+		.await?
+		.is_some_and(|import| import.checksum == checksum)
+	{
+		info!("{} is up to date", T::DATASET);
+		return Ok(None);
+	}
+
+	// the temporary table lives in the connection of the transaction
+	let transaction = db.begin().await?;
+	transaction
+		.execute_unprepared(&format!(
+			"CREATE TEMP TABLE IF NOT EXISTS {STAGING_TABLE} (id TEXT NOT NULL, ordering INTEGER NOT NULL, \
+			 PRIMARY KEY (id, ordering)) WITHOUT ROWID"
+		))
+		.await?;
+	transaction.execute_unprepared(&format!("DELETE FROM {STAGING_TABLE}")).await?;
+
+	let mut refresh = Refresh::default();
+	let mut row_count = 0;
+	let mut batch = Vec::with_capacity(BATCH_SIZE);
+	for row in Dataset::<T, _>::open_gzip(file_path)? {
+		match row {
+			Ok(row) => batch.push(row),
+			Err(err @ ImdbError::IoError(_)) => return Err(err),
+			Err(err) => warn!("Skipping the row of {}: {err}", file_path.display()),
+		}
+		if batch.len() == BATCH_SIZE {
+			row_count += batch.len();
+			apply_batch(&transaction, std::mem::take(&mut batch), &mut refresh).await?;
+		}
+	}
+	row_count += batch.len();
+	apply_batch(&transaction, batch, &mut refresh).await?;
+	delete_missing::<T, _>(&transaction, keep, &mut refresh).await?;
+	transaction.execute_unprepared(&format!("DROP TABLE {STAGING_TABLE}")).await?;
+	info!("Refreshed {}: {refresh:?}", T::DATASET);
+
+	let imported = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|now| now.as_secs())
+		.unwrap_or_default();
+	imports::Entity::insert(imports::ActiveModel {
+		dataset: ActiveValue::Set(T::DATASET.to_owned()),
+		checksum: ActiveValue::Set(checksum),
+		imported: ActiveValue::Set(imported as i64),
+		rows: ActiveValue::Set(row_count as i64),
+	})
+	.on_conflict(
+		OnConflict::column(imports::Column::Dataset)
+			.update_columns([
+				imports::Column::Checksum,
+				imports::Column::Imported,
+				imports::Column::Rows,
+			])
+			.to_owned(),
+	)
+	.exec(&transaction)
+	.await?;
+	transaction.commit().await?;
+
+	Ok(Some(refresh))
+}
+
+/// Refreshes the dataset from its `{DATASET}.tsv.gz` dump in the folder, the missing dump is skipped.
+async fn refresh_dump<T: Table>(
+	db: &DatabaseConnection,
+	dumps: &Path,
+	keep: &HashSet<String>,
+) -> Result<Option<Refresh>> {
+	let file_path = dumps.join(format!("{}.tsv.gz", T::DATASET));
+	if !file_path.exists() {
+		info!("{} is missing, skipped", file_path.display());
+		return Ok(None);
+	}
+	refresh::<T>(db, &file_path, keep).await
+}
+
+/// Refreshes all the datasets from the folder of the dumps and rebuilds the search index when the titles
+/// have changed. Returns the changes by the dataset name, the skipped datasets are left out.
+pub async fn refresh_dumps(
+	db: &DatabaseConnection,
+	dumps: &Path,
+	keep: &HashSet<String>,
+) -> Result<Vec<(&'static str, Refresh)>> {
+	let refreshed = [
+		(TitleBasics::DATASET, refresh_dump::<TitleBasics>(db, dumps, keep).await?),
+		(Title::DATASET, refresh_dump::<Title>(db, dumps, keep).await?),
+		(Episode::DATASET, refresh_dump::<Episode>(db, dumps, keep).await?),
+		(Rating::DATASET, refresh_dump::<Rating>(db, dumps, keep).await?),
+		(Principal::DATASET, refresh_dump::<Principal>(db, dumps, keep).await?),
+		(Name::DATASET, refresh_dump::<Name>(db, dumps, keep).await?),
+	];
+	let refreshed: Vec<(&'static str, Refresh)> = refreshed
+		.into_iter()
+		.filter_map(|(dataset, refresh)| Some((dataset, refresh?)))
+		.collect();
+	if refreshed.iter().any(|(dataset, refresh)| {
+		[TitleBasics::DATASET, Title::DATASET].contains(dataset) && !refresh.is_empty()
+	}) {
+		let indexed = search::rebuild_index(db).await?;
+		info!("Indexed {indexed} titles");
+	}
+	Ok(refreshed)
+}
@@ -0,0 +1,198 @@
+use std::{
+	collections::{HashMap, HashSet},
+	ops::RangeInclusive,
+};
+
+use sea_orm::{
+	ConnectionTrait,
+	DatabaseConnection,
+	DbBackend,
+	EntityTrait,
+	PaginatorTrait,
+	Statement,
+	TransactionTrait,
+	Value,
+};
+
+use crate::{
+	errors::Result,
+	local::{
+		entities::{akas, basics},
+		normalize::{normalize, search_key, similarity},
+		title::tconst,
+		BATCH_SIZE,
+	},
+};
+
+/// Rows fetched by the full text index before the similarity ranking.
+const CANDIDATES: u64 = 200;
+/// Shortest word the trigram index can match.
+const MIN_WORD_CHARS: usize = 3;
+
+/// Title type and year window of the searched titles, no filter by default.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchFilter {
+	years: Option<RangeInclusive<u16>>,
+	title_types: Vec<String>,
+}
+
+impl SearchFilter {
+	pub fn with_years(mut self, years: RangeInclusive<u16>) -> Self {
+		self.years = Some(years);
+		self
+	}
+
+	/// Titles started within the `window` years of the year, e.g. of the release name.
+	pub fn with_year(self, year: u16, window: u16) -> Self {
+		self.with_years(year.saturating_sub(window)..=year.saturating_add(window))
+	}
+
+	/// IMDb title types like `movie`, `tvMovie` or `tvSeries`.
+	pub fn with_title_types(mut self, title_types: &[&str]) -> Self {
+		self.title_types = title_types.iter().map(|title_type| title_type.to_string()).collect();
+		self
+	}
+}
+
+/// Found title with the best matching of its names.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TitleMatch {
+	pub tconst: String,
+	pub primary_title: Option<String>,
+	pub title_type: Option<String>,
+	pub year: Option<u16>,
+	/// Normalized name which matched.
+	pub name: String,
+	/// Trigram similarity of the search keys of the query and name, see [`similarity`] and [`search_key`].
+	pub similarity: f64,
+}
+
+async fn insert_names<C: ConnectionTrait>(db: &C, names: &[(String, String)]) -> Result<()> {
+	for batch in names.chunks(BATCH_SIZE) {
+		let placeholders = vec!["(?, ?, ?)"; batch.len()].join(", ");
+		let values = batch.iter().flat_map(|(title_id, name)| {
+			[
+				Value::from(title_id.as_str()),
+				Value::from(name.as_str()),
+				Value::from(search_key(name)),
+			]
+		});
+		db.execute(Statement::from_sql_and_values(
+			DbBackend::Sqlite,
+			format!("INSERT INTO title_search (title_id, name, key) VALUES {placeholders}"),
+			values,
+		))
+		.await?;
+	}
+	Ok(())
+}
+
+/// Refills the search index with the normalized primary, original and aka titles and their search keys,
+/// returns the number of the indexed names.
+pub async fn rebuild_index(db: &DatabaseConnection) -> Result<usize> {
+	let transaction = db.begin().await?;
+	transaction.execute_unprepared("DELETE FROM title_search").await?;
+	let mut indexed = 0;
+
+	let mut pages = basics::Entity::find().paginate(&transaction, BATCH_SIZE as u64 * 10);
+	while let Some(page) = pages.fetch_and_next().await? {
+		let mut names = vec![];
+		for title in page {
+			let primary = normalize(&title.primary_title);
+			let original = normalize(&title.original_title);
+			if original != primary {
+				names.push((title.id.clone(), original));
+			}
+			names.push((title.id, primary));
+		}
+		insert_names(&transaction, &names).await?;
+		indexed += names.len();
+	}
+
+	let mut pages = akas::Entity::find().paginate(&transaction, BATCH_SIZE as u64 * 10);
+	while let Some(page) = pages.fetch_and_next().await? {
+		let names: HashSet<(String, String)> =
+			page.into_iter().map(|aka| (aka.title_id, normalize(&aka.name))).collect();
+		let names: Vec<_> = names.into_iter().collect();
+		insert_names(&transaction, &names).await?;
+		indexed += names.len();
+	}
+
+	transaction.commit().await?;
+	Ok(indexed)
+}
+
+/// Titles with the names similar to the query, the most similar first. The full text index finds the names
+/// sharing words with the query, which are then ranked by the trigram similarity. Both are done on the search
+/// keys, so Cyrillic queries find the romanized names and the other way round.
+pub async fn search_titles(
+	db: &DatabaseConnection,
+	query: &str,
+	filter: &SearchFilter,
+	limit: usize,
+) -> Result<Vec<TitleMatch>> {
+	let query = search_key(query);
+	if query.is_empty() {
+		return Ok(vec![]);
+	}
+	let words: Vec<String> = query
+		.split(' ')
+		.filter(|word| word.chars().count() >= MIN_WORD_CHARS)
+		.map(|word| format!("\"{word}\""))
+		.collect();
+	let (mut sql, mut values) = if words.is_empty() {
+		// too short for the trigrams
+		("s.key = ?".to_owned(), vec![Value::from(query.as_str())])
+	} else {
+		("s.key MATCH ?".to_owned(), vec![Value::from(words.join(" OR "))])
+	};
+	if let Some(years) = &filter.years {
+		sql.push_str(" AND b.start_year BETWEEN ? AND ?");
+		values.extend([Value::from(*years.start()), Value::from(*years.end())]);
+	}
+	if !filter.title_types.is_empty() {
+		sql.push_str(&format!(
+			" AND b.title_type IN ({})",
+			vec!["?"; filter.title_types.len()].join(", ")
+		));
+		values.extend(filter.title_types.iter().map(|title_type| Value::from(title_type.as_str())));
+	}
+	values.push(Value::from(CANDIDATES));
+
+	let rows = db
+		.query_all(Statement::from_sql_and_values(
+			DbBackend::Sqlite,
+			format!(
+				"SELECT s.title_id, s.name, s.key, b.primary_title, b.title_type, b.start_year FROM title_search s LEFT JOIN \
+				 basics b ON b.id = s.title_id WHERE {sql} ORDER BY s.rank LIMIT ?"
+			),
+			values,
+		))
+		.await?;
+
+	let mut matches: HashMap<String, TitleMatch> = HashMap::new();
+	for row in rows {
+		let title_id: String = row.try_get("", "title_id")?;
+		let name: String = row.try_get("", "name")?;
+		let key: String = row.try_get("", "key")?;
+		let found = TitleMatch {
+			tconst: tconst(&title_id),
+			primary_title: row.try_get("", "primary_title")?,
+			title_type: row.try_get("", "title_type")?,
+			year: row.try_get::<Option<i32>>("", "start_year")?.map(|year| year as u16),
+			similarity: similarity(&query, &key),
+			name,
+		};
+		match matches.get(&title_id) {
+			Some(known) if known.similarity >= found.similarity => {}
+			_ => {
+				matches.insert(title_id, found);
+			}
+		}
+	}
+
+	let mut matches: Vec<TitleMatch> = matches.into_values().collect();
+	matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then_with(|| a.tconst.cmp(&b.tconst)));
+	matches.truncate(limit);
+	Ok(matches)
+}
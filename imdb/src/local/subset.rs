@@ -0,0 +1,188 @@
+use std::{
+	collections::{HashMap, HashSet},
+	io,
+	ops::RangeInclusive,
+	path::Path,
+};
+
+use log::{info, warn};
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use sqlx::sqlite::SqliteJournalMode;
+
+use crate::{
+	config::DatabaseConfig,
+	errors::{ImdbError, Result},
+	local::{
+		basics::{self, TitleBasics},
+		create_database,
+		episode::{self, Episode},
+		name::{self, Name},
+		principal::{self, Principal},
+		rating::{self, Rating},
+		refresh::Table,
+		search,
+		title::{self, Title},
+		tsv::Dataset,
+	},
+};
+
+/// Titles of the subset, everything by default. The akas are kept for the regions or languages of interest
+/// (all when neither is set) and the original titles.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubsetFilter {
+	title_types: Vec<String>,
+	min_votes: u32,
+	regions: Vec<String>,
+	languages: Vec<String>,
+	years: Option<RangeInclusive<u16>>,
+}
+
+impl SubsetFilter {
+	/// IMDb title types like `movie`, `tvMovie` or `tvSeries`.
+	pub fn with_title_types(mut self, title_types: &[&str]) -> Self {
+		self.title_types = title_types.iter().map(|title_type| title_type.to_string()).collect();
+		self
+	}
+
+	/// Titles with at least the number of votes, titles without ratings are dropped.
+	pub fn with_min_votes(mut self, min_votes: u32) -> Self {
+		self.min_votes = min_votes;
+		self
+	}
+
+	/// Region codes of the akas, e.g. `RU` or `US`.
+	pub fn with_regions(mut self, regions: &[&str]) -> Self {
+		self.regions = regions.iter().map(|region| region.to_string()).collect();
+		self
+	}
+
+	/// Language codes of the akas, e.g. `ru` or `en`.
+	pub fn with_languages(mut self, languages: &[&str]) -> Self {
+		self.languages = languages.iter().map(|language| language.to_string()).collect();
+		self
+	}
+
+	/// Titles started within the years, titles without the start year are dropped.
+	pub fn with_years(mut self, years: RangeInclusive<u16>) -> Self {
+		self.years = Some(years);
+		self
+	}
+
+	fn accepts_title(&self, title: &TitleBasics, votes: Option<u32>) -> bool {
+		(self.title_types.is_empty() || self.title_types.contains(&title.title_type))
+			&& self
+				.years
+				.as_ref()
+				.is_none_or(|years| title.start_year.is_some_and(|year| years.contains(&year)))
+			&& (self.min_votes == 0 || votes.is_some_and(|votes| votes >= self.min_votes))
+	}
+
+	fn accepts_aka(&self, aka: &Title) -> bool {
+		let listed = |codes: &[String], code: &Option<String>| {
+			code.as_ref()
+				.is_some_and(|code| codes.iter().any(|listed| listed.eq_ignore_ascii_case(code)))
+		};
+		(self.regions.is_empty() && self.languages.is_empty())
+			|| aka.is_original_title
+			|| listed(&self.regions, &aka.region)
+			|| listed(&self.languages, &aka.language)
+	}
+}
+
+/// Numbers of the rows copied to the subset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Subset {
+	pub titles: usize,
+	pub akas: usize,
+	pub episodes: usize,
+	pub ratings: usize,
+	pub principals: usize,
+	pub names: usize,
+	/// Names in the search index.
+	pub indexed: usize,
+}
+
+/// Rows of the `{DATASET}.tsv.gz` dump accepted by `keep`, read without loading the whole dump. Only the
+/// basics are required, the other missing dumps are skipped.
+fn read_dump<T: Table>(dumps: &Path, mut keep: impl FnMut(&T) -> bool) -> Result<Vec<T>> {
+	let file_path = dumps.join(format!("{}.tsv.gz", T::DATASET));
+	if !file_path.exists() && T::DATASET != TitleBasics::DATASET {
+		info!("{} is missing, skipped", file_path.display());
+		return Ok(vec![]);
+	}
+	let mut rows = vec![];
+	for row in Dataset::<T, _>::open_gzip(&file_path)? {
+		match row {
+			Ok(row) if keep(&row) => rows.push(row),
+			Ok(_) => {}
+			Err(err @ ImdbError::IoError(_)) => return Err(err),
+			Err(err) => warn!("Skipping the row of {}: {err}", file_path.display()),
+		}
+	}
+	info!("{} rows of {} kept", rows.len(), T::DATASET);
+	Ok(rows)
+}
+
+/// Builds a new database of the filtered titles from the dumps folder, with their akas, episodes of the
+/// series, ratings, principals and their names, and the search index. The database is a single file
+/// without the journal, to be copied and opened read-only with
+/// [`open_database`](crate::local::open_database).
+pub async fn build_subset(dumps: &Path, target: &Path, filter: &SubsetFilter) -> Result<Subset> {
+	if target.exists() {
+		return Err(io::Error::new(
+			io::ErrorKind::AlreadyExists,
+			format!("{} already exists", target.display()),
+		)
+		.into());
+	}
+
+	let ratings: HashMap<String, Rating> =
+		read_dump(dumps, |rating: &Rating| rating.votes >= filter.min_votes)?
+			.into_iter()
+			.map(|rating| (rating.title_id.clone(), rating))
+			.collect();
+	let titles = read_dump(dumps, |title: &TitleBasics| {
+		filter.accepts_title(title, ratings.get(&title.title_id).map(|rating| rating.votes))
+	})?;
+	let ids: HashSet<&str> = titles.iter().map(|title| title.title_id.as_str()).collect();
+	let ratings: Vec<Rating> = ratings
+		.into_values()
+		.filter(|rating| ids.contains(rating.title_id.as_str()))
+		.collect();
+	let akas = read_dump(dumps, |aka: &Title| {
+		ids.contains(aka.title_id.as_str()) && filter.accepts_aka(aka)
+	})?;
+	// all the episodes of the series, their own titles are kept by the filter
+	let episodes = read_dump(dumps, |episode: &Episode| ids.contains(episode.parent_id.as_str()))?;
+	let principals = read_dump(dumps, |principal: &Principal| ids.contains(principal.title_id.as_str()))?;
+	let people: HashSet<&str> = principals.iter().map(|principal| principal.person_id.as_str()).collect();
+	let names = read_dump(dumps, |name: &Name| people.contains(name.person_id.as_str()))?;
+
+	let config = DatabaseConfig {
+		journal_mode: SqliteJournalMode::Delete,
+		pool_size: 1,
+		..DatabaseConfig::file(target)?
+	};
+	let db = create_database(&config).await?;
+	let transaction = db.begin().await?;
+	basics::fill_basics_table(&transaction, &titles).await?;
+	title::fill_akas_table(&transaction, &akas).await?;
+	episode::fill_episodes_table(&transaction, &episodes).await?;
+	rating::fill_ratings_table(&transaction, &ratings).await?;
+	principal::fill_principals_table(&transaction, &principals).await?;
+	name::fill_names_table(&transaction, &names).await?;
+	transaction.commit().await?;
+	let indexed = search::rebuild_index(&db).await?;
+	db.execute_unprepared("VACUUM").await?;
+	db.close().await?;
+
+	Ok(Subset {
+		titles: titles.len(),
+		akas: akas.len(),
+		episodes: episodes.len(),
+		ratings: ratings.len(),
+		principals: principals.len(),
+		names: names.len(),
+		indexed,
+	})
+}
@@ -1,127 +1,212 @@
-use std::io::{BufRead, BufReader};
-
-use log::debug;
-use nom::{
-	branch::alt,
-	bytes::complete::{tag, take_until, take_while_m_n},
-	IResult,
+use async_trait::async_trait;
+use sea_orm::{
+	sea_query::OnConflict,
+	ActiveValue,
+	ColumnTrait,
+	Condition,
+	ConnectionTrait,
+	EntityTrait,
+	QueryFilter,
+	QueryOrder,
+	QuerySelect,
 };
-use sea_orm::{ActiveValue, EntityTrait};
 
-use crate::local::entities::titles;
+use crate::{
+	errors::Result,
+	local::{
+		entities::{akas, titles},
+		refresh::Table,
+		tsv::{self, join_array, split_array, Fields, Row},
+		BATCH_SIZE,
+	},
+};
 
-#[derive(Debug)]
+/// Row of `title.akas.tsv`, the title id is stored without the `tt` prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Title {
 	pub title_id: String,
-	pub ordering: u8,
+	pub ordering: u32,
 	pub name: String,
 	pub region: Option<String>,
 	pub language: Option<String>,
-	pub types: Option<String>,
-	pub attributes: Option<String>,
+	pub types: Vec<String>,
+	pub attributes: Vec<String>,
 	pub is_original_title: bool,
 }
 
-pub async fn fill_title_akas_table(db: &sea_orm::DatabaseConnection, id: i32) -> Result<(), sea_orm::DbErr> {
-	let title = titles::ActiveModel {
-		title: ActiveValue::Set("test".to_owned()),
-		id: ActiveValue::Set(id),
-	};
-
-	titles::Entity::insert(title).exec(db).await?;
-	Ok(())
-}
-
-pub fn parse_gzip_file(file_path: &std::path::Path) -> Vec<Title> {
-	let file = std::fs::File::open(file_path).unwrap();
-	let reader = BufReader::new(file);
-	let gzip = flate2::read::GzDecoder::new(reader);
-
-	BufReader::new(gzip)
-		.lines()
-		.flatten()
-		.filter_map(|line| {
-			debug!("{}", line);
-			title(&line).ok().map(|(_, title)| {
-				debug!("{:#?}", &title);
-				title
-			})
+impl Row for Title {
+	const COLUMNS: &'static [&'static str] = &[
+		"titleId",
+		"ordering",
+		"title",
+		"region",
+		"language",
+		"types",
+		"attributes",
+		"isOriginalTitle",
+	];
+
+	fn decode(fields: &mut Fields) -> Result<Self> {
+		Ok(Title {
+			title_id: fields.id("tt")?,
+			ordering: fields.required()?,
+			name: fields.required()?,
+			region: fields.optional()?,
+			language: fields.optional()?,
+			types: fields.array()?,
+			attributes: fields.array()?,
+			is_original_title: fields.required()?,
 		})
-		.collect()
+	}
 }
 
-fn title(input: &str) -> IResult<&str, Title> {
-	let (input, title_id) = title_id(input)?;
-	let (input, _) = tag("\t")(input)?;
-	let (input, ordering) = ordering(input)?;
-	let (input, _) = tag("\t")(input)?;
-	let (input, name) = name(input)?;
-	let (input, _) = tag("\t")(input)?;
-	let (input, region) = region(input)?;
-	let (input, _) = tag("\t")(input)?;
-	let (input, language) = language(input)?;
-	let (input, _) = tag("\t")(input)?;
-	let (input, types) = types(input)?;
-	let (input, _) = tag("\t")(input)?;
-	let (input, attributes) = attributes(input)?;
-	let (input, _) = tag("\t")(input)?;
-	let (input, is_original_title) = is_original_title(input)?;
-
-	Ok((input, Title {
-		title_id: title_id.to_string(),
-		ordering,
-		name: name.to_string(),
-		region: region.map(|s| s.to_string()),
-		language: language.map(|s| s.to_string()),
-		types: types.map(|s| s.to_string()),
-		attributes: attributes.map(|s| s.to_string()),
-		is_original_title,
-	}))
+impl From<akas::Model> for Title {
+	fn from(model: akas::Model) -> Self {
+		Title {
+			title_id: model.title_id,
+			ordering: model.ordering as u32,
+			name: model.name,
+			region: model.region,
+			language: model.language,
+			types: split_array(model.types),
+			attributes: split_array(model.attributes),
+			is_original_title: model.is_original_title,
+		}
+	}
 }
 
-fn title_id(input: &str) -> IResult<&str, &str> {
-	let (input, _) = tag("tt")(input)?;
-	let (input, id) = take_while_m_n(7, 8, |c: char| c.is_ascii_digit())(input)?;
-	Ok((input, id))
-}
+#[async_trait]
+impl Table for Title {
+	type Key = (String, u32);
 
-fn ordering(input: &str) -> IResult<&str, u8> {
-	let (input, ordering) = take_while_m_n(1, 2, |c: char| c.is_ascii_digit())(input)?;
-	Ok((input, ordering.parse().unwrap()))
-}
+	const DATASET: &'static str = "title.akas";
 
-fn name(input: &str) -> IResult<&str, &str> {
-	let (input, name) = take_until("\t")(input)?;
-	Ok((input, name))
-}
+	fn key(&self) -> Self::Key {
+		(self.title_id.clone(), self.ordering)
+	}
 
-fn none(input: &str) -> IResult<&str, &str> {
-	tag("\\N")(input)
+	fn tconst(&self) -> Option<String> {
+		Some(tconst(&self.title_id))
+	}
+
+	async fn find<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<Vec<Self>> {
+		let mut found = vec![];
+		for batch in keys.chunks(BATCH_SIZE / 2) {
+			let condition = batch.iter().fold(Condition::any(), |condition, (title_id, ordering)| {
+				condition.add(
+					akas::Column::TitleId
+						.eq(title_id.as_str())
+						.and(akas::Column::Ordering.eq(*ordering as i32)),
+				)
+			});
+			found.extend(
+				akas::Entity::find()
+					.filter(condition)
+					.all(db)
+					.await?
+					.into_iter()
+					.map(Self::from),
+			);
+		}
+		Ok(found)
+	}
+
+	async fn page<C: ConnectionTrait>(db: &C, after: Option<&Self::Key>, limit: u64) -> Result<Vec<Self>> {
+		let mut query = akas::Entity::find();
+		if let Some((title_id, ordering)) = after {
+			query = query.filter(
+				Condition::any().add(akas::Column::TitleId.gt(title_id.as_str())).add(
+					akas::Column::TitleId
+						.eq(title_id.as_str())
+						.and(akas::Column::Ordering.gt(*ordering as i32)),
+				),
+			);
+		}
+		let found = query
+			.order_by_asc(akas::Column::TitleId)
+			.order_by_asc(akas::Column::Ordering)
+			.limit(limit)
+			.all(db)
+			.await?;
+		Ok(found.into_iter().map(Self::from).collect())
+	}
+
+	async fn upsert<C: ConnectionTrait>(db: &C, rows: &[Self]) -> Result<()> {
+		fill_akas_table(db, rows).await
+	}
+
+	async fn delete<C: ConnectionTrait>(db: &C, keys: &[Self::Key]) -> Result<()> {
+		for batch in keys.chunks(BATCH_SIZE / 2) {
+			let condition = batch.iter().fold(Condition::any(), |condition, (title_id, ordering)| {
+				condition.add(
+					akas::Column::TitleId
+						.eq(title_id.as_str())
+						.and(akas::Column::Ordering.eq(*ordering as i32)),
+				)
+			});
+			akas::Entity::delete_many().filter(condition).exec(db).await?;
+		}
+		Ok(())
+	}
 }
 
-fn region(input: &str) -> IResult<&str, Option<&str>> {
-	let (input, region) = alt((none, take_while_m_n(1, 4, |c: char| c.is_ascii_uppercase())))(input)?;
-	Ok((input, if region == "\\N" { None } else { Some(region) }))
+/// Inserts the akas, replacing the already imported ones.
+pub async fn fill_akas_table<C: ConnectionTrait>(db: &C, titles: &[Title]) -> Result<()> {
+	for batch in titles.chunks(BATCH_SIZE) {
+		let models = batch.iter().map(|title| akas::ActiveModel {
+			title_id: ActiveValue::Set(title.title_id.clone()),
+			ordering: ActiveValue::Set(title.ordering as i32),
+			name: ActiveValue::Set(title.name.clone()),
+			region: ActiveValue::Set(title.region.clone()),
+			language: ActiveValue::Set(title.language.clone()),
+			types: ActiveValue::Set(join_array(&title.types)),
+			attributes: ActiveValue::Set(join_array(&title.attributes)),
+			is_original_title: ActiveValue::Set(title.is_original_title),
+		});
+		akas::Entity::insert_many(models)
+			.on_conflict(
+				OnConflict::columns([akas::Column::TitleId, akas::Column::Ordering])
+					.update_columns([
+						akas::Column::Name,
+						akas::Column::Region,
+						akas::Column::Language,
+						akas::Column::Types,
+						akas::Column::Attributes,
+						akas::Column::IsOriginalTitle,
+					])
+					.to_owned(),
+			)
+			.exec(db)
+			.await?;
+	}
+	Ok(())
 }
 
-fn language(input: &str) -> IResult<&str, Option<&str>> {
-	let (input, language) = alt((none, take_while_m_n(1, 3, |c: char| c.is_ascii_lowercase())))(input)?;
-	Ok((input, if language == "\\N" { None } else { Some(language) }))
+pub async fn fill_title_akas_table(
+	db: &sea_orm::DatabaseConnection,
+	id: i32,
+) -> std::result::Result<(), sea_orm::DbErr> {
+	let title = titles::ActiveModel {
+		title: ActiveValue::Set("test".to_owned()),
+		id: ActiveValue::Set(id),
+	};
+
+	titles::Entity::insert(title).exec(db).await?;
+	Ok(())
 }
 
-fn types(input: &str) -> IResult<&str, Option<&str>> {
-	let (input, types) = alt((none, take_while_m_n(1, 100, |c: char| c.is_ascii_alphanumeric())))(input)?;
-	Ok((input, if types == "\\N" { None } else { Some(types) }))
+pub fn parse_gzip_file(file_path: &std::path::Path) -> Result<Vec<Title>> {
+	tsv::read_gzip_file(file_path)
 }
 
-fn attributes(input: &str) -> IResult<&str, Option<&str>> {
-	let (input, attributes) = alt((none, take_until("\t")))(input)?;
-	Ok((input, if attributes == "\\N" { None } else { Some(attributes) }))
+/// Title id as stored, without the `tt` prefix of the tconst.
+pub(crate) fn id(tconst: &str) -> &str {
+	tconst.strip_prefix("tt").unwrap_or(tconst)
 }
 
-fn is_original_title(input: &str) -> IResult<&str, bool> {
-	let (input, is_original_title) = alt((tag("0"), tag("1")))(input)?;
-	Ok((input, is_original_title == "1"))
+/// Tconst of the stored title id.
+pub(crate) fn tconst(id: &str) -> String {
+	format!("tt{id}")
 }
 
 #[cfg(test)]
@@ -129,16 +214,14 @@ mod tests {
 	use super::*;
 
 	#[test]
-	fn test_title_id() {
-		assert_eq!(title_id("tt1234567"), Ok(("", "1234567")));
-		assert_eq!(title_id("tt12345678"), Ok(("", "12345678")));
-		assert_eq!(title_id("tt123456789"), Ok(("9", "12345678")));
-		assert_eq!(
-			title_id("tt123456"),
-			Err(nom::Err::Error(nom::error::Error {
-				input: "123456",
-				code: nom::error::ErrorKind::TakeWhileMN
-			}))
-		);
+	fn test_title() {
+		let title: Title =
+			tsv::decode_line(1, "tt0124315\t1024\tБрат\tRU\tru\timdbDisplay,original\t\\N\t1").unwrap();
+		assert_eq!(title.title_id, "0124315");
+		assert_eq!(title.ordering, 1024);
+		assert_eq!(title.types, ["imdbDisplay", "original"]);
+		assert!(title.attributes.is_empty());
+		assert!(title.is_original_title);
+		assert!(tsv::decode_line::<Title>(1, "tt0124315\t1\tБрат\tRU\tru\t\\N\t\\N\tyes").is_err());
 	}
 }
@@ -0,0 +1,177 @@
+/// Romanization system of the Russian Cyrillic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scheme {
+	/// GOST 7.79-2000 system B, ASCII only.
+	Gost,
+	/// ISO 9, one Latin letter with diacritics for each Cyrillic one.
+	Iso9,
+	/// BGN/PCGN, the common English one.
+	Bgn,
+	/// Release names, ASCII BGN without the sign marks.
+	#[default]
+	Scene,
+}
+
+/// Lowercase Cyrillic letters with their GOST, ISO 9, BGN and scene romanizations.
+const LETTERS: [(char, [&str; 4]); 33] = [
+	('а', ["a", "a", "a", "a"]),
+	('б', ["b", "b", "b", "b"]),
+	('в', ["v", "v", "v", "v"]),
+	('г', ["g", "g", "g", "g"]),
+	('д', ["d", "d", "d", "d"]),
+	('е', ["e", "e", "e", "e"]),
+	('ё', ["yo", "ë", "ë", "yo"]),
+	('ж', ["zh", "ž", "zh", "zh"]),
+	('з', ["z", "z", "z", "z"]),
+	('и', ["i", "i", "i", "i"]),
+	('й', ["j", "j", "y", "y"]),
+	('к', ["k", "k", "k", "k"]),
+	('л', ["l", "l", "l", "l"]),
+	('м', ["m", "m", "m", "m"]),
+	('н', ["n", "n", "n", "n"]),
+	('о', ["o", "o", "o", "o"]),
+	('п', ["p", "p", "p", "p"]),
+	('р', ["r", "r", "r", "r"]),
+	('с', ["s", "s", "s", "s"]),
+	('т', ["t", "t", "t", "t"]),
+	('у', ["u", "u", "u", "u"]),
+	('ф', ["f", "f", "f", "f"]),
+	('х', ["x", "h", "kh", "h"]),
+	('ц', ["cz", "c", "ts", "ts"]),
+	('ч', ["ch", "č", "ch", "ch"]),
+	('ш', ["sh", "š", "sh", "sh"]),
+	('щ', ["shh", "ŝ", "shch", "sch"]),
+	('ъ', ["``", "ʺ", "\"", ""]),
+	('ы', ["y`", "y", "y", "y"]),
+	('ь', ["`", "ʹ", "'", ""]),
+	('э', ["e`", "è", "e", "e"]),
+	('ю', ["yu", "û", "yu", "yu"]),
+	('я', ["ya", "â", "ya", "ya"]),
+];
+
+/// Latin spellings folded together, longest first, the single letters are kept.
+const LATIN_FOLDS: [(&str, &str); 21] = [
+	("shch", "s"),
+	("sch", "s"),
+	("shh", "s"),
+	("zh", "z"),
+	("kh", "h"),
+	("ts", "c"),
+	("tc", "c"),
+	("cz", "c"),
+	("ch", "c"),
+	("sh", "s"),
+	("yu", "u"),
+	("ju", "u"),
+	("ya", "a"),
+	("ja", "a"),
+	("yo", "e"),
+	("jo", "e"),
+	("ye", "e"),
+	("x", "ks"),
+	("j", "i"),
+	("y", "i"),
+	("w", "v"),
+];
+
+/// Cyrillic letters folded to the Latin key, besides the ones with the same single letter in all schemes.
+/// `й` and `ё` are folded by the normalization which strips the diacritics.
+const CYRILLIC_FOLDS: [(char, &str); 17] = [
+	('ж', "z"),
+	('и', "i"),
+	('й', "i"),
+	('х', "h"),
+	('ц', "c"),
+	('ч', "c"),
+	('ш', "s"),
+	('щ', "s"),
+	('ъ', ""),
+	('ы', "i"),
+	('ь', ""),
+	('э', "e"),
+	('ю', "u"),
+	('я', "a"),
+	('і', "i"),
+	('є', "e"),
+	('ґ', "g"),
+];
+
+fn romanize(letter: char, scheme: Scheme) -> Option<&'static str> {
+	LETTERS
+		.iter()
+		.find(|(cyrillic, _)| *cyrillic == letter)
+		.map(|(_, latin)| latin[scheme as usize])
+}
+
+/// Romanizes the Russian Cyrillic letters of the text, the rest is kept. Capital letters stay capital.
+pub fn to_latin(text: &str, scheme: Scheme) -> String {
+	let mut latin = String::with_capacity(text.len());
+	for c in text.chars() {
+		let lower = c.to_lowercase().next().unwrap_or(c);
+		match romanize(lower, scheme) {
+			Some(romanized) if lower != c => {
+				let mut chars = romanized.chars();
+				latin.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+				latin.push_str(chars.as_str());
+			}
+			Some(romanized) => latin.push_str(romanized),
+			None => latin.push(c),
+		}
+	}
+	latin
+}
+
+/// Key of the normalized (lowercase, without diacritics) text which is the same for its Cyrillic spelling and
+/// the romanizations of all the schemes, e.g. `ironia sudbi` for `ирония судьбы`, `ironiya sudby` and
+/// `ironija sudby`. Different words may share the key, it is for the fuzzy matching only.
+pub fn fold(text: &str) -> String {
+	let mut key = String::with_capacity(text.len());
+	let mut rest = text;
+	'chars: while let Some(c) = rest.chars().next() {
+		for (latin, folded) in LATIN_FOLDS {
+			if let Some(after) = rest.strip_prefix(latin) {
+				key.push_str(folded);
+				rest = after;
+				continue 'chars;
+			}
+		}
+		match CYRILLIC_FOLDS.iter().find(|(cyrillic, _)| *cyrillic == c) {
+			Some((_, folded)) => key.push_str(folded),
+			// the letters romanized the same by all the schemes
+			None => match romanize(c, Scheme::Scene) {
+				Some(romanized) => key.push_str(romanized),
+				None => key.push(c),
+			},
+		}
+		rest = &rest[c.len_utf8()..];
+	}
+	key
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_latin() {
+		let title = "Ирония судьбы, или С лёгким паром!";
+		assert_eq!(to_latin(title, Scheme::Gost), "Ironiya sud`by`, ili S lyogkim parom!");
+		assert_eq!(to_latin(title, Scheme::Iso9), "Ironiâ sudʹby, ili S lëgkim parom!");
+		assert_eq!(to_latin(title, Scheme::Bgn), "Ironiya sud'by, ili S lëgkim parom!");
+		assert_eq!(to_latin(title, Scheme::Scene), "Ironiya sudby, ili S lyogkim parom!");
+		assert_eq!(to_latin("Щука и Ёж", Scheme::Scene), "Schuka i Yozh");
+	}
+
+	#[test]
+	fn test_fold() {
+		for spelling in ["ирония судьбы", "ironiya sudby", "ironija sudby", "ironia sudbi"] {
+			assert_eq!(fold(spelling), "ironia sudbi", "{spelling}");
+		}
+		assert_eq!(fold("елки"), fold("yolki"));
+		assert_eq!(fold("жмурки"), fold("zhmurki"));
+		assert_eq!(fold("кин дза дза"), "kin dza dza");
+		assert_eq!(fold("aleksei"), fold("alexey"));
+		assert_eq!(fold("щекотка"), fold("shchekotka"));
+		assert_eq!(fold("brat 2"), "brat 2");
+	}
+}
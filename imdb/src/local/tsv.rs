@@ -0,0 +1,287 @@
+use std::{
+	fs::File,
+	io::{BufRead, BufReader, Lines},
+	marker::PhantomData,
+	path::Path,
+};
+
+use flate2::read::GzDecoder;
+use log::warn;
+
+use crate::errors::{ImdbError, Result};
+
+/// Null value of the IMDb datasets.
+pub const NULL: &str = "\\N";
+/// Separator of the array values (genres, types, professions).
+const ARRAY_SEPARATOR: char = ',';
+/// Digits of the shortest IMDb ids.
+const MIN_ID_DIGITS: usize = 7;
+
+/// Row of a dataset, declared by its columns.
+pub trait Row: Sized {
+	/// Column names as in the header, in order.
+	const COLUMNS: &'static [&'static str];
+
+	/// Decodes the columns in the order of [`Row::COLUMNS`].
+	fn decode(fields: &mut Fields) -> Result<Self>;
+}
+
+/// Typed value of a column.
+pub trait Decode: Sized {
+	fn decode(raw: &str) -> Option<Self>;
+}
+
+macro_rules! decode_from_str {
+	($($type:ty),*) => {
+		$(impl Decode for $type {
+			fn decode(raw: &str) -> Option<Self> {
+				raw.parse().ok()
+			}
+		})*
+	};
+}
+
+decode_from_str!(String, u8, u16, u32, u64, i32, i64, f64);
+
+impl Decode for bool {
+	fn decode(raw: &str) -> Option<Self> {
+		match raw {
+			"0" => Some(false),
+			"1" => Some(true),
+			_ => None,
+		}
+	}
+}
+
+/// Columns of a line, decoded in order.
+pub struct Fields<'a> {
+	line: usize,
+	columns: &'static [&'static str],
+	values: Vec<&'a str>,
+	index: usize,
+}
+
+impl<'a> Fields<'a> {
+	fn next_raw(&mut self) -> (usize, &'a str) {
+		let index = self.index;
+		self.index += 1;
+		(index, self.values.get(index).copied().unwrap_or(NULL))
+	}
+
+	fn error(&self, index: usize, raw: &str) -> ImdbError {
+		let column = self.columns.get(index).copied().unwrap_or("?");
+		ImdbError::ColumnError(self.line, column.to_owned(), raw.to_owned())
+	}
+
+	/// Value of the next column, which can't be null.
+	pub fn required<T: Decode>(&mut self) -> Result<T> {
+		let (index, raw) = self.next_raw();
+		T::decode(raw).filter(|_| raw != NULL).ok_or_else(|| self.error(index, raw))
+	}
+
+	/// Value of the next column or none for null.
+	pub fn optional<T: Decode>(&mut self) -> Result<Option<T>> {
+		let (index, raw) = self.next_raw();
+		if raw == NULL {
+			return Ok(None);
+		}
+		T::decode(raw).map(Some).ok_or_else(|| self.error(index, raw))
+	}
+
+	/// Comma separated values of the next column, empty for null.
+	pub fn array<T: Decode>(&mut self) -> Result<Vec<T>> {
+		let (index, raw) = self.next_raw();
+		if raw == NULL || raw.is_empty() {
+			return Ok(vec![]);
+		}
+		raw.split(ARRAY_SEPARATOR)
+			.map(|value| T::decode(value).ok_or_else(|| self.error(index, raw)))
+			.collect()
+	}
+
+	/// IMDb id of the next column without its prefix (`tt` for titles, `nm` for names).
+	pub fn id(&mut self, prefix: &str) -> Result<String> {
+		let (index, raw) = self.next_raw();
+		raw.strip_prefix(prefix)
+			.filter(|id| id.len() >= MIN_ID_DIGITS && id.chars().all(|c| c.is_ascii_digit()))
+			.map(str::to_owned)
+			.ok_or_else(|| self.error(index, raw))
+	}
+}
+
+/// Array values joined for a database column, none if empty.
+pub(crate) fn join_array(values: &[String]) -> Option<String> {
+	Some(values.join(&ARRAY_SEPARATOR.to_string())).filter(|joined| !joined.is_empty())
+}
+
+/// Array values of a database column.
+pub(crate) fn split_array(joined: Option<String>) -> Vec<String> {
+	joined
+		.map(|joined| joined.split(ARRAY_SEPARATOR).map(str::to_owned).collect())
+		.unwrap_or_default()
+}
+
+/// Decodes the line of the row, `line` is its 1-based number for the errors.
+pub fn decode_line<R: Row>(line: usize, text: &str) -> Result<R> {
+	let values: Vec<&str> = text.split('\t').collect();
+	if values.len() != R::COLUMNS.len() {
+		return Err(ImdbError::ColumnCountError(line, R::COLUMNS.len(), values.len()));
+	}
+	R::decode(&mut Fields {
+		line,
+		columns: R::COLUMNS,
+		values,
+		index: 0,
+	})
+}
+
+/// Rows of a dataset. The header, when the first line has one, is validated against the row columns.
+pub struct Dataset<R, B> {
+	lines: Lines<B>,
+	line: usize,
+	first: Option<String>,
+	done: bool,
+	row: PhantomData<R>,
+}
+
+impl<R: Row, B: BufRead> Dataset<R, B> {
+	pub fn new(reader: B) -> Result<Self> {
+		let mut lines = reader.lines();
+		let first = lines.next().transpose()?;
+		let mut dataset = Dataset {
+			lines,
+			line: 1,
+			first: None,
+			done: false,
+			row: PhantomData,
+		};
+		match first {
+			Some(header) if header.split('\t').next() == R::COLUMNS.first().copied() => {
+				if !header.split('\t').eq(R::COLUMNS.iter().copied()) {
+					return Err(ImdbError::HeaderError(header, R::COLUMNS.join("\t")));
+				}
+			}
+			first => {
+				dataset.first = first;
+				dataset.line = 0;
+			}
+		}
+		Ok(dataset)
+	}
+}
+
+impl<R: Row> Dataset<R, BufReader<GzDecoder<BufReader<File>>>> {
+	pub fn open_gzip(file_path: &Path) -> Result<Self> {
+		let file = File::open(file_path)?;
+		Self::new(BufReader::new(GzDecoder::new(BufReader::new(file))))
+	}
+}
+
+impl<R: Row, B: BufRead> Iterator for Dataset<R, B> {
+	type Item = Result<R>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if self.done {
+				return None;
+			}
+			let text = match self.first.take().map(Ok).or_else(|| self.lines.next())? {
+				Ok(text) => text,
+				Err(err) => {
+					self.done = true;
+					return Some(Err(err.into()));
+				}
+			};
+			self.line += 1;
+			if !text.is_empty() {
+				return Some(decode_line(self.line, &text));
+			}
+		}
+	}
+}
+
+/// Reads the rows of the gzipped dataset. The rows which can't be decoded are logged and skipped, reading
+/// and header errors fail.
+pub fn read_gzip_file<R: Row>(file_path: &Path) -> Result<Vec<R>> {
+	let mut rows = vec![];
+	for row in Dataset::<R, _>::open_gzip(file_path)? {
+		match row {
+			Ok(row) => rows.push(row),
+			Err(err @ ImdbError::IoError(_)) => return Err(err),
+			Err(err) => warn!("Skipping the row of {}: {err}", file_path.display()),
+		}
+	}
+	Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, PartialEq)]
+	struct Sample {
+		id: String,
+		ordering: u32,
+		name: Option<String>,
+		genres: Vec<String>,
+		original: bool,
+	}
+
+	impl Row for Sample {
+		const COLUMNS: &'static [&'static str] = &["tconst", "ordering", "name", "genres", "isOriginal"];
+
+		fn decode(fields: &mut Fields) -> Result<Self> {
+			Ok(Sample {
+				id: fields.id("tt")?,
+				ordering: fields.required()?,
+				name: fields.optional()?,
+				genres: fields.array()?,
+				original: fields.required()?,
+			})
+		}
+	}
+
+	fn samples(text: &str) -> Result<Vec<Result<Sample>>> {
+		Ok(Dataset::<Sample, _>::new(text.as_bytes())?.collect())
+	}
+
+	#[test]
+	fn test_header_and_nulls() {
+		let rows =
+			samples("tconst\tordering\tname\tgenres\tisOriginal\ntt0124315\t300\t\\N\tCrime,Drama\t1\n")
+				.unwrap();
+		assert_eq!(rows.len(), 1);
+		assert_eq!(rows[0].as_ref().unwrap(), &Sample {
+			id: "0124315".to_owned(),
+			ordering: 300,
+			name: None,
+			genres: vec!["Crime".to_owned(), "Drama".to_owned()],
+			original: true,
+		});
+
+		// no header
+		let rows = samples("tt12345678\t1\tBrat\t\\N\t0\n\ntt1234567\t2\tBrother\t\t0").unwrap();
+		assert_eq!(rows.len(), 2);
+		assert_eq!(rows[0].as_ref().unwrap().genres, Vec::<String>::new());
+		assert_eq!(rows[1].as_ref().unwrap().name.as_deref(), Some("Brother"));
+
+		assert!(matches!(
+			Dataset::<Sample, _>::new("tconst\tordering\ttitle\tgenres\tisOriginal\n".as_bytes()),
+			Err(ImdbError::HeaderError(..))
+		));
+	}
+
+	#[test]
+	fn test_errors() {
+		let rows = samples(
+			"tt0124315\t99999999999\tBrat\t\\N\t0\ntt123456\t1\tBrat\t\\N\t0\ntt0124315\t1\tBrat\t\\N\n",
+		)
+		.unwrap();
+		let errors: Vec<String> = rows.into_iter().map(|row| row.unwrap_err().to_string()).collect();
+		assert_eq!(errors, [
+			"Line 1, column ordering: can't decode \"99999999999\"",
+			"Line 2, column tconst: can't decode \"tt123456\"",
+			"Line 3: expected 5 columns, found 4",
+		]);
+	}
+}
@@ -1,11 +1,106 @@
-mod errors;
-mod local;
+use std::{collections::HashSet, fs, path::PathBuf};
 
-use crate::errors::Result;
+use clap::{Parser, Subcommand};
+use log::info;
+use media_order_imdb::{
+	config::DatabaseConfig,
+	errors::Result,
+	local::{self, subset::SubsetFilter},
+};
+use simple_logger::SimpleLogger;
+
+#[derive(Parser)]
+#[command(version, about = "Local IMDb database")]
+struct Cli {
+	#[command(subcommand)]
+	command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Create the database (IMDB_DATABASE) or migrate it to the latest version
+	Create,
+	/// Build a compact database of the filtered titles from the folder of the IMDb dumps (`*.tsv.gz`), to be
+	/// copied and used read-only
+	Subset {
+		dumps: PathBuf,
+		/// New database file
+		target: PathBuf,
+		/// Comma separated title types, e.g. `movie,tvSeries`
+		#[arg(long, value_delimiter = ',')]
+		types: Vec<String>,
+		#[arg(long, default_value_t = 0)]
+		min_votes: u32,
+		/// Comma separated regions of the akas, e.g. `RU,US`
+		#[arg(long, value_delimiter = ',')]
+		regions: Vec<String>,
+		/// Comma separated languages of the akas, e.g. `ru,en`
+		#[arg(long, value_delimiter = ',')]
+		languages: Vec<String>,
+		#[arg(long)]
+		from_year: Option<u16>,
+		#[arg(long)]
+		to_year: Option<u16>,
+	},
+	/// Apply the changes of the new IMDb dumps (`*.tsv.gz`) in the folder to the database (IMDB_DATABASE), the
+	/// unchanged dumps are skipped
+	Refresh {
+		dumps: PathBuf,
+		/// File of the tconsts (one per line) kept when they disappear from the dumps, e.g. the matched titles
+		#[arg(long)]
+		keep: Option<PathBuf>,
+	},
+}
+
+fn strs(values: &[String]) -> Vec<&str> {
+	values.iter().map(String::as_str).collect()
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-	let res = local::create_database("local_imdb").await?;
-	dbg!(&res);
+	SimpleLogger::new().init().unwrap();
+
+	match Cli::parse().command.unwrap_or(Command::Create) {
+		Command::Create => {
+			let config = DatabaseConfig::from_env()?;
+			local::create_database(&config).await?;
+			info!("Database {} is up to date", config.location);
+		}
+		Command::Subset {
+			dumps,
+			target,
+			types,
+			min_votes,
+			regions,
+			languages,
+			from_year,
+			to_year,
+		} => {
+			let mut filter = SubsetFilter::default()
+				.with_title_types(&strs(&types))
+				.with_min_votes(min_votes)
+				.with_regions(&strs(&regions))
+				.with_languages(&strs(&languages));
+			if from_year.is_some() || to_year.is_some() {
+				filter = filter.with_years(from_year.unwrap_or(u16::MIN)..=to_year.unwrap_or(u16::MAX));
+			}
+			let subset = local::subset::build_subset(&dumps, &target, &filter).await?;
+			info!("Built {}: {subset:?}", target.display());
+		}
+		Command::Refresh { dumps, keep } => {
+			let keep: HashSet<String> = match keep {
+				Some(keep) => fs::read_to_string(keep)?
+					.lines()
+					.map(str::trim)
+					.filter(|tconst| !tconst.is_empty())
+					.map(str::to_owned)
+					.collect(),
+				None => HashSet::new(),
+			};
+			let db = local::create_database(&DatabaseConfig::from_env()?).await?;
+			let refreshed = local::refresh::refresh_dumps(&db, &dumps, &keep).await?;
+			info!("Refreshed {} datasets from {}", refreshed.len(), dumps.display());
+		}
+	}
 	Ok(())
 }
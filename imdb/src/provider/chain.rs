@@ -0,0 +1,93 @@
+use std::{future::Future, pin::Pin};
+
+use async_trait::async_trait;
+use log::warn;
+
+use crate::{
+	errors::{ImdbError, Result},
+	provider::{Artwork, EpisodeMetadata, MetadataProvider, TitleMetadata},
+};
+
+const NAME: &str = "chain";
+
+/// Answer of a provider, as returned by the [`async_trait`] methods.
+type Answer<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Providers asked in the order of their priorities, the higher first and the added first among the equal.
+/// The next provider is asked when one fails or finds nothing, the failures are logged and only returned
+/// when no provider answered.
+#[derive(Default)]
+pub struct ProviderChain {
+	providers: Vec<(i32, Box<dyn MetadataProvider>)>,
+}
+
+impl ProviderChain {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with(mut self, provider: impl MetadataProvider + 'static, priority: i32) -> Self {
+		let position = self
+			.providers
+			.iter()
+			.position(|(other, _)| *other < priority)
+			.unwrap_or(self.providers.len());
+		self.providers.insert(position, (priority, Box::new(provider)));
+		self
+	}
+
+	/// Names of the providers in the asking order.
+	pub fn names(&self) -> Vec<&str> {
+		self.providers.iter().map(|(_, provider)| provider.name()).collect()
+	}
+
+	/// The first found by the providers, `found` tells whether the answer stops the chain.
+	async fn first<'a, T: Default>(
+		&'a self,
+		ask: impl Fn(&'a dyn MetadataProvider) -> Answer<'a, T>,
+		found: impl Fn(&T) -> bool,
+	) -> Result<T> {
+		let mut answered = None;
+		let mut failure: Option<ImdbError> = None;
+		for (_, provider) in &self.providers {
+			match ask(provider.as_ref()).await {
+				Ok(answer) if found(&answer) => return Ok(answer),
+				Ok(answer) => answered = answered.or(Some(answer)),
+				Err(err) => {
+					warn!("Metadata provider {} failed: {err}", provider.name());
+					failure = Some(err);
+				}
+			}
+		}
+		match (answered, failure) {
+			(Some(answer), _) => Ok(answer),
+			(None, Some(err)) => Err(err),
+			(None, None) => Ok(T::default()),
+		}
+	}
+}
+
+#[async_trait]
+impl MetadataProvider for ProviderChain {
+	fn name(&self) -> &str {
+		NAME
+	}
+
+	async fn search(&self, title: &str, year: Option<u16>, limit: usize) -> Result<Vec<TitleMetadata>> {
+		self.first(|provider| provider.search(title, year, limit), |found| !found.is_empty())
+			.await
+	}
+
+	async fn get(&self, tconst: &str) -> Result<Option<TitleMetadata>> {
+		self.first(|provider| provider.get(tconst), Option::is_some).await
+	}
+
+	async fn episodes(&self, series: &str) -> Result<Vec<EpisodeMetadata>> {
+		self.first(|provider| provider.episodes(series), |found| !found.is_empty())
+			.await
+	}
+
+	async fn artwork(&self, tconst: &str) -> Result<Vec<Artwork>> {
+		self.first(|provider| provider.artwork(tconst), |found| !found.is_empty()).await
+	}
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{
+	errors::{ImdbError, Result},
+	local::normalize::{search_key, similarity},
+	provider::{Artwork, EpisodeMetadata, MetadataProvider, TitleMetadata},
+};
+
+/// Least similarity of the found titles, about a shared word of a short title.
+const MIN_SCORE: f64 = 0.3;
+
+/// In-memory provider of the given titles for the tests of the matcher and the chains.
+#[derive(Clone, Debug, Default)]
+pub struct FixtureProvider {
+	name: String,
+	titles: Vec<TitleMetadata>,
+	episodes: HashMap<String, Vec<EpisodeMetadata>>,
+	artwork: HashMap<String, Vec<Artwork>>,
+	/// Error of every call, to test the fallbacks.
+	failure: Option<String>,
+}
+
+impl FixtureProvider {
+	pub fn new(name: &str) -> Self {
+		Self {
+			name: name.to_owned(),
+			..Self::default()
+		}
+	}
+
+	pub fn with_title(mut self, title: TitleMetadata) -> Self {
+		self.titles.push(TitleMetadata {
+			source: self.name.clone(),
+			..title
+		});
+		self
+	}
+
+	pub fn with_episode(mut self, series: &str, episode: EpisodeMetadata) -> Self {
+		let episodes = self.episodes.entry(series.to_owned()).or_default();
+		episodes.push(episode);
+		episodes.sort_by_key(|episode| (episode.season, episode.episode));
+		self
+	}
+
+	pub fn with_artwork(mut self, tconst: &str, artwork: Artwork) -> Self {
+		self.artwork.entry(tconst.to_owned()).or_default().push(artwork);
+		self
+	}
+
+	/// Fails every call with the message, like an unavailable service.
+	pub fn with_failure(mut self, message: &str) -> Self {
+		self.failure = Some(message.to_owned());
+		self
+	}
+
+	fn check(&self) -> Result<()> {
+		match &self.failure {
+			Some(message) => Err(ImdbError::ProviderError(self.name.clone(), message.clone())),
+			None => Ok(()),
+		}
+	}
+}
+
+#[async_trait]
+impl MetadataProvider for FixtureProvider {
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	async fn search(&self, title: &str, year: Option<u16>, limit: usize) -> Result<Vec<TitleMetadata>> {
+		self.check()?;
+		let query = search_key(title);
+		let mut found: Vec<TitleMetadata> = self
+			.titles
+			.iter()
+			.filter(|found| match (year, found.year) {
+				(Some(year), Some(found)) => year.abs_diff(found) <= 1,
+				(Some(_), None) => false,
+				(None, _) => true,
+			})
+			.filter_map(|found| {
+				let names = [Some(&found.title), found.original_title.as_ref()];
+				let score = names
+					.into_iter()
+					.flatten()
+					.map(|name| similarity(&query, &search_key(name)))
+					.fold(0.0, f64::max);
+				(score >= MIN_SCORE).then(|| TitleMetadata { score, ..found.clone() })
+			})
+			.collect();
+		found.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tconst.cmp(&b.tconst)));
+		found.truncate(limit);
+		Ok(found)
+	}
+
+	async fn get(&self, tconst: &str) -> Result<Option<TitleMetadata>> {
+		self.check()?;
+		Ok(self.titles.iter().find(|title| title.tconst == tconst).cloned())
+	}
+
+	async fn episodes(&self, series: &str) -> Result<Vec<EpisodeMetadata>> {
+		self.check()?;
+		Ok(self.episodes.get(series).cloned().unwrap_or_default())
+	}
+
+	async fn artwork(&self, tconst: &str) -> Result<Vec<Artwork>> {
+		self.check()?;
+		Ok(self.artwork.get(tconst).cloned().unwrap_or_default())
+	}
+}
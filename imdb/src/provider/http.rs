@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{header::ACCEPT, redirect::Policy, Client};
+use url::Url;
+
+use crate::errors::{ImdbError, Result};
+
+const NAME: &str = "http";
+/// Largest accepted response body, the API responses take a few kilobytes.
+const MAX_BODY: usize = 4 << 20;
+const MAX_REDIRECTS: usize = 5;
+
+pub(crate) fn error(message: String) -> ImdbError {
+	ImdbError::ProviderError(NAME.to_owned(), message)
+}
+
+/// Requests of the HTTP providers, replaceable by a stub.
+#[async_trait]
+pub trait Transport: Send + Sync {
+	/// Body of the successful response to the GET request.
+	async fn get(&self, url: &Url) -> Result<String>;
+}
+
+/// HTTPS (rustls) and HTTP client following a few redirects, with the request timeout and the limit of the
+/// response size.
+#[derive(Clone, Debug)]
+pub struct HttpTransport {
+	client: Client,
+	timeout: Duration,
+	max_body: usize,
+}
+
+impl HttpTransport {
+	pub fn new() -> Result<Self> {
+		let client = Client::builder()
+			.redirect(Policy::limited(MAX_REDIRECTS))
+			.build()
+			.map_err(|err| error(err.to_string()))?;
+		Ok(Self {
+			client,
+			timeout: Duration::from_secs(10),
+			max_body: MAX_BODY,
+		})
+	}
+
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	/// Largest accepted response body in bytes.
+	pub fn with_max_body(mut self, max_body: usize) -> Self {
+		self.max_body = max_body;
+		self
+	}
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+	async fn get(&self, url: &Url) -> Result<String> {
+		let mut response = self
+			.client
+			.get(url.clone())
+			.header(ACCEPT, "application/json")
+			.timeout(self.timeout)
+			.send()
+			.await
+			.map_err(|err| error(err.without_url().to_string()))?;
+		let status = response.status();
+		if !status.is_success() {
+			return Err(error(format!("HTTP {} for {}", status.as_u16(), response.url())));
+		}
+		let too_large = || error(format!("response of {} exceeds {} bytes", url, self.max_body));
+		if response.content_length().is_some_and(|length| length > self.max_body as u64) {
+			return Err(too_large());
+		}
+		let mut body = vec![];
+		while let Some(chunk) = response.chunk().await.map_err(|err| error(err.without_url().to_string()))? {
+			if body.len() + chunk.len() > self.max_body {
+				return Err(too_large());
+			}
+			body.extend_from_slice(&chunk);
+		}
+		String::from_utf8(body).map_err(|_| error(format!("malformed response of {url}")))
+	}
+}
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+
+use crate::{
+	errors::Result,
+	local::{
+		basics::{self, TitleBasics},
+		episode,
+		rating::{self, popularity, Rating},
+		search::{self, SearchFilter},
+		title::tconst,
+	},
+	provider::{Artwork, EpisodeMetadata, MetadataProvider, TitleMetadata},
+};
+
+const NAME: &str = "imdb";
+/// Years between the searched year and the title start, release names often have the premiere year.
+const YEAR_WINDOW: u16 = 1;
+/// Share of the score given by the popularity of the title, so the well known of the equally similar titles
+/// goes first while an exact match of an obscure one still scores high.
+const POPULARITY_WEIGHT: f64 = 0.1;
+
+/// Provider of the local IMDb database, which has no artwork.
+pub struct LocalProvider {
+	db: DatabaseConnection,
+}
+
+impl LocalProvider {
+	pub fn new(db: DatabaseConnection) -> Self {
+		Self { db }
+	}
+}
+
+fn metadata(title: TitleBasics, rating: Option<&Rating>, score: f64) -> TitleMetadata {
+	TitleMetadata {
+		tconst: tconst(&title.title_id),
+		original_title: (title.original_title != title.primary_title).then_some(title.original_title),
+		title: title.primary_title,
+		title_type: Some(title.title_type),
+		year: title.start_year,
+		genres: title.genres,
+		rating: rating.map(|rating| rating.average),
+		votes: rating.map(|rating| rating.votes),
+		source: NAME.to_owned(),
+		score,
+	}
+}
+
+#[async_trait]
+impl MetadataProvider for LocalProvider {
+	fn name(&self) -> &str {
+		NAME
+	}
+
+	async fn search(&self, title: &str, year: Option<u16>, limit: usize) -> Result<Vec<TitleMetadata>> {
+		let filter = match year {
+			Some(year) => SearchFilter::default().with_year(year, YEAR_WINDOW),
+			None => SearchFilter::default(),
+		};
+		let matches = search::search_titles(&self.db, title, &filter, limit).await?;
+		let tconsts: Vec<String> = matches.iter().map(|found| found.tconst.clone()).collect();
+		let mut titles = basics::find_basics(&self.db, &tconsts).await?;
+		let ratings = rating::find_ratings(&self.db, &tconsts).await?;
+		// the akas of the titles missing from the basics are skipped
+		let mut found: Vec<TitleMetadata> = matches
+			.into_iter()
+			.filter_map(|found| {
+				let title = titles.remove(&found.tconst)?;
+				let rating = ratings.get(&found.tconst);
+				let votes = rating.map(|rating| rating.votes).unwrap_or_default();
+				let score =
+					found.similarity * (1.0 - POPULARITY_WEIGHT + POPULARITY_WEIGHT * popularity(votes));
+				Some(metadata(title, rating, score))
+			})
+			.collect();
+		found.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tconst.cmp(&b.tconst)));
+		Ok(found)
+	}
+
+	async fn get(&self, tconst: &str) -> Result<Option<TitleMetadata>> {
+		let tconsts = [tconst.to_owned()];
+		let Some(title) = basics::find_basics(&self.db, &tconsts).await?.remove(tconst) else {
+			return Ok(None);
+		};
+		let ratings = rating::find_ratings(&self.db, &tconsts).await?;
+		Ok(Some(metadata(title, ratings.get(tconst), 1.0)))
+	}
+
+	async fn episodes(&self, series: &str) -> Result<Vec<EpisodeMetadata>> {
+		let episodes: Vec<EpisodeMetadata> = episode::episode_map(&self.db, series)
+			.await?
+			.into_iter()
+			.flat_map(|(season, episodes)| {
+				episodes.into_iter().map(move |(episode, tconst)| EpisodeMetadata {
+					tconst,
+					season,
+					episode,
+					title: None,
+				})
+			})
+			.collect();
+		let tconsts: Vec<String> = episodes.iter().map(|episode| episode.tconst.clone()).collect();
+		let titles = basics::find_basics(&self.db, &tconsts).await?;
+		Ok(episodes
+			.into_iter()
+			.map(|episode| EpisodeMetadata {
+				title: titles.get(&episode.tconst).map(|title| title.primary_title.clone()),
+				..episode
+			})
+			.collect())
+	}
+
+	async fn artwork(&self, _tconst: &str) -> Result<Vec<Artwork>> {
+		Ok(vec![])
+	}
+}
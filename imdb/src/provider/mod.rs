@@ -0,0 +1,68 @@
+pub mod chain;
+pub mod fixture;
+pub mod http;
+pub mod local;
+pub mod omdb;
+
+use async_trait::async_trait;
+
+use crate::errors::Result;
+
+/// Title found by a provider, identified by its IMDb tconst.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TitleMetadata {
+	pub tconst: String,
+	pub title: String,
+	pub original_title: Option<String>,
+	/// IMDb title type like `movie` or `tvSeries`.
+	pub title_type: Option<String>,
+	pub year: Option<u16>,
+	pub genres: Vec<String>,
+	pub rating: Option<f64>,
+	pub votes: Option<u32>,
+	/// Name of the provider which found the title.
+	pub source: String,
+	/// Similarity of the searched title from 0 to 1, weighed by the popularity for the local searches, 1 for
+	/// the lookups by id.
+	pub score: f64,
+}
+
+/// Numbered episode of a series.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpisodeMetadata {
+	pub tconst: String,
+	pub season: u16,
+	pub episode: u16,
+	pub title: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArtworkKind {
+	Poster,
+	Backdrop,
+	Thumb,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Artwork {
+	pub kind: ArtworkKind,
+	pub url: String,
+}
+
+/// Source of the title metadata for the matcher. Unknown titles are not errors: they are absent or empty,
+/// errors are left for the failures of the source itself so the [`chain`] can fall back to the next one.
+#[async_trait]
+pub trait MetadataProvider: Send + Sync {
+	/// Short name, e.g. `imdb`, stored as the [`TitleMetadata::source`].
+	fn name(&self) -> &str;
+
+	/// Titles similar to the title, started within a year of the `year` if it is set, the best first.
+	async fn search(&self, title: &str, year: Option<u16>, limit: usize) -> Result<Vec<TitleMetadata>>;
+
+	async fn get(&self, tconst: &str) -> Result<Option<TitleMetadata>>;
+
+	/// Numbered episodes of the series ordered by season and episode.
+	async fn episodes(&self, series: &str) -> Result<Vec<EpisodeMetadata>>;
+
+	async fn artwork(&self, tconst: &str) -> Result<Vec<Artwork>>;
+}
@@ -0,0 +1,242 @@
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize};
+use url::Url;
+
+use crate::{
+	errors::{ImdbError, Result},
+	local::normalize::{search_key, similarity},
+	provider::{
+		http::{HttpTransport, Transport},
+		Artwork,
+		ArtworkKind,
+		EpisodeMetadata,
+		MetadataProvider,
+		TitleMetadata,
+	},
+};
+
+const NAME: &str = "omdb";
+/// Value of the unknown fields.
+const NOT_AVAILABLE: &str = "N/A";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Status {
+	response: String,
+	error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SearchResponse {
+	search: Vec<SearchItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SearchItem {
+	title: String,
+	year: String,
+	#[serde(rename = "imdbID")]
+	imdb_id: String,
+	#[serde(rename = "Type")]
+	title_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TitleResponse {
+	title: String,
+	year: String,
+	genre: Option<String>,
+	poster: Option<String>,
+	#[serde(rename = "imdbID")]
+	imdb_id: String,
+	#[serde(rename = "imdbRating")]
+	imdb_rating: Option<String>,
+	#[serde(rename = "imdbVotes")]
+	imdb_votes: Option<String>,
+	#[serde(rename = "Type")]
+	title_type: String,
+	#[serde(rename = "totalSeasons")]
+	total_seasons: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SeasonResponse {
+	episodes: Vec<EpisodeItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct EpisodeItem {
+	title: String,
+	episode: String,
+	#[serde(rename = "imdbID")]
+	imdb_id: String,
+}
+
+fn available(value: Option<&str>) -> Option<&str> {
+	value.filter(|value| *value != NOT_AVAILABLE)
+}
+
+/// Start year of `1997` or `2005–2013`.
+fn start_year(year: &str) -> Option<u16> {
+	year.get(..4)?.parse().ok()
+}
+
+/// IMDb title type of the OMDb one.
+fn title_type(omdb_type: &str) -> String {
+	match omdb_type {
+		"series" => "tvSeries",
+		"episode" => "tvEpisode",
+		"game" => "videoGame",
+		other => other,
+	}
+	.to_owned()
+}
+
+impl From<TitleResponse> for TitleMetadata {
+	fn from(found: TitleResponse) -> Self {
+		TitleMetadata {
+			tconst: found.imdb_id,
+			title: found.title,
+			original_title: None,
+			title_type: Some(title_type(&found.title_type)),
+			year: start_year(&found.year),
+			genres: available(found.genre.as_deref())
+				.map(|genres| genres.split(',').map(|genre| genre.trim().to_owned()).collect())
+				.unwrap_or_default(),
+			rating: available(found.imdb_rating.as_deref()).and_then(|rating| rating.parse().ok()),
+			votes: available(found.imdb_votes.as_deref())
+				.and_then(|votes| votes.replace(',', "").parse().ok()),
+			source: NAME.to_owned(),
+			score: 1.0,
+		}
+	}
+}
+
+/// Client of an OMDb-style API, which answers the `?apikey=…&s=title&y=year`, `?i=tconst` and
+/// `?i=tconst&Season=1` queries with JSON keyed by the IMDb ids.
+pub struct OmdbProvider {
+	base_url: Url,
+	api_key: String,
+	transport: Box<dyn Transport>,
+}
+
+impl OmdbProvider {
+	pub fn new(base_url: &str, api_key: &str) -> Result<Self> {
+		Ok(Self {
+			base_url: Url::parse(base_url)?,
+			api_key: api_key.to_owned(),
+			transport: Box::new(HttpTransport::new()?),
+		})
+	}
+
+	pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+		self.transport = Box::new(transport);
+		self
+	}
+
+	/// Response to the query, none when nothing is found.
+	async fn request<T: DeserializeOwned>(&self, query: &[(&str, &str)]) -> Result<Option<T>> {
+		let mut url = self.base_url.clone();
+		url.query_pairs_mut().append_pair("apikey", &self.api_key).extend_pairs(query);
+		let body = self.transport.get(&url).await?;
+		let status: Status = serde_json::from_str(&body)?;
+		if status.response.eq_ignore_ascii_case("true") {
+			return Ok(Some(serde_json::from_str(&body)?));
+		}
+		match status.error {
+			// unknown titles and seasons
+			Some(error) if error.contains("not found") || error.starts_with("Incorrect IMDb ID") => Ok(None),
+			error => Err(ImdbError::ProviderError(
+				NAME.to_owned(),
+				error.unwrap_or_else(|| "unexpected response".to_owned()),
+			)),
+		}
+	}
+
+	async fn title(&self, tconst: &str) -> Result<Option<TitleResponse>> {
+		self.request(&[("i", tconst)]).await
+	}
+}
+
+#[async_trait]
+impl MetadataProvider for OmdbProvider {
+	fn name(&self) -> &str {
+		NAME
+	}
+
+	async fn search(&self, title: &str, year: Option<u16>, limit: usize) -> Result<Vec<TitleMetadata>> {
+		let year = year.map(|year| year.to_string());
+		let mut query = vec![("s", title)];
+		query.extend(year.as_deref().map(|year| ("y", year)));
+		let Some(response) = self.request::<SearchResponse>(&query).await? else {
+			return Ok(vec![]);
+		};
+		let key = search_key(title);
+		let mut found: Vec<TitleMetadata> = response
+			.search
+			.into_iter()
+			.map(|item| TitleMetadata {
+				score: similarity(&key, &search_key(&item.title)),
+				tconst: item.imdb_id,
+				original_title: None,
+				title_type: Some(title_type(&item.title_type)),
+				year: start_year(&item.year),
+				title: item.title,
+				genres: vec![],
+				rating: None,
+				votes: None,
+				source: NAME.to_owned(),
+			})
+			.collect();
+		found.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tconst.cmp(&b.tconst)));
+		found.truncate(limit);
+		Ok(found)
+	}
+
+	async fn get(&self, tconst: &str) -> Result<Option<TitleMetadata>> {
+		Ok(self.title(tconst).await?.map(TitleMetadata::from))
+	}
+
+	async fn episodes(&self, series: &str) -> Result<Vec<EpisodeMetadata>> {
+		let seasons = self
+			.title(series)
+			.await?
+			.and_then(|found| available(found.total_seasons.as_deref())?.parse::<u16>().ok())
+			.unwrap_or_default();
+		let mut episodes = vec![];
+		for season in 1..=seasons {
+			let number = season.to_string();
+			let Some(response) =
+				self.request::<SeasonResponse>(&[("i", series), ("Season", &number)]).await?
+			else {
+				continue;
+			};
+			episodes.extend(response.episodes.into_iter().filter_map(|item| {
+				Some(EpisodeMetadata {
+					episode: item.episode.parse().ok()?,
+					tconst: item.imdb_id,
+					season,
+					title: Some(item.title),
+				})
+			}));
+		}
+		episodes.sort_by_key(|episode| (episode.season, episode.episode));
+		Ok(episodes)
+	}
+
+	async fn artwork(&self, tconst: &str) -> Result<Vec<Artwork>> {
+		let poster = self.title(tconst).await?.and_then(|found| found.poster);
+		Ok(available(poster.as_deref())
+			.map(|url| Artwork {
+				kind: ArtworkKind::Poster,
+				url: url.to_owned(),
+			})
+			.into_iter()
+			.collect())
+	}
+}
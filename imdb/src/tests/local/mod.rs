@@ -1,33 +1,535 @@
+use std::{collections::HashSet, io::Write, path::Path};
+
+use flate2::{write::GzEncoder, Compression};
+use sea_orm::{ConnectionTrait, Database};
 use simple_logger::SimpleLogger;
 
-use crate::local::title;
+use crate::{
+	config::{DatabaseConfig, DatabaseLocation},
+	errors::ImdbError,
+	local::{
+		basics::{self, TitleBasics},
+		episode::{self, Episode},
+		migration::{Migrator, MigratorTrait},
+		name,
+		principal::{self, Principal},
+		rating::{self, Rating},
+		refresh::{self, Table},
+		search::{self, SearchFilter},
+		subset,
+		title::{self, Title},
+		tsv,
+	},
+};
 
 #[test]
 fn parse_gzip_title() {
-	SimpleLogger::new().init().unwrap();
+	// the logger is shared by the tests running in parallel
+	let _ = SimpleLogger::new().init();
 	let file_path =
 		std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/local/title.akas.tsv.gz");
-	let titles = title::parse_gzip_file(&file_path);
+	let titles = title::parse_gzip_file(&file_path).unwrap();
 
-	assert_eq!(titles.len(), 772);
+	// the malformed row is skipped
+	assert_eq!(titles.len(), 771);
 	assert_eq!(titles[0].title_id, "13522842");
 	assert_eq!(titles[0].ordering, 1);
 	assert_eq!(titles[0].name, "एपिसोड #1.3980");
 	assert_eq!(titles[0].region, Some("IN".to_owned()));
 	assert_eq!(titles[0].language, Some("hi".to_owned()));
-	assert_eq!(titles[0].types, None);
-	assert_eq!(titles[0].attributes, None);
+	assert!(titles[0].types.is_empty());
+	assert!(titles[0].attributes.is_empty());
 	assert_eq!(titles[0].is_original_title, false);
+
+	let errors: Vec<String> = tsv::Dataset::<Title, _>::open_gzip(&file_path)
+		.unwrap()
+		.filter_map(|row| row.err())
+		.map(|err| err.to_string())
+		.collect();
+	assert_eq!(errors, [r#"Line 172, column isOriginalTitle: can't decode "0tt1514563""#]);
 }
 
 #[tokio::test]
 async fn fill_title_akas_table() {
-	SimpleLogger::new().init().unwrap();
-	let db = crate::local::create_database("test_imdb").await.unwrap();
+	// the logger is shared by the tests running in parallel
+	let _ = SimpleLogger::new().init();
+	let config = DatabaseConfig::file("test_imdb.sqlite").unwrap();
+	let db = crate::local::create_database(&config).await.unwrap();
 	let _ = title::fill_title_akas_table(&db, 1).await;
 	let result = title::fill_title_akas_table(&db, 2).await;
 	dbg!(&result);
 	assert!(result.is_ok());
 	let _ = db.close().await;
 	std::fs::remove_file("test_imdb.sqlite").unwrap();
+	// left by the WAL journal when the connections are not closed yet
+	for suffix in ["-wal", "-shm"] {
+		let _ = std::fs::remove_file(format!("test_imdb.sqlite{suffix}"));
+	}
+}
+
+#[tokio::test]
+async fn import_and_query_episodes() {
+	let file_path =
+		std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/local/title.episode.tsv.gz");
+	let episodes = episode::parse_gzip_file(&file_path).unwrap();
+	assert_eq!(episodes.len(), 13);
+	assert_eq!(episodes[0].title_id, "0664801");
+	assert_eq!(episodes[0].parent_id, "0386676");
+	assert_eq!((episodes[0].season, episodes[0].episode), (Some(1), Some(1)));
+
+	let db = Database::connect("sqlite::memory:").await.unwrap();
+	Migrator::up(&db, None).await.unwrap();
+	episode::fill_episodes_table(&db, &episodes).await.unwrap();
+	// reimport replaces the rows
+	episode::fill_episodes_table(&db, &episodes).await.unwrap();
+
+	assert_eq!(
+		episode::find_episode(&db, "tt0386676", 2, 3).await.unwrap(),
+		Some("tt0714003".to_owned())
+	);
+	assert_eq!(episode::find_episode(&db, "tt0386676", 2, 4).await.unwrap(), None);
+	assert_eq!(
+		episode::find_series(&db, "tt1664529").await.unwrap(),
+		Some("tt1475582".to_owned())
+	);
+	assert_eq!(episode::find_series(&db, "tt1475582").await.unwrap(), None);
+
+	let map = episode::episode_map(&db, "tt0386676").await.unwrap();
+	assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2]);
+	assert_eq!(map[&1].len(), 6);
+	assert_eq!(map[&2][&1], "tt0714001");
+	let map = episode::episode_map(&db, "tt1475582").await.unwrap();
+	assert_eq!(map[&0][&1], "tt1664530");
+	assert!(episode::episode_map(&db, "tt0000001").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn import_and_query_ratings_and_people() {
+	let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/local");
+	let ratings = rating::parse_gzip_file(&dir.join("title.ratings.tsv.gz")).unwrap();
+	let names = name::parse_gzip_file(&dir.join("name.basics.tsv.gz")).unwrap();
+	let principals = principal::parse_gzip_file(&dir.join("title.principals.tsv.gz")).unwrap();
+	assert_eq!((ratings.len(), names.len(), principals.len()), (6, 4, 8));
+
+	let db = Database::connect("sqlite::memory:").await.unwrap();
+	Migrator::up(&db, None).await.unwrap();
+	rating::fill_ratings_table(&db, &ratings).await.unwrap();
+	name::fill_names_table(&db, &names).await.unwrap();
+	principal::fill_principals_table(&db, &principals).await.unwrap();
+
+	let library: Vec<String> = ["tt0124315", "tt0238883", "tt0113277", "tt0211915"]
+		.into_iter()
+		.map(str::to_owned)
+		.collect();
+	let directed = principal::titles_with_person(&db, "aleksei balabanov", "director", Some(&library))
+		.await
+		.unwrap();
+	assert_eq!(directed, ["tt0124315", "tt0238883"]);
+	let directed = principal::titles_with_person(&db, "Aleksei Balabanov", "director", None)
+		.await
+		.unwrap();
+	assert_eq!(directed.len(), 3);
+	let written = principal::titles_with_person(&db, "Aleksei Balabanov", "writer", None)
+		.await
+		.unwrap();
+	assert_eq!(written, ["tt0238883"]);
+	assert!(principal::titles_with_person(&db, "Aleksei%", "director", None)
+		.await
+		.unwrap()
+		.is_empty());
+
+	// the watched Amelie is excluded by the caller
+	let unwatched = &library[..3];
+	let top = rating::top_rated(&db, unwatched, 10_000, 2).await.unwrap();
+	let top: Vec<_> = top.iter().map(|rating| (rating.tconst(), rating.average)).collect();
+	assert_eq!(top, [("tt0113277".to_owned(), 8.3), ("tt0124315".to_owned(), 7.8)]);
+
+	let found = rating::find_ratings(&db, &library).await.unwrap();
+	assert_eq!(found.len(), 4);
+	assert!(found["tt0211915"].popularity() > found["tt0124315"].popularity());
+}
+
+#[tokio::test]
+async fn refresh_changed_rows_only() {
+	let dir = std::env::temp_dir().join(format!("media-order-imdb-refresh-{}", std::process::id()));
+	std::fs::create_dir_all(&dir).unwrap();
+	let write = |name: &str, rows: &[&str]| {
+		let path = dir.join(name);
+		let mut gzip = GzEncoder::new(std::fs::File::create(&path).unwrap(), Compression::default());
+		gzip.write_all(b"tconst\taverageRating\tnumVotes\n").unwrap();
+		for row in rows {
+			writeln!(gzip, "{row}").unwrap();
+		}
+		gzip.finish().unwrap();
+		path
+	};
+	let first = write("first.tsv.gz", &[
+		"tt0124315\t7.8\t41234",
+		"tt0238883\t7.4\t30012",
+		"tt0120094\t7.9\t1500",
+	]);
+	let second = write("second.tsv.gz", &["tt0124315\t7.8\t41300", "tt0113277\t8.3\t702114"]);
+
+	let db = Database::connect("sqlite::memory:").await.unwrap();
+	Migrator::up(&db, None).await.unwrap();
+	let keep = HashSet::from(["tt0238883".to_owned()]);
+
+	let refreshed = refresh::refresh::<Rating>(&db, &first, &keep).await.unwrap();
+	assert_eq!(refreshed.map(|refreshed| refreshed.inserted), Some(3));
+	// the same dump is skipped
+	assert_eq!(refresh::refresh::<Rating>(&db, &first, &keep).await.unwrap(), None);
+
+	let refreshed = refresh::refresh::<Rating>(&db, &second, &keep).await.unwrap();
+	assert_eq!(
+		refreshed,
+		Some(refresh::Refresh {
+			inserted: 1,
+			updated: 1,
+			deleted: 1,
+			kept: 1,
+		})
+	);
+	let ids: Vec<String> = ["tt0124315", "tt0238883", "tt0120094", "tt0113277"]
+		.into_iter()
+		.map(str::to_owned)
+		.collect();
+	let found = rating::find_ratings(&db, &ids).await.unwrap();
+	assert_eq!(found["tt0124315"].votes, 41300);
+	assert!(found.contains_key("tt0238883"));
+	assert!(!found.contains_key("tt0120094"));
+
+	let import = refresh::last_import(&db, "title.ratings").await.unwrap().unwrap();
+	assert_eq!(import.checksum, refresh::checksum(&second).unwrap());
+	assert_eq!(import.rows, 2);
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn refresh_dumps_in_batches() {
+	let dir = std::env::temp_dir().join(format!("media-order-imdb-refresh-dumps-{}", std::process::id()));
+	let write = |name: &str, header: &str, rows: &[String]| {
+		std::fs::create_dir_all(&dir).unwrap();
+		let file = std::fs::File::create(dir.join(name)).unwrap();
+		let mut gzip = GzEncoder::new(file, Compression::default());
+		writeln!(gzip, "{header}").unwrap();
+		for row in rows {
+			writeln!(gzip, "{row}").unwrap();
+		}
+		gzip.finish().unwrap();
+	};
+	let ratings_header = "tconst\taverageRating\tnumVotes";
+	let akas_header = "titleId\tordering\ttitle\tregion\tlanguage\ttypes\tattributes\tisOriginalTitle";
+	// more rows than fit a batch
+	let ratings: Vec<String> = (0..2500).map(|id| format!("tt{id:07}\t7.0\t100")).collect();
+	let akas: Vec<String> = (1..=3)
+		.map(|ordering| format!("tt0124315\t{ordering}\tBrat\tRU\tru\t\\N\t\\N\t0"))
+		.collect();
+	write("title.ratings.tsv.gz", ratings_header, &ratings);
+	write("title.akas.tsv.gz", akas_header, &akas);
+
+	let db = Database::connect("sqlite::memory:").await.unwrap();
+	Migrator::up(&db, None).await.unwrap();
+	let keep = HashSet::from(["tt0000001".to_owned()]);
+	let refreshed = refresh::refresh_dumps(&db, &dir, &keep).await.unwrap();
+	let datasets: Vec<_> = refreshed
+		.iter()
+		.map(|(dataset, refresh)| (*dataset, refresh.inserted))
+		.collect();
+	assert_eq!(datasets, [("title.akas", 3), ("title.ratings", 2500)]);
+
+	// every other rating and the last aka are gone, one rating changed
+	let mut ratings: Vec<String> = ratings.into_iter().step_by(2).collect();
+	ratings[1] = "tt0000002\t8.0\t100".to_owned();
+	write("title.ratings.tsv.gz", ratings_header, &ratings);
+	write("title.akas.tsv.gz", akas_header, &akas[..2]);
+	let refreshed = refresh::refresh_dumps(&db, &dir, &keep).await.unwrap();
+	assert_eq!(refreshed, [
+		("title.akas", refresh::Refresh {
+			deleted: 1,
+			..Default::default()
+		}),
+		("title.ratings", refresh::Refresh {
+			updated: 1,
+			deleted: 1249,
+			kept: 1,
+			..Default::default()
+		}),
+	]);
+	let ids: Vec<String> = ["tt0000001", "tt0000002", "tt0000003"].into_iter().map(str::to_owned).collect();
+	let found = rating::find_ratings(&db, &ids).await.unwrap();
+	assert_eq!(found.len(), 2);
+	assert_eq!(found["tt0000002"].average, 8.0);
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn basics(id: &str, title_type: &str, primary_title: &str, original_title: &str, year: u16) -> TitleBasics {
+	TitleBasics {
+		title_id: id.to_owned(),
+		title_type: title_type.to_owned(),
+		primary_title: primary_title.to_owned(),
+		original_title: original_title.to_owned(),
+		is_adult: false,
+		start_year: Some(year),
+		end_year: None,
+		runtime: None,
+		genres: vec![],
+	}
+}
+
+fn aka(id: &str, ordering: u32, name: &str) -> Title {
+	Title {
+		title_id: id.to_owned(),
+		ordering,
+		name: name.to_owned(),
+		region: None,
+		language: None,
+		types: vec![],
+		attributes: vec![],
+		is_original_title: false,
+	}
+}
+
+#[tokio::test]
+async fn search_normalized_titles() {
+	let db = Database::connect("sqlite::memory:").await.unwrap();
+	Migrator::up(&db, None).await.unwrap();
+	basics::fill_basics_table(&db, &[
+		basics("0133093", "movie", "The Matrix", "The Matrix", 1999),
+		basics("0211915", "movie", "Amélie", "Le fabuleux destin d'Amélie Poulain", 2001),
+		basics("0124315", "movie", "Brother", "Brat", 1997),
+		basics("0238883", "movie", "Brother 2", "Brat 2", 2000),
+		basics("0472580", "movie", "Yolki", "Ёлки", 2010),
+		basics("0386676", "tvSeries", "The Office", "The Office", 2005),
+	])
+	.await
+	.unwrap();
+	title::fill_akas_table(&db, &[aka("0124315", 1, "Брат"), aka("0133093", 2, "Матрица")])
+		.await
+		.unwrap();
+	assert_eq!(search::rebuild_index(&db).await.unwrap(), 12);
+
+	let found = |query: &'static str, filter: SearchFilter| {
+		let db = &db;
+		async move {
+			search::search_titles(db, query, &filter, 3)
+				.await
+				.unwrap()
+				.into_iter()
+				.map(|found| found.tconst)
+				.collect::<Vec<_>>()
+		}
+	};
+	assert_eq!(found("Matrix, The", SearchFilter::default()).await[0], "tt0133093");
+	assert_eq!(found("матрица", SearchFilter::default()).await, ["tt0133093"]);
+	assert_eq!(found("amelie", SearchFilter::default()).await, ["tt0211915"]);
+	assert_eq!(found("Елки", SearchFilter::default()).await, ["tt0472580"]);
+	assert_eq!(found("Brat", SearchFilter::default()).await, ["tt0124315", "tt0238883"]);
+	assert_eq!(found("Brat", SearchFilter::default().with_year(2001, 1)).await, ["tt0238883"]);
+	assert!(found("Brat", SearchFilter::default().with_title_types(&["tvSeries"]))
+		.await
+		.is_empty());
+	assert_eq!(
+		found("office", SearchFilter::default().with_title_types(&["tvSeries"])).await,
+		["tt0386676"]
+	);
+
+	let best = search::search_titles(&db, "The Matrix", &SearchFilter::default(), 1)
+		.await
+		.unwrap();
+	assert_eq!(best[0].similarity, 1.0);
+	assert_eq!(best[0].year, Some(1999));
+}
+
+#[tokio::test]
+async fn search_transliterated_titles() {
+	let db = Database::connect("sqlite::memory:").await.unwrap();
+	Migrator::up(&db, None).await.unwrap();
+	basics::fill_basics_table(&db, &[
+		basics("0073179", "tvMovie", "The Irony of Fate", "The Irony of Fate", 1976),
+		basics("0093341", "movie", "Kin-dza-dza!", "Kin-dza-dza!", 1986),
+		basics("0402910", "movie", "Dead Man's Bluff", "Zhmurki", 2005),
+	])
+	.await
+	.unwrap();
+	title::fill_akas_table(&db, &[aka("0073179", 1, "Ирония судьбы, или С лёгким паром!")])
+		.await
+		.unwrap();
+	search::rebuild_index(&db).await.unwrap();
+
+	let best = |query: &'static str| {
+		let db = &db;
+		async move {
+			search::search_titles(db, query, &SearchFilter::default(), 1)
+				.await
+				.unwrap()
+				.pop()
+				.unwrap()
+		}
+	};
+	// romanized queries find the Cyrillic aka
+	for query in [
+		"Ironiya sudby",
+		"Ironija.sudby",
+		"Ironiâ sudʹby",
+		"Ironiya sud'by ili s lyogkim parom",
+	] {
+		let found = best(query).await;
+		assert_eq!(found.tconst, "tt0073179", "{query}");
+		assert_eq!(found.name, "ирония судьбы или с легким паром");
+	}
+	// and Cyrillic queries the romanized titles
+	assert_eq!(best("Кин-дза-дза!").await.tconst, "tt0093341");
+	let found = best("Жмурки").await;
+	assert_eq!((found.tconst.as_str(), found.similarity), ("tt0402910", 1.0));
+}
+
+#[tokio::test]
+async fn open_configured_database() {
+	let dir = std::env::temp_dir().join(format!("media-order-imdb-config-{}", std::process::id()));
+	std::fs::create_dir_all(&dir).unwrap();
+	let config_path = dir.join("imdb.env");
+	std::fs::write(
+		&config_path,
+		"# local IMDb\nIMDB_DATABASE = imdb.sqlite\nIMDB_DATABASE_SYNCHRONOUS=full\n\nIMDB_DATABASE_POOL_SIZE=2\n",
+	)
+	.unwrap();
+	let config = DatabaseConfig::from_file(&config_path).unwrap();
+	let rating = Rating {
+		title_id: "0124315".to_owned(),
+		average: 7.8,
+		votes: 41300,
+	};
+	let ids = ["tt0124315".to_owned()];
+	assert_eq!(config.location, DatabaseLocation::File(dir.join("imdb.sqlite")));
+	assert_eq!(config.pool_size, 2);
+	std::fs::write(&config_path, "IMDB_DATABASE_POOL_SIZE=many\n").unwrap();
+	assert!(matches!(
+		DatabaseConfig::from_file(&config_path),
+		Err(ImdbError::ConfigError(name, value)) if name == "IMDB_DATABASE_POOL_SIZE" && value == "many"
+	));
+
+	// the in-memory database stays between the queries
+	let db = crate::local::create_database(&DatabaseConfig::memory()).await.unwrap();
+	rating::fill_ratings_table(&db, std::slice::from_ref(&rating)).await.unwrap();
+	crate::local::check_migrations(&db, &DatabaseLocation::Memory).await.unwrap();
+	assert_eq!(rating::find_ratings(&db, &ids).await.unwrap().len(), 1);
+
+	// only created by `create_database`
+	assert!(crate::local::open_database(&config).await.is_err());
+	let db = crate::local::create_database(&config).await.unwrap();
+	rating::fill_ratings_table(&db, &[rating]).await.unwrap();
+	db.close().await.unwrap();
+
+	let db = crate::local::open_database(&config.clone().read_only()).await.unwrap();
+	assert_eq!(rating::find_ratings(&db, &ids).await.unwrap()["tt0124315"].votes, 41300);
+	assert!(db.execute_unprepared("DELETE FROM ratings").await.is_err());
+	db.close().await.unwrap();
+
+	// the database of an older version is reported instead of failing on the missing tables
+	let outdated = DatabaseConfig::file(dir.join("outdated.sqlite")).unwrap();
+	let db = Database::connect(format!("sqlite://{}?mode=rwc", dir.join("outdated.sqlite").display()))
+		.await
+		.unwrap();
+	Migrator::up(&db, Some(2)).await.unwrap();
+	db.close().await.unwrap();
+	match crate::local::open_database(&outdated).await {
+		Err(ImdbError::MigrationPendingError(location, pending)) => {
+			assert_eq!(location, outdated.location.to_string());
+			assert_eq!(pending.len(), 4);
+			assert_eq!(pending[0], "m20261019_000003_create_ratings_and_people");
+		}
+		other => panic!("unexpected {other:?}"),
+	}
+	std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Writes the gzipped dump of the table with the header.
+fn write_dump<T: Table>(dir: &Path, rows: &[&str]) {
+	let path = dir.join(format!("{}.tsv.gz", T::DATASET));
+	let mut gzip = GzEncoder::new(std::fs::File::create(path).unwrap(), Compression::default());
+	writeln!(gzip, "{}", T::COLUMNS.join("\t")).unwrap();
+	for row in rows {
+		writeln!(gzip, "{row}").unwrap();
+	}
+	gzip.finish().unwrap();
+}
+
+#[tokio::test]
+async fn build_filtered_subset() {
+	let dir = std::env::temp_dir().join(format!("media-order-imdb-subset-{}", std::process::id()));
+	std::fs::create_dir_all(&dir).unwrap();
+	write_dump::<TitleBasics>(&dir, &[
+		"tt0124315\tmovie\tBrat\tBrat\t0\t1997\t\\N\t100\tCrime,Drama",
+		"tt0238883\tmovie\tBrother 2\tBrat 2\t0\t2000\t\\N\t127\tCrime,Drama",
+		"tt0073179\ttvMovie\tThe Irony of Fate\tIroniya sudby\t0\t1976\t\\N\t184\tComedy",
+		"tt0386676\ttvSeries\tThe Office\tThe Office\t0\t2005\t2013\t22\tComedy",
+		"tt0664801\ttvEpisode\tPilot\tPilot\t0\t2005\t\\N\t23\tComedy",
+		"tt0056172\tmovie\tLawrence of Arabia\tLawrence of Arabia\t0\t1962\t\\N\t218\tAdventure",
+		"tt9999999\tmovie\tObscure\tObscure\t0\t2001\t\\N\t90\tDrama",
+	]);
+	write_dump::<Rating>(&dir, &[
+		"tt0124315\t7.8\t41300",
+		"tt0238883\t7.2\t30000",
+		"tt0073179\t8.2\t50000",
+		"tt0386676\t9.0\t700000",
+		"tt0664801\t7.4\t8000",
+		"tt0056172\t8.3\t300000",
+		"tt9999999\t5.0\t10",
+	]);
+	write_dump::<Title>(&dir, &[
+		"tt0124315\t1\tБрат\tRU\t\\N\t\\N\t\\N\t0",
+		"tt0124315\t2\tBrother\tUS\t\\N\t\\N\t\\N\t0",
+		"tt0124315\t3\tBrat\t\\N\t\\N\toriginal\t\\N\t1",
+		"tt0386676\t1\tThe Office\tUS\ten\t\\N\t\\N\t0",
+		"tt0386676\t2\tОфис\t\\N\tru\t\\N\t\\N\t0",
+		"tt0073179\t1\tИрония судьбы\tRU\t\\N\t\\N\t\\N\t0",
+	]);
+	write_dump::<Episode>(&dir, &[
+		"tt0664801\ttt0386676\t1\t1",
+		"tt0664802\ttt0386676\t1\t2",
+		"tt0081234\ttt0099999\t1\t1",
+	]);
+	write_dump::<Principal>(&dir, &[
+		"tt0124315\t1\tnm0103614\tactor\t\\N\t[\"Danila\"]",
+		"tt0073179\t1\tnm0573279\tactor\t\\N\t[\"Zhenya\"]",
+	]);
+	// names.basics is missing and skipped
+
+	let filter = subset::SubsetFilter::default()
+		.with_title_types(&["movie", "tvSeries"])
+		.with_min_votes(1000)
+		.with_regions(&["ru"])
+		.with_languages(&["ru"])
+		.with_years(1990..=2010);
+	let target = dir.join("subset.sqlite");
+	let built = subset::build_subset(&dir, &target, &filter).await.unwrap();
+	assert_eq!(built, subset::Subset {
+		titles: 3,
+		akas: 3,
+		episodes: 2,
+		ratings: 3,
+		principals: 1,
+		names: 0,
+		indexed: 7,
+	});
+	assert!(subset::build_subset(&dir, &target, &filter).await.is_err());
+
+	// a single file opened read-only
+	let files: Vec<_> = std::fs::read_dir(&dir)
+		.unwrap()
+		.map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+		.filter(|name| name.starts_with("subset"))
+		.collect();
+	assert_eq!(files, ["subset.sqlite"]);
+	let db = crate::local::open_database(&DatabaseConfig::file(&target).unwrap().read_only())
+		.await
+		.unwrap();
+	let found = search::search_titles(&db, "Офис", &SearchFilter::default(), 1).await.unwrap();
+	assert_eq!(found[0].tconst, "tt0386676");
+	assert_eq!(
+		episode::find_episode(&db, "tt0386676", 1, 2).await.unwrap(),
+		Some("tt0664802".to_owned())
+	);
+	db.close().await.unwrap();
+	std::fs::remove_dir_all(&dir).unwrap();
 }
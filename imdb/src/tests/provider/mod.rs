@@ -0,0 +1,325 @@
+mod raw;
+
+use std::{
+	collections::HashMap,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+};
+
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::TcpListener,
+};
+use url::Url;
+
+use crate::{
+	config::DatabaseConfig,
+	errors::ImdbError,
+	local::{
+		basics::{self, TitleBasics},
+		create_database,
+		episode::{self, Episode},
+		rating::{self, Rating},
+		search,
+		title::{self, Title},
+	},
+	provider::{
+		chain::ProviderChain,
+		fixture::FixtureProvider,
+		http::{HttpTransport, Transport},
+		local::LocalProvider,
+		omdb::OmdbProvider,
+		Artwork,
+		ArtworkKind,
+		EpisodeMetadata,
+		MetadataProvider,
+		TitleMetadata,
+	},
+};
+
+const API_KEY: &str = "secret";
+
+/// Serves the canned responses keyed by the query without the API key, e.g. `i=tt0124315&Season=1`, and
+/// counts the requests.
+async fn mock_server(responses: &[(&str, &str)]) -> (String, Arc<AtomicUsize>) {
+	let responses: HashMap<String, String> = responses
+		.iter()
+		.map(|(query, body)| (query.to_string(), body.to_string()))
+		.collect();
+	let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let base_url = format!("http://{}/", listener.local_addr().unwrap());
+	let requests = Arc::new(AtomicUsize::new(0));
+	let counter = requests.clone();
+	let base = Url::parse(&base_url).unwrap();
+	tokio::spawn(async move {
+		while let Ok((mut stream, _)) = listener.accept().await {
+			counter.fetch_add(1, Ordering::SeqCst);
+			let mut request = vec![];
+			let mut buffer = [0; 1024];
+			while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+				match stream.read(&mut buffer).await {
+					Ok(0) | Err(_) => break,
+					Ok(read) => request.extend_from_slice(&buffer[..read]),
+				}
+			}
+			let target = String::from_utf8_lossy(&request).split(' ').nth(1).unwrap_or("/").to_owned();
+			let url = base.join(&target).unwrap();
+			let mut api_key = None;
+			let mut query = vec![];
+			for (name, value) in url.query_pairs() {
+				match name.as_ref() {
+					"apikey" => api_key = Some(value.into_owned()),
+					_ => query.push(format!("{name}={value}")),
+				}
+			}
+			let (status, body) = match responses.get(&query.join("&")) {
+				_ if api_key.as_deref() != Some(API_KEY) => {
+					("401 Unauthorized", r#"{"Response":"False","Error":"Invalid API key!"}"#)
+				}
+				Some(body) => ("200 OK", body.as_str()),
+				None => ("200 OK", r#"{"Response":"False","Error":"Movie not found!"}"#),
+			};
+			let response = format!(
+				"HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+				body.len()
+			);
+			let _ = stream.write_all(response.as_bytes()).await;
+		}
+	});
+	(base_url, requests)
+}
+
+#[tokio::test]
+async fn omdb_provider_against_mock_server() {
+	let (base_url, requests) = mock_server(&[
+		(
+			"s=Brat&y=1997",
+			r#"{"Search":[
+				{"Title":"Brat 2","Year":"2000","imdbID":"tt0238883","Type":"movie","Poster":"N/A"},
+				{"Title":"Brat","Year":"1997","imdbID":"tt0124315","Type":"movie","Poster":"N/A"}
+			],"totalResults":"2","Response":"True"}"#,
+		),
+		(
+			"i=tt0124315",
+			r#"{"Title":"Brat","Year":"1997","Genre":"Crime, Drama","Poster":"http://posters/brat.jpg",
+				"imdbRating":"7.8","imdbVotes":"41,300","imdbID":"tt0124315","Type":"movie","Response":"True"}"#,
+		),
+		(
+			"i=tt0386676",
+			r#"{"Title":"The Office","Year":"2005–2013","Genre":"Comedy","Poster":"N/A","imdbRating":"9.0",
+				"imdbVotes":"N/A","imdbID":"tt0386676","Type":"series","totalSeasons":"2","Response":"True"}"#,
+		),
+		(
+			"i=tt0386676&Season=1",
+			r#"{"Title":"The Office","Season":"1","Episodes":[
+				{"Title":"Diversity Day","Released":"2005-03-29","Episode":"2","imdbRating":"8.2","imdbID":"tt0664802"},
+				{"Title":"Pilot","Released":"2005-03-24","Episode":"1","imdbRating":"7.4","imdbID":"tt0664801"}
+			],"Response":"True"}"#,
+		),
+	])
+	.await;
+	let omdb = OmdbProvider::new(&base_url, API_KEY).unwrap();
+
+	let found = omdb.search("Brat", Some(1997), 10).await.unwrap();
+	let found: Vec<_> = found.iter().map(|found| (found.tconst.as_str(), found.year)).collect();
+	assert_eq!(found, [("tt0124315", Some(1997)), ("tt0238883", Some(2000))]);
+	assert!(omdb.search("Brat", Some(1990), 10).await.unwrap().is_empty());
+
+	let brat = omdb.get("tt0124315").await.unwrap().unwrap();
+	assert_eq!(brat.genres, ["Crime", "Drama"]);
+	assert_eq!((brat.rating, brat.votes), (Some(7.8), Some(41300)));
+	let office = omdb.get("tt0386676").await.unwrap().unwrap();
+	assert_eq!(
+		(office.title_type.as_deref(), office.year, office.votes),
+		(Some("tvSeries"), Some(2005), None)
+	);
+	assert_eq!(omdb.get("tt0000001").await.unwrap(), None);
+
+	// the missing second season is skipped
+	let episodes = omdb.episodes("tt0386676").await.unwrap();
+	assert_eq!(episodes.len(), 2);
+	assert_eq!(episodes[0], EpisodeMetadata {
+		tconst: "tt0664801".to_owned(),
+		season: 1,
+		episode: 1,
+		title: Some("Pilot".to_owned()),
+	});
+	assert_eq!(omdb.artwork("tt0124315").await.unwrap(), [Artwork {
+		kind: ArtworkKind::Poster,
+		url: "http://posters/brat.jpg".to_owned(),
+	}]);
+	assert!(omdb.artwork("tt0386676").await.unwrap().is_empty());
+	assert_eq!(requests.load(Ordering::SeqCst), 10);
+
+	let unauthorized = OmdbProvider::new(&base_url, "wrong").unwrap();
+	assert!(matches!(
+		unauthorized.get("tt0124315").await,
+		Err(ImdbError::ProviderError(name, _)) if name == "http"
+	));
+}
+
+#[tokio::test]
+async fn http_transport_limits() {
+	let (base_url, _) = mock_server(&[("i=tt0124315", r#"{"Title":"Brat","Response":"True"}"#)]).await;
+	let url = Url::parse(&format!("{base_url}?apikey={API_KEY}&i=tt0124315")).unwrap();
+
+	let body = HttpTransport::new().unwrap().get(&url).await.unwrap();
+	assert_eq!(body, r#"{"Title":"Brat","Response":"True"}"#);
+	// the raw client of the mock servers reads the same
+	assert_eq!(raw::RawTransport::default().get(&url).await.unwrap(), body);
+	let limited = HttpTransport::new().unwrap().with_max_body(16);
+	assert!(matches!(
+		limited.get(&url).await,
+		Err(ImdbError::ProviderError(name, message)) if name == "http" && message.contains("exceeds 16 bytes")
+	));
+}
+
+#[tokio::test]
+async fn local_provider() {
+	let db = create_database(&DatabaseConfig::memory()).await.unwrap();
+	basics::fill_basics_table(&db, &[
+		TitleBasics {
+			title_id: "0124315".to_owned(),
+			title_type: "movie".to_owned(),
+			primary_title: "Brother".to_owned(),
+			original_title: "Brat".to_owned(),
+			is_adult: false,
+			start_year: Some(1997),
+			end_year: None,
+			runtime: Some(100),
+			genres: vec!["Crime".to_owned(), "Drama".to_owned()],
+		},
+		TitleBasics {
+			title_id: "0000100".to_owned(),
+			title_type: "movie".to_owned(),
+			primary_title: "Brat".to_owned(),
+			original_title: "Brat".to_owned(),
+			is_adult: false,
+			start_year: Some(2010),
+			end_year: None,
+			runtime: Some(90),
+			genres: vec![],
+		},
+		TitleBasics {
+			title_id: "0664801".to_owned(),
+			title_type: "tvEpisode".to_owned(),
+			primary_title: "Pilot".to_owned(),
+			original_title: "Pilot".to_owned(),
+			is_adult: false,
+			start_year: Some(2005),
+			end_year: None,
+			runtime: Some(23),
+			genres: vec![],
+		},
+	])
+	.await
+	.unwrap();
+	title::fill_akas_table(&db, &[Title {
+		title_id: "0124315".to_owned(),
+		ordering: 1,
+		name: "Брат".to_owned(),
+		region: Some("RU".to_owned()),
+		language: None,
+		types: vec![],
+		attributes: vec![],
+		is_original_title: false,
+	}])
+	.await
+	.unwrap();
+	rating::fill_ratings_table(&db, &[Rating {
+		title_id: "0124315".to_owned(),
+		average: 7.8,
+		votes: 41300,
+	}])
+	.await
+	.unwrap();
+	episode::fill_episodes_table(&db, &[
+		Episode {
+			title_id: "0664801".to_owned(),
+			parent_id: "0386676".to_owned(),
+			season: Some(1),
+			episode: Some(1),
+		},
+		Episode {
+			title_id: "0664802".to_owned(),
+			parent_id: "0386676".to_owned(),
+			season: Some(1),
+			episode: Some(2),
+		},
+	])
+	.await
+	.unwrap();
+	search::rebuild_index(&db).await.unwrap();
+	let imdb = LocalProvider::new(db);
+
+	let found = imdb.search("Брат", Some(1998), 5).await.unwrap();
+	assert_eq!(found.len(), 1);
+	assert_eq!(
+		(found[0].title.as_str(), found[0].original_title.as_deref(), found[0].rating),
+		("Brother", Some("Brat"), Some(7.8))
+	);
+	assert!(imdb.search("Брат", Some(2005), 5).await.unwrap().is_empty());
+	// the equally similar titles are ranked by popularity
+	let found = imdb.search("Brat", None, 5).await.unwrap();
+	let found: Vec<_> = found.iter().map(|found| found.tconst.as_str()).collect();
+	assert_eq!(found, ["tt0124315", "tt0000100"]);
+	assert_eq!(imdb.get("tt0124315").await.unwrap().unwrap().votes, Some(41300));
+	assert_eq!(imdb.get("tt0000001").await.unwrap(), None);
+	let episodes = imdb.episodes("tt0386676").await.unwrap();
+	let episodes: Vec<_> = episodes
+		.iter()
+		.map(|episode| (episode.episode, episode.title.as_deref()))
+		.collect();
+	assert_eq!(episodes, [(1, Some("Pilot")), (2, None)]);
+	assert!(imdb.artwork("tt0124315").await.unwrap().is_empty());
+}
+
+fn title(tconst: &str, title: &str, year: u16) -> TitleMetadata {
+	TitleMetadata {
+		tconst: tconst.to_owned(),
+		title: title.to_owned(),
+		original_title: None,
+		title_type: Some("movie".to_owned()),
+		year: Some(year),
+		genres: vec![],
+		rating: None,
+		votes: None,
+		source: String::new(),
+		score: 1.0,
+	}
+}
+
+#[tokio::test]
+async fn provider_chain_fallbacks() {
+	let chain = ProviderChain::new()
+		.with(
+			FixtureProvider::new("backup")
+				.with_title(title("tt0124315", "Brat", 1997))
+				.with_title(title("tt0386676", "The Office", 2005))
+				.with_artwork("tt0124315", Artwork {
+					kind: ArtworkKind::Poster,
+					url: "http://posters/brat.jpg".to_owned(),
+				}),
+			1,
+		)
+		.with(FixtureProvider::new("down").with_failure("service unavailable"), 10)
+		.with(FixtureProvider::new("manual").with_title(title("tt0124315", "Brat", 1997)), 5);
+	assert_eq!(chain.names(), ["down", "manual", "backup"]);
+
+	let found = chain.search("Brat", Some(1997), 5).await.unwrap();
+	assert_eq!((found[0].tconst.as_str(), found[0].source.as_str()), ("tt0124315", "manual"));
+	let found = chain.search("Office", None, 5).await.unwrap();
+	assert_eq!((found[0].tconst.as_str(), found[0].source.as_str()), ("tt0386676", "backup"));
+	assert_eq!(chain.get("tt0386676").await.unwrap().unwrap().source, "backup");
+	assert_eq!(chain.artwork("tt0124315").await.unwrap().len(), 1);
+	// answered with nothing, the failure is only logged
+	assert!(chain.episodes("tt0386676").await.unwrap().is_empty());
+
+	let down = ProviderChain::new().with(FixtureProvider::new("down").with_failure("service unavailable"), 0);
+	assert!(matches!(
+		down.search("Brat", None, 5).await,
+		Err(ImdbError::ProviderError(name, message)) if name == "down" && message == "service unavailable"
+	));
+}
@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::TcpStream,
+};
+use url::{Position, Url};
+
+use crate::{
+	errors::Result,
+	provider::http::{error, Transport},
+};
+
+/// HTTP/1.1 over plain TCP without redirects and limits, a minimal client of the mock servers.
+#[derive(Clone, Debug)]
+pub struct RawTransport {
+	timeout: Duration,
+}
+
+impl Default for RawTransport {
+	fn default() -> Self {
+		Self {
+			timeout: Duration::from_secs(10),
+		}
+	}
+}
+
+impl RawTransport {
+	async fn request(host: &str, port: u16, target: &str) -> Result<Vec<u8>> {
+		let mut stream = TcpStream::connect((host, port)).await?;
+		let request = format!(
+			"GET {target} HTTP/1.1\r\nHost: {host}\r\nAccept: application/json\r\nConnection: close\r\n\r\n"
+		);
+		stream.write_all(request.as_bytes()).await?;
+		let mut response = vec![];
+		stream.read_to_end(&mut response).await?;
+		Ok(response)
+	}
+}
+
+#[async_trait]
+impl Transport for RawTransport {
+	async fn get(&self, url: &Url) -> Result<String> {
+		if url.scheme() != "http" {
+			return Err(error(format!("unsupported scheme of {url}")));
+		}
+		let host = url.host_str().ok_or_else(|| error(format!("no host in {url}")))?;
+		let port = url.port_or_known_default().unwrap_or(80);
+		let target = &url[Position::BeforePath..Position::AfterQuery];
+		let response = tokio::time::timeout(self.timeout, Self::request(host, port, target))
+			.await
+			.map_err(|_| error(format!("{url} timed out")))??;
+		parse_response(url, &response)
+	}
+}
+
+fn split_at_crlf(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+	let position = bytes.windows(2).position(|window| window == b"\r\n")?;
+	Some((&bytes[..position], &bytes[position + 2..]))
+}
+
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+	let mut decoded = vec![];
+	loop {
+		let (size, rest) = split_at_crlf(body)?;
+		let size = String::from_utf8_lossy(size);
+		let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+		if size == 0 {
+			return Some(decoded);
+		}
+		decoded.extend_from_slice(rest.get(..size)?);
+		body = rest.get(size + 2..)?;
+	}
+}
+
+/// Body of the successful response, other statuses fail.
+fn parse_response(url: &Url, response: &[u8]) -> Result<String> {
+	let malformed = || error(format!("malformed response of {url}"));
+	let split = response
+		.windows(4)
+		.position(|window| window == b"\r\n\r\n")
+		.ok_or_else(malformed)?;
+	let head = String::from_utf8_lossy(&response[..split]);
+	let body = &response[split + 4..];
+
+	let mut lines = head.lines();
+	let status: u16 = lines
+		.next()
+		.and_then(|line| line.split(' ').nth(1))
+		.and_then(|status| status.parse().ok())
+		.ok_or_else(malformed)?;
+	if !(200..300).contains(&status) {
+		return Err(error(format!("HTTP {status} for {url}")));
+	}
+	let chunked = lines.any(|line| {
+		line.split_once(':').is_some_and(|(name, value)| {
+			name.eq_ignore_ascii_case("transfer-encoding") && value.to_ascii_lowercase().contains("chunked")
+		})
+	});
+	let body = if chunked {
+		decode_chunked(body).ok_or_else(malformed)?
+	} else {
+		body.to_vec()
+	};
+	String::from_utf8(body).map_err(|_| malformed())
+}
+
+#[test]
+fn test_parse_response() {
+	let url = Url::parse("http://localhost/?i=tt0124315").unwrap();
+	let plain = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"Title\":\"Brat\"}";
+	assert_eq!(parse_response(&url, plain).unwrap(), "{\"Title\":\"Brat\"}");
+	let chunked = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n6\r\n{\"Titl\r\na\r\ne\":\"Brat\"}\r\n0\r\n\r\n";
+	assert_eq!(parse_response(&url, chunked).unwrap(), "{\"Title\":\"Brat\"}");
+	assert!(parse_response(&url, b"HTTP/1.1 401 Unauthorized\r\n\r\n").is_err());
+	assert!(parse_response(&url, b"garbage").is_err());
+}